@@ -0,0 +1,286 @@
+use std::ops::Range;
+
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_field::{AbstractField, Field, PrimeField32};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+
+use p3_baby_bear::BabyBear;
+use p3_challenger::{HashChallenger, SerializingChallenger32};
+use p3_commit::ExtensionMmcs;
+use p3_field::extension::BinomialExtensionField;
+use p3_fri::TwoAdicFriPcs;
+use p3_keccak::Keccak256Hash;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_monty_31::dft::RecursiveDft;
+use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher32};
+use p3_uni_stark::{Proof, StarkConfig};
+use tracing::info_span;
+
+use crate::config::{FriParams, MERKLE_ARITY};
+use crate::error::Error;
+use crate::field::{check_value_in_field, FieldKind};
+use crate::proof_info::{ProofStats, ProveStats};
+use crate::timings;
+
+// Range check for BabyBear that commits the value and its 4 big endian byte limbs, 5 columns instead of 32.
+//
+// `p3_uni_stark` has no lookup argument, so every limb is range checked with the vanishing polynomial of
+// its allowed values instead of a byte table lookup. Those constraints have degree 256, so the quotient is
+// split into 256 chunks and the FRI config needs `log_blowup >= 8`. The committed trace is much narrower
+// than `babybear_v1`, but the quotient commitment grows accordingly, so the proof is not smaller overall.
+pub struct BabyBearCompactRangeCheckAir {
+    pub value: u32,
+}
+
+impl BabyBearCompactRangeCheckAir {
+    pub const WIDTH: usize = 5; // value + 4 byte limbs
+}
+
+// Baby Bear Modulus split into big endian bytes
+// 0x78 0x00 0x00 0x01
+const TOP_LIMB_MAX: u32 = 0x78;
+
+impl<F: Field> BaseAir<F> for BabyBearCompactRangeCheckAir {
+    fn width(&self) -> usize {
+        Self::WIDTH
+    }
+}
+
+// Product of `x - i` over `range`, which is zero iff `x` is one of the values in `range`.
+fn vanishing<AB: AirBuilder>(x: AB::Var, range: Range<u32>) -> AB::Expr {
+    let x: AB::Expr = x.into();
+    range.map(|i| x.clone() - AB::Expr::from_canonical_u32(i)).product::<AB::Expr>()
+}
+
+impl<AB: AirBuilder> Air<AB> for BabyBearCompactRangeCheckAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let current_row = main.row_slice(0);
+        let value = current_row[0];
+        let limbs = &current_row[1..5];
+
+        // Making sure the lower limbs are bytes
+        for &limb in &limbs[1..] {
+            builder.assert_zero(vanishing::<AB>(limb, 0..256));
+        }
+
+        // Making sure the top limb is at most the top byte of the modulus
+        builder.assert_zero(vanishing::<AB>(limbs[0], 0..TOP_LIMB_MAX + 1));
+
+        // Assert if the top limb is 0x78, then the remaining limbs have to be zero.
+        let remaining_limbs_sum = limbs[1..].iter().map(|&limb| limb.into()).sum::<AB::Expr>();
+        builder.when(vanishing::<AB>(limbs[0], 0..TOP_LIMB_MAX)).assert_zero(remaining_limbs_sum);
+
+        // Assert the value column is reconstructed from the limbs
+        let mut reconstructed_value = AB::Expr::zero();
+        for i in 0..4 {
+            reconstructed_value += AB::Expr::from_canonical_u32(1 << (8 * (3 - i))) * limbs[i];
+        }
+        builder.assert_eq(value, reconstructed_value);
+
+        // Assert if the committed value matches the original value
+        builder.when_first_row().assert_eq(AB::Expr::from_wrapped_u32(self.value), value);
+    }
+}
+
+pub fn generate_trace<F: Field>(value: u32) -> RowMajorMatrix<F> {
+    let mut row = Vec::with_capacity(5);
+    row.push(F::from_wrapped_u32(value));
+    // Split the value into bytes, in big endian format
+    for i in (0..4).rev() {
+        row.push(F::from_canonical_u32((value >> (8 * i)) & 0xff));
+    }
+    RowMajorMatrix::new(row, 5)
+}
+
+// Like `generate_trace`, but rejects values at or above the field order instead of building bits that can't verify.
+pub fn try_generate_trace<F: PrimeField32>(value: u32) -> Result<RowMajorMatrix<F>, Error> {
+    if value >= F::ORDER_U32 {
+        return Err(Error::ValueOutOfField { field: FieldKind::BabyBear, value: u64::from(value) });
+    }
+    Ok(generate_trace(value))
+}
+
+pub const DEFAULT_FRI_PARAMS: FriParams = FriParams {
+    log_blowup: 8, // the limb constraints have degree 256, so `log_blowup` has to be at least 8
+    num_queries: 100,
+    proof_of_work_bits: 16,
+};
+
+pub type Val = BabyBear;
+pub type Challenge = BinomialExtensionField<Val, 4>;
+
+pub type ByteHash = Keccak256Hash;
+pub type FieldHash = SerializingHasher32<ByteHash>;
+pub type MyCompress = CompressionFunctionFromHasher<ByteHash, MERKLE_ARITY, 32>;
+pub type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+pub type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+pub type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+pub type Dft = RecursiveDft<Val>;
+pub type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+pub type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+fn config(trace_height: usize, params: &FriParams) -> MyConfig {
+    let byte_hash = ByteHash {};
+    let field_hash = FieldHash::new(Keccak256Hash {});
+    let compress = MyCompress::new(byte_hash);
+    let val_mmcs = ValMmcs::new(field_hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+
+    let fri_config = params.fri_config(challenge_mmcs);
+    let dft = Dft::new(trace_height << fri_config.log_blowup);
+
+    let pcs = Pcs::new(dft, val_mmcs, fri_config);
+    MyConfig::new(pcs)
+}
+
+// A fresh challenger with an empty transcript.
+pub fn challenger() -> Challenger {
+    Challenger::from_hasher(vec![], ByteHash {})
+}
+
+pub fn prove(value: u32) -> Proof<MyConfig> {
+    prove_with_params(value, &DEFAULT_FRI_PARAMS)
+}
+
+pub fn prove_with_params(value: u32, params: &FriParams) -> Proof<MyConfig> {
+    prove_with_challenger(value, params, &mut challenger())
+}
+
+// Continues the transcript of `challenger`, binding the proof to everything observed before.
+pub fn prove_with_challenger(value: u32, params: &FriParams, challenger: &mut Challenger) -> Proof<MyConfig> {
+    prove_with_trace_hook(value, params, challenger, None)
+}
+
+// `trace_hook` is called with the generated trace right before it is committed to.
+pub fn prove_with_trace_hook(
+    value: u32,
+    params: &FriParams,
+    challenger: &mut Challenger,
+    trace_hook: Option<&mut dyn FnMut(&RowMajorMatrix<Val>)>,
+) -> Proof<MyConfig> {
+    let air = BabyBearCompactRangeCheckAir { value };
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<Val>(value));
+    let config = config(trace.height(), params);
+
+    if let Some(trace_hook) = trace_hook {
+        trace_hook(&trace);
+    }
+
+    info_span!("commit_trace").in_scope(|| p3_uni_stark::prove(&config, &air, challenger, trace, &vec![]))
+}
+
+pub fn verify(value: u32, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    verify_with_params(value, proof, &DEFAULT_FRI_PARAMS)
+}
+
+pub fn verify_with_params(
+    value: u32,
+    proof: &Proof<MyConfig>,
+    params: &FriParams,
+) -> Result<(), Error> {
+    verify_with_challenger(value, proof, params, &mut challenger())
+}
+
+// `challenger` has to be in the same state the prover's challenger was in.
+pub fn verify_with_challenger(
+    value: u32,
+    proof: &Proof<MyConfig>,
+    params: &FriParams,
+    challenger: &mut Challenger,
+) -> Result<(), Error> {
+    check_value_in_field(FieldKind::BabyBear, u64::from(value))?;
+    let air = BabyBearCompactRangeCheckAir { value };
+    let config = config(1, params);
+
+    p3_uni_stark::verify(&config, &air, challenger, proof, &vec![])
+        .map_err(|e| Error::verification_failed(FieldKind::BabyBear, u64::from(value), e))
+}
+
+// Returns the proof of work stats of the proof, which are also logged, or why the proof didn't verify.
+pub fn prove_and_verify(value: u32) -> Result<ProofStats, Error> {
+    prove_and_verify_with_params(value, &DEFAULT_FRI_PARAMS)
+}
+
+pub fn prove_and_verify_with_params(value: u32, params: &FriParams) -> Result<ProofStats, Error> {
+    timings::init_tracing();
+
+    let proof = info_span!("prove").in_scope(|| prove_with_params(value, params));
+    info_span!("verify")
+        .in_scope(|| verify_with_params(value, &proof, params))?;
+
+    let stats = ProofStats::from_proof(&proof, params)?;
+    tracing::info!("{}", stats);
+    Ok(stats)
+}
+
+/// Returns the proof size and the proving and verifying times, or why the proof didn't verify.
+pub fn prove_and_verify_with_stats(value: u32) -> Result<ProveStats, Error> {
+    ProveStats::measure(
+        || Ok(prove_with_params(value, &DEFAULT_FRI_PARAMS)),
+        |proof| verify_with_params(value, proof, &DEFAULT_FRI_PARAMS),
+    )
+}
+
+/// Whether `value` is in range with this module's AIR, like `prover::check` for the `_v1` provers.
+pub fn check(value: u64) -> bool {
+    if check_value_in_field(FieldKind::BabyBear, value).is_err() {
+        return false;
+    }
+    let value = value as u32;
+    verify_with_params(value, &prove_with_params(value, &DEFAULT_FRI_PARAMS), &DEFAULT_FRI_PARAMS).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::babybear_v1;
+    use crate::debug::check_constraints;
+
+    fn violations(value: u32, trace: &RowMajorMatrix<Val>) -> usize {
+        check_constraints(&BabyBearCompactRangeCheckAir { value }, trace, &[]).len()
+    }
+
+    #[test]
+    fn width_matches_the_const() {
+        let air = BabyBearCompactRangeCheckAir { value: 0 };
+        assert_eq!(<BabyBearCompactRangeCheckAir as BaseAir<Val>>::width(&air), BabyBearCompactRangeCheckAir::WIDTH);
+        assert_eq!(generate_trace::<Val>(0).width(), BabyBearCompactRangeCheckAir::WIDTH);
+    }
+
+    #[test]
+    fn values_below_the_order_are_accepted() {
+        for value in [0, 1, 255, 256, 0x77ff_ffff, Val::ORDER_U32 - 1] {
+            assert_eq!(violations(value, &generate_trace(value)), 0, "{} was rejected", value);
+        }
+        let proof = prove(Val::ORDER_U32 - 1);
+        verify(Val::ORDER_U32 - 1, &proof).unwrap();
+    }
+
+    #[test]
+    fn the_order_is_rejected() {
+        assert_ne!(violations(Val::ORDER_U32, &generate_trace(Val::ORDER_U32)), 0);
+        assert!(matches!(try_generate_trace::<Val>(Val::ORDER_U32), Err(Error::ValueOutOfField { .. })));
+    }
+
+    #[test]
+    fn limbs_above_a_byte_are_rejected() {
+        // 256 written as a single low limb instead of carrying into the next one
+        let forged = RowMajorMatrix::new(
+            vec![Val::from_canonical_u32(256), Val::zero(), Val::zero(), Val::zero(), Val::from_canonical_u32(256)],
+            BabyBearCompactRangeCheckAir::WIDTH,
+        );
+        assert_ne!(violations(256, &forged), 0);
+    }
+
+    #[test]
+    fn proof_size_compared_to_babybear_v1() {
+        // The main trace is 5 columns instead of 32, but the degree 256 limb constraints need a 256 chunk
+        // quotient, which outweighs the narrower trace.
+        assert!(BabyBearCompactRangeCheckAir::WIDTH < babybear_v1::WIDTH);
+        let compact = bincode::serialized_size(&prove(100)).unwrap();
+        let v1 = bincode::serialized_size(&babybear_v1::prove(100)).unwrap();
+        assert!(compact > v1, "compact {} bytes, babybear_v1 {} bytes", compact, v1);
+    }
+}
@@ -0,0 +1,207 @@
+use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+use p3_challenger::{CanObserve, DuplexChallenger};
+use p3_commit::ExtensionMmcs;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractExtensionField, AbstractField, Field, PrimeField32};
+use p3_fri::TwoAdicFriPcs;
+use p3_matrix::Matrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_monty_31::dft::RecursiveDft;
+use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_uni_stark::{Proof, StarkConfig};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde_json::Value;
+use tracing::info_span;
+
+use crate::babybear_v1::{generate_trace, public_values, BabyBearRangeCheckAir, DEFAULT_FRI_PARAMS};
+use crate::config::FriParams;
+use crate::error::Error;
+use crate::field::{check_value_in_field, FieldKind};
+use crate::proof_info::find;
+
+// Number of field elements in a Merkle digest.
+pub const DIGEST_ELEMS: usize = 8;
+
+// Seeds the round constants of the permutation. Prover, verifier and any outer circuit have to derive the same
+// constants, `StdRng` isn't guaranteed to be stable across `rand` versions.
+const PERMUTATION_SEED: u64 = 1;
+
+pub type Val = BabyBear;
+pub type Challenge = BinomialExtensionField<Val, 4>;
+
+pub type Perm = Poseidon2BabyBear<16>;
+pub type MyHash = PaddingFreeSponge<Perm, 16, 8, DIGEST_ELEMS>;
+pub type MyCompress = TruncatedPermutation<Perm, 2, DIGEST_ELEMS, 16>;
+pub type ValMmcs = MerkleTreeMmcs<<Val as Field>::Packing, <Val as Field>::Packing, MyHash, MyCompress, DIGEST_ELEMS>;
+pub type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+pub type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+pub type Dft = RecursiveDft<Val>;
+pub type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+pub type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+pub fn permutation() -> Perm {
+    Perm::new_from_rng_128(&mut StdRng::seed_from_u64(PERMUTATION_SEED))
+}
+
+// The `babybear_v1` AIR and FRI layout with Poseidon2 in place of Keccak for the Merkle trees and the transcript.
+pub fn config(trace_height: usize, params: &FriParams) -> MyConfig {
+    let perm = permutation();
+    let hash = MyHash::new(perm.clone());
+    let compress = MyCompress::new(perm);
+    let val_mmcs = ValMmcs::new(hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+
+    let fri_config = params.fri_config(challenge_mmcs);
+    let dft = Dft::new(trace_height << fri_config.log_blowup);
+
+    let pcs = Pcs::new(dft, val_mmcs, fri_config);
+    MyConfig::new(pcs)
+}
+
+pub fn challenger() -> Challenger {
+    Challenger::new(permutation())
+}
+
+// A challenger that has observed every byte of `domain`, like the Keccak challengers seeded with a domain.
+pub fn domain_challenger(domain: &[u8]) -> Challenger {
+    let mut challenger = challenger();
+    for &byte in domain {
+        challenger.observe(Val::from_canonical_u8(byte));
+    }
+    challenger
+}
+
+// Continues the transcript of `challenger`, binding the proof to everything observed before.
+pub fn prove_with_challenger(value: u32, params: &FriParams, challenger: &mut Challenger) -> Proof<MyConfig> {
+    let air = BabyBearRangeCheckAir { value };
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<Val>(value));
+    let config = config(trace.height(), params);
+
+    let public_values = public_values(value);
+    info_span!("commit_trace").in_scope(|| p3_uni_stark::prove(&config, &air, challenger, trace, &public_values))
+}
+
+// `challenger` has to be in the same state the prover's challenger was in.
+pub fn verify_with_challenger(
+    value: u32,
+    proof: &Proof<MyConfig>,
+    params: &FriParams,
+    challenger: &mut Challenger,
+) -> Result<(), Error> {
+    check_value_in_field(FieldKind::BabyBear, u64::from(value))?;
+    let air = BabyBearRangeCheckAir { value };
+    let config = config(1, params);
+
+    p3_uni_stark::verify(&config, &air, challenger, proof, &public_values(value))
+        .map_err(|e| Error::verification_failed(FieldKind::BabyBear, u64::from(value), e))
+}
+
+/// A `babybear_v1` range check proven with Poseidon2, for verification inside another BabyBear STARK.
+///
+/// This is the starting point for recursive composition: the commitments are Poseidon2 digests and the opened
+/// values extension field elements, ready to be used as inputs of an outer circuit, next to the full proof. An
+/// outer verifier still has to recompute the transcript and check the FRI openings of `proof` in its own AIR.
+pub struct RecursionProof {
+    pub trace_commitment: [Val; DIGEST_ELEMS],
+    pub quotient_commitment: [Val; DIGEST_ELEMS],
+    // The trace row and the next row at the out of domain point.
+    pub trace_local: Vec<Challenge>,
+    pub trace_next: Vec<Challenge>,
+    // One vector per quotient chunk.
+    pub quotient_chunks: Vec<Vec<Challenge>>,
+    pub proof: Proof<MyConfig>,
+}
+
+// Collects every number below `value` in order, the base field elements whatever serde wraps them in.
+fn base_elements(value: &Value, elements: &mut Vec<Val>) -> Result<(), Error> {
+    match value {
+        Value::Number(number) => {
+            let element = number
+                .as_u64()
+                .and_then(|n| u32::try_from(n).ok())
+                .filter(|&n| n < Val::ORDER_U32)
+                .ok_or_else(|| Error::Serialization(format!("{} isn't a BabyBear element", number)))?;
+            elements.push(Val::from_canonical_u32(element));
+        }
+        Value::Array(items) => items.iter().try_for_each(|item| base_elements(item, elements))?,
+        Value::Object(fields) => fields.values().try_for_each(|field| base_elements(field, elements))?,
+        _ => {}
+    }
+    Ok(())
+}
+
+fn field<'a>(value: &'a Value, key: &str) -> Result<&'a Value, Error> {
+    find(value, key).ok_or_else(|| Error::Serialization(format!("proof has no `{}`", key)))
+}
+
+fn digest(value: &Value) -> Result<[Val; DIGEST_ELEMS], Error> {
+    let mut elements = vec![];
+    base_elements(value, &mut elements)?;
+    elements
+        .try_into()
+        .map_err(|elements: Vec<Val>| Error::Serialization(format!("digest has {} elements", elements.len())))
+}
+
+fn challenges(value: &Value) -> Result<Vec<Challenge>, Error> {
+    let mut elements = vec![];
+    base_elements(value, &mut elements)?;
+    let degree = <Challenge as AbstractExtensionField<Val>>::D;
+    if elements.len() % degree != 0 {
+        return Err(Error::Serialization(format!("{} elements aren't extension elements", elements.len())));
+    }
+    Ok(elements.chunks(degree).map(Challenge::from_base_slice).collect())
+}
+
+// The fields of `p3_uni_stark::Proof` aren't public, so they are read from its serde representation like
+// `proof_info` does.
+fn recursion_proof(proof: Proof<MyConfig>) -> Result<RecursionProof, Error> {
+    let value = serde_json::to_value(&proof).map_err(|e| Error::Serialization(e.to_string()))?;
+    let commitments = field(&value, "commitments")?;
+    let opened_values = field(&value, "opened_values")?;
+
+    let quotient_chunks = field(opened_values, "quotient_chunks")?
+        .as_array()
+        .ok_or_else(|| Error::Serialization("quotient chunks aren't a list".to_string()))?
+        .iter()
+        .map(challenges)
+        .collect::<Result<_, _>>()?;
+
+    Ok(RecursionProof {
+        trace_commitment: digest(field(commitments, "trace")?)?,
+        quotient_commitment: digest(field(commitments, "quotient_chunks")?)?,
+        trace_local: challenges(field(opened_values, "trace_local")?)?,
+        trace_next: challenges(field(opened_values, "trace_next")?)?,
+        quotient_chunks,
+        proof,
+    })
+}
+
+/// Proves that the canonical value of `value` is in range, with commitments and openings as field elements.
+pub fn prove_for_recursion(value: Val) -> Result<RecursionProof, Error> {
+    recursion_proof(prove_with_challenger(value.as_canonical_u32(), &DEFAULT_FRI_PARAMS, &mut challenger()))
+}
+
+// Verifies `proof.proof` natively, the other fields are derived from it.
+pub fn verify_for_recursion(value: Val, proof: &RecursionProof) -> Result<(), Error> {
+    verify_with_challenger(value.as_canonical_u32(), &proof.proof, &DEFAULT_FRI_PARAMS, &mut challenger())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::babybear_v1::WIDTH;
+
+    #[test]
+    fn proof_exposes_commitments_and_openings() {
+        let value = Val::from_canonical_u32(100);
+        let proof = prove_for_recursion(value).unwrap();
+        verify_for_recursion(value, &proof).unwrap();
+        assert!(verify_for_recursion(Val::from_canonical_u32(101), &proof).is_err());
+
+        assert_ne!(proof.trace_commitment, proof.quotient_commitment);
+        assert_eq!(proof.trace_local.len(), WIDTH);
+        assert_eq!(proof.trace_next.len(), WIDTH);
+        assert!(!proof.quotient_chunks.is_empty());
+    }
+}
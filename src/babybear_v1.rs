@@ -1,5 +1,5 @@
-use p3_air::{Air, AirBuilder, BaseAir};
-use p3_field::{AbstractField, Field};
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+use p3_field::{AbstractField, Field, PrimeField32};
 use p3_matrix::Matrix;
 use p3_matrix::dense::RowMajorMatrix;
 
@@ -7,62 +7,86 @@ use p3_baby_bear::BabyBear;
 use p3_challenger::{HashChallenger, SerializingChallenger32};
 use p3_commit::ExtensionMmcs;
 use p3_field::extension::BinomialExtensionField;
-use p3_fri::{FriConfig, TwoAdicFriPcs};
+use p3_fri::TwoAdicFriPcs;
 use p3_keccak::Keccak256Hash;
 use p3_merkle_tree::MerkleTreeMmcs;
 use p3_monty_31::dft::RecursiveDft;
 use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher32};
-use p3_uni_stark::{prove, verify, StarkConfig};
-use tracing_forest::util::LevelFilter;
-use tracing_forest::ForestLayer;
-use tracing_subscriber::layer::SubscriberExt;
-use tracing_subscriber::util::SubscriberInitExt;
-use tracing_subscriber::{EnvFilter, Registry};
+use p3_uni_stark::{Proof, StarkConfig};
+use tracing::info_span;
+
+use crate::config::{FriParams, MERKLE_ARITY};
+use crate::error::Error;
+use crate::field::{check_value_in_field, FieldKind};
+use crate::modulus::{and_chain, eval_modulus_boundary_degree_2, BABYBEAR};
+use crate::proof_info::{ProofStats, ProveStats};
+use crate::prover;
 
 pub struct BabyBearRangeCheckAir {
     pub value: u32, // define constraint input, value is assigned to check against the reconstructed value.
 }
 
-// Baby Bear Modulus in big endian format
+// Number of committed AND columns after the 32 bits, the running AND of the 4 top one bits.
+const AND_COLUMNS: usize = BABYBEAR.top_ones - 1;
+pub(crate) const WIDTH: usize = 32 + AND_COLUMNS;
+
+impl BabyBearRangeCheckAir {
+    pub const WIDTH: usize = WIDTH;
+}
+
+// Baby Bear Modulus in big endian format, see `modulus::BABYBEAR`
 // 01111000 00000000 00000000 00000001
 impl<F: Field> BaseAir<F> for BabyBearRangeCheckAir {
     fn width(&self) -> usize {
-        32
+        Self::WIDTH
     }
 }
 
-impl<AB: AirBuilder> Air<AB> for BabyBearRangeCheckAir {
+impl<AB: AirBuilderWithPublicValues> Air<AB> for BabyBearRangeCheckAir {
     fn eval(&self, builder: &mut AB) {
         let main = builder.main();
         let current_row = main.row_slice(0);
 
-        // Assert that the most significant bit is zero
-        builder.assert_eq(current_row[0], AB::Expr::zero());
-
-        // Value to check if the 1st to 4th bits are all one
-        let upper_bits_product = current_row[1..5].iter().map(|&bit| bit.into()).product::<AB::Expr>();
-        // Value to check if the sum of the remaining bits is zero, only if `upper_bits_product` is 1.
-        let remaining_bits_sum = current_row[5..32].iter().map(|&bit | bit.into()).sum::<AB::Expr>();
-        
-        // Assert if the 1st to 4th bits are all one, then `remaining_bits_sum` has to be zero.
-        builder.when(upper_bits_product.clone()).assert_zero(remaining_bits_sum);
-
-        // initializing the `reconstructed_value`
-        let mut reconstructed_value = AB::Expr::zero();
-        for i in 0..32 {
-            let bit = current_row[i];
-            // Making sure every bit is either 0 or 1
-            builder.assert_bool(bit); 
-            reconstructed_value += AB::Expr::from_wrapped_u32(1 << (31-i)) * bit; // using `from_wrapped_u32` to make sure the value is in range of 32 bits.
-        }
+        // The public value is the value, see `public_values`
+        let value: AB::Expr = builder.public_values()[0].into();
+        builder.assert_eq(value.clone(), AB::Expr::from_wrapped_u32(self.value));
+
+        let reconstructed_value = eval_value_columns(builder, &current_row);
 
         // Assert if the reconstructed value matches the original value
-        builder.when_first_row().assert_eq(AB::Expr::from_wrapped_u32(self.value), reconstructed_value);
+        builder.when_first_row().assert_eq(value, reconstructed_value);
     }
 }
 
-pub fn generate_trace<F: Field>(value: u32) -> RowMajorMatrix<F> {
-    let mut bits = Vec::with_capacity(32); // 32 bits per row
+// The weights of the 32 big endian bits, `2^31` down to `1`.
+pub(crate) fn bit_weights<F: AbstractField>() -> [F; 32] {
+    core::array::from_fn(|i| F::from_wrapped_u32(1 << (31 - i))) // using `from_wrapped_u32` to make sure the value is in range of 32 bits.
+}
+
+// Constrains `WIDTH` columns laid out by `value_columns` to a value below the modulus and returns the value.
+pub(crate) fn eval_value_columns<AB: AirBuilder>(builder: &mut AB, columns: &[AB::Var]) -> AB::Expr {
+    // Making sure every bit is either 0 or 1. Every constraint has to vanish, so their order doesn't matter for
+    // soundness, but the boundary and the reconstruction below only mean something for boolean cells: a single
+    // cell holding e.g. `(p + 7) / 2` at weight 2 reconstructs to 7 by wrapping around the modulus.
+    for &bit in &columns[..32] {
+        builder.assert_bool(bit);
+    }
+
+    // Assert that the most significant bit is zero, and if the 1st to 4th bits are all one then the
+    // remaining bits are zero. The AND columns keep every constraint at degree 2.
+    eval_modulus_boundary_degree_2(builder, &columns[..32], &columns[32..WIDTH], &BABYBEAR);
+
+    // initializing the `reconstructed_value`
+    let mut reconstructed_value = AB::Expr::zero();
+    for (&bit, weight) in columns[..32].iter().zip(bit_weights::<AB::F>()) {
+        reconstructed_value += AB::Expr::from(bit) * weight;
+    }
+    reconstructed_value
+}
+
+// The 32 bits of `value` followed by the AND columns.
+pub(crate) fn value_columns<F: Field>(value: u32) -> Vec<F> {
+    let mut bits = Vec::with_capacity(WIDTH); // 32 bits and the AND columns per value
     // Convert the value to binary, in big endian format
     for i in (0..32).rev() {
         if (value & (1 << i)) != 0 {
@@ -71,59 +95,281 @@ pub fn generate_trace<F: Field>(value: u32) -> RowMajorMatrix<F> {
             bits.push(F::zero());
         }
     }
-    RowMajorMatrix::new(bits, 32)
+    let ands = and_chain(&bits, &BABYBEAR);
+    bits.extend(ands);
+    bits
+}
+
+// The trace is a single row, the 32 bits followed by the AND columns. Every constraint has degree 2, so the
+// quotient has degree 1 and `log_blowup: 1` covers it even at height 1. The tests below prove and verify at
+// that height.
+pub fn generate_trace<F: Field>(value: u32) -> RowMajorMatrix<F> {
+    RowMajorMatrix::new(value_columns(value), WIDTH)
 }
 
-pub fn prove_and_verify<F: Field>(value: u32) {
-    let env_filter = EnvFilter::builder()
-        .with_default_directive(LevelFilter::INFO.into())
-        .from_env_lossy();
+// Like `generate_trace`, but rejects values at or above the field order instead of building bits that can't verify.
+pub fn try_generate_trace<F: PrimeField32>(value: u32) -> Result<RowMajorMatrix<F>, Error> {
+    if value >= F::ORDER_U32 {
+        return Err(Error::ValueOutOfField { field: FieldKind::BabyBear, value: u64::from(value) });
+    }
+    Ok(generate_trace(value))
+}
+
+pub const DEFAULT_FRI_PARAMS: FriParams = FriParams {
+    log_blowup: 1,
+    num_queries: 100,
+    proof_of_work_bits: 16,
+};
+
+// Smallest proofs at 100 conjectured bits of security, `4 * 21 + 16`, see `config::ProofSizePreset`.
+pub const MINIMAL_FRI_PARAMS: FriParams = FriParams {
+    log_blowup: 4,
+    num_queries: 21,
+    proof_of_work_bits: 16,
+};
+
+pub type Val = BabyBear;
+pub type Challenge = BinomialExtensionField<Val, 4>;
 
-    Registry::default()
-        .with(env_filter)
-        .with(ForestLayer::default())
-        .init();
+pub type ByteHash = Keccak256Hash;
+pub type FieldHash = SerializingHasher32<ByteHash>;
+pub type MyCompress = CompressionFunctionFromHasher<ByteHash, MERKLE_ARITY, 32>;
+pub type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+pub type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+pub type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+pub type Dft = RecursiveDft<Val>;
+pub type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+pub type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
 
-    type Val = BabyBear;
-    type Challenge = BinomialExtensionField<Val, 4>;
+// Nameable outside of this module, e.g. to store proofs.
+pub type BabyBearConfig = MyConfig;
+pub type BabyBearProof = Proof<MyConfig>;
 
-    type ByteHash = Keccak256Hash;
-    type FieldHash = SerializingHasher32<ByteHash>;
+// `trace_height` is only used to size the DFT twiddles, the verifier never runs a DFT.
+pub fn config(trace_height: usize, params: &FriParams) -> MyConfig {
     let byte_hash = ByteHash {};
     let field_hash = FieldHash::new(Keccak256Hash {});
-
-    type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
     let compress = MyCompress::new(byte_hash);
-
-    type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
     let val_mmcs = ValMmcs::new(field_hash, compress);
-
-    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
     let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
 
-    type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+    let fri_config = params.fri_config(challenge_mmcs);
+    let dft = Dft::new(trace_height << fri_config.log_blowup);
 
-    let air = BabyBearRangeCheckAir { value };
-    let trace = generate_trace::<Val>( value);
-
-    let fri_config = FriConfig {
-        log_blowup: 2,
-        num_queries: 100,
-        proof_of_work_bits: 16,
-        mmcs: challenge_mmcs,
-    };
-    type Dft = RecursiveDft<Val>;
-    let dft = Dft::new(trace.height() << fri_config.log_blowup);
-
-    type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
     let pcs = Pcs::new(dft, val_mmcs, fri_config);
+    MyConfig::new(pcs)
+}
+
+// A fresh challenger with an empty transcript.
+pub fn challenger() -> Challenger {
+    Challenger::from_hasher(vec![], ByteHash {})
+}
+
+/// Proves `value < p` with the default FRI parameters and returns the proof for `verify`, e.g. after it was
+/// serialized and sent elsewhere. `prove_and_verify` runs both and only keeps the stats.
+pub fn prove(value: u32) -> Proof<MyConfig> {
+    prove_with_params(value, &DEFAULT_FRI_PARAMS)
+}
+
+// Runs `prove` on tokio's blocking thread pool so it doesn't stall the async runtime. A panic in the prover is
+// resumed in the awaiting task.
+#[cfg(feature = "async")]
+pub async fn prove_async(value: u32) -> Proof<MyConfig> {
+    match tokio::task::spawn_blocking(move || prove(value)).await {
+        Ok(proof) => proof,
+        Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+        Err(e) => panic!("proving task didn't finish: {}", e),
+    }
+}
+
+pub fn prove_with_params(value: u32, params: &FriParams) -> Proof<MyConfig> {
+    prove_with_challenger(value, &public_values(value), params, &mut challenger())
+}
 
-    type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
-    let config = MyConfig::new(pcs);
+// The public values of the range check of `value`, the value itself reduced into the field like the AIR does.
+pub fn public_values(value: u32) -> Vec<Val> {
+    vec![Val::from_wrapped_u32(value)]
+}
+
+// Continues the transcript of `challenger`, binding the proof to everything observed before.
+pub fn prove_with_challenger(
+    value: u32,
+    public_values: &[Val],
+    params: &FriParams,
+    challenger: &mut Challenger,
+) -> Proof<MyConfig> {
+    prove_with_trace_hook(value, public_values, params, challenger, None)
+}
 
-    let mut challenger = Challenger::from_hasher(vec![], byte_hash);
-    let proof = prove(&config, &air, &mut challenger, trace, &vec![]);
+// `trace_hook` is called with the generated trace right before it is committed to.
+pub fn prove_with_trace_hook(
+    value: u32,
+    public_values: &[Val],
+    params: &FriParams,
+    challenger: &mut Challenger,
+    trace_hook: Option<&mut dyn FnMut(&RowMajorMatrix<Val>)>,
+) -> Proof<MyConfig> {
+    let air = BabyBearRangeCheckAir { value };
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<Val>(value));
+    let config = config(trace.height(), params);
+
+    if let Some(trace_hook) = trace_hook {
+        trace_hook(&trace);
+    }
+
+    // `commit_trace` covers committing to the trace and quotient as well as the FRI opening proof.
+    info_span!("commit_trace").in_scope(|| p3_uni_stark::prove(&config, &air, challenger, trace, public_values))
+}
 
-    let mut challenger = Challenger::from_hasher(vec![], byte_hash);
-    let _ = verify(&config, &air, &mut challenger, &proof, &vec![]).expect("verification failed");
-}
\ No newline at end of file
+/// The bincode encoding of `proof`, without the field and FRI parameter header of `serialize::serialize_tagged`,
+/// so both sides have to agree on them out of band.
+pub fn serialize_proof(proof: &Proof<MyConfig>) -> Vec<u8> {
+    bincode::serialize(proof).expect("proofs always serialize")
+}
+
+pub fn deserialize_proof(bytes: &[u8]) -> Result<Proof<MyConfig>, Error> {
+    bincode::deserialize(bytes).map_err(|e| Error::Serialization(e.to_string()))
+}
+
+pub fn verify(value: u32, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    verify_with_params(value, proof, &DEFAULT_FRI_PARAMS)
+}
+
+pub fn verify_with_params(
+    value: u32,
+    proof: &Proof<MyConfig>,
+    params: &FriParams,
+) -> Result<(), Error> {
+    verify_with_challenger(value, &public_values(value), proof, params, &mut challenger())
+}
+
+// `challenger` has to be in the same state the prover's challenger was in, and `public_values` the ones the proof
+// was made with.
+pub fn verify_with_challenger(
+    value: u32,
+    public_values: &[Val],
+    proof: &Proof<MyConfig>,
+    params: &FriParams,
+    challenger: &mut Challenger,
+) -> Result<(), Error> {
+    check_value_in_field(FieldKind::BabyBear, u64::from(value))?;
+    let air = BabyBearRangeCheckAir { value };
+    let config = config(1, params);
+
+    p3_uni_stark::verify(&config, &air, challenger, proof, public_values)
+        .map_err(|e| Error::verification_failed(FieldKind::BabyBear, u64::from(value), e))
+}
+
+// Returns the proof of work stats of the proof, which are also logged, or why the proof didn't verify.
+pub fn prove_and_verify(value: u32) -> Result<ProofStats, Error> {
+    prove_and_verify_with_params(value, &DEFAULT_FRI_PARAMS)
+}
+
+pub fn prove_and_verify_with_params(value: u32, params: &FriParams) -> Result<ProofStats, Error> {
+    prover::prove_and_verify_with_params::<Val>(u64::from(value), params)
+}
+
+pub fn prove_and_verify_with_stats(value: u32) -> Result<ProveStats, Error> {
+    prover::prove_and_verify_with_stats::<Val>(u64::from(value))
+}
+
+/// Whether `value` is in range, see `prover::check`.
+pub fn check(value: u64) -> bool {
+    prover::check::<Val>(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p3_field::PrimeField32;
+    use p3_uni_stark::{get_log_quotient_degree, get_max_constraint_degree};
+
+    #[test]
+    fn prove_and_verify_runs_repeatedly() {
+        // Both calls try to install the tracing subscriber, only the first one does
+        prove_and_verify(1).unwrap();
+        prove_and_verify(2).unwrap();
+    }
+
+    #[test]
+    fn proof_is_kept_for_later_verification() {
+        let bytes = serialize_proof(&prove(100));
+        let proof = deserialize_proof(&bytes).unwrap();
+        verify(100, &proof).unwrap();
+        assert!(verify(101, &proof).is_err());
+
+        assert!(matches!(deserialize_proof(&bytes[..bytes.len() / 2]), Err(Error::Serialization(_))));
+    }
+
+    #[test]
+    fn width_matches_the_const() {
+        let air = BabyBearRangeCheckAir { value: 0 };
+        assert_eq!(<BabyBearRangeCheckAir as BaseAir<Val>>::width(&air), BabyBearRangeCheckAir::WIDTH);
+    }
+
+    #[test]
+    fn constraint_degree_fits_the_blowup() {
+        let air = BabyBearRangeCheckAir { value: 0 };
+        assert_eq!(get_max_constraint_degree::<Val, _>(&air, 1), 2);
+        assert!(get_log_quotient_degree::<Val, _>(&air, 1) <= DEFAULT_FRI_PARAMS.log_blowup);
+    }
+
+    #[test]
+    fn height_one_trace_proves_and_verifies() {
+        for value in [0, 1, Val::ORDER_U32 - 1] {
+            assert_eq!(generate_trace::<Val>(value).height(), 1);
+            let proof = prove(value);
+            assert!(verify(value, &proof).is_ok(), "{} didn't verify", value);
+        }
+    }
+
+    #[test]
+    fn trace_hook_sees_the_committed_trace() {
+        let mut shapes = vec![];
+        let proof = prove_with_trace_hook(
+            100,
+            &public_values(100),
+            &DEFAULT_FRI_PARAMS,
+            &mut challenger(),
+            Some(&mut |trace: &RowMajorMatrix<Val>| shapes.push((trace.width(), trace.height()))),
+        );
+        assert_eq!(shapes, vec![(WIDTH, 1)]);
+        assert!(verify(100, &proof).is_ok());
+    }
+
+    #[test]
+    fn proof_is_bound_to_the_public_value() {
+        let proof = prove(100);
+        let params = &DEFAULT_FRI_PARAMS;
+        assert!(verify_with_challenger(100, &public_values(100), &proof, params, &mut challenger()).is_ok());
+        assert!(verify_with_challenger(100, &public_values(101), &proof, params, &mut challenger()).is_err());
+    }
+
+    #[test]
+    fn try_generate_trace_rejects_values_above_the_order() {
+        assert!(try_generate_trace::<Val>(Val::ORDER_U32 - 1).is_ok());
+        assert!(matches!(try_generate_trace::<Val>(Val::ORDER_U32), Err(Error::ValueOutOfField { .. })));
+        assert!(matches!(try_generate_trace::<Val>(u32::MAX), Err(Error::ValueOutOfField { .. })));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_proof_verifies() {
+        fn assert_send<T: Send>(_: &T) {}
+        let future = prove_async(100);
+        assert_send(&future);
+
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let proof = runtime.block_on(future);
+        assert!(verify(100, &proof).is_ok());
+    }
+
+    #[test]
+    fn bit_weights_reconstruct_the_value() {
+        let value = BabyBear::ORDER_U32 - 1;
+        let bits = value_columns::<BabyBear>(value);
+        let weights = bit_weights::<BabyBear>();
+        let reconstructed: BabyBear = bits.iter().zip(weights).map(|(&bit, weight)| bit * weight).sum();
+        assert_eq!(reconstructed, BabyBear::from_canonical_u32(value));
+    }
+}
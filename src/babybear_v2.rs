@@ -1,5 +1,5 @@
 use p3_air::{Air, AirBuilder, BaseAir};
-use p3_field::{AbstractField, Field};
+use p3_field::{AbstractField, Field, PrimeField32};
 use p3_matrix::Matrix;
 use p3_matrix::dense::RowMajorMatrix;
 
@@ -7,17 +7,25 @@ use p3_baby_bear::BabyBear;
 use p3_challenger::{HashChallenger, SerializingChallenger32};
 use p3_commit::ExtensionMmcs;
 use p3_field::extension::BinomialExtensionField;
-use p3_fri::{FriConfig, TwoAdicFriPcs};
+use p3_fri::TwoAdicFriPcs;
 use p3_keccak::Keccak256Hash;
 use p3_merkle_tree::MerkleTreeMmcs;
 use p3_monty_31::dft::RecursiveDft;
 use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher32};
-use p3_uni_stark::{prove, verify, StarkConfig};
-use tracing_forest::util::LevelFilter;
-use tracing_forest::ForestLayer;
-use tracing_subscriber::layer::SubscriberExt;
-use tracing_subscriber::util::SubscriberInitExt;
-use tracing_subscriber::{EnvFilter, Registry};
+use p3_uni_stark::{Proof, StarkConfig};
+use tracing::info_span;
+
+use crate::config::{FriParams, MERKLE_ARITY};
+use crate::error::Error;
+use crate::field::{check_value_in_field, FieldKind};
+use crate::modulus::BABYBEAR;
+use crate::proof_info::{ProofStats, ProveStats};
+use crate::timings;
+
+// Index of the first bit after the four top one bits of the modulus, i.e. the 4th bit is `TAIL - 1`.
+const TAIL: usize = BABYBEAR.tail_start();
+// The AND chain below is written out for exactly four top one bits.
+const _: () = assert!(BABYBEAR.top_ones == 4);
 
 pub struct BabyBearRangeCheckBitDecompositionAir<T> {
     // The original value to check.
@@ -33,11 +41,53 @@ pub struct BabyBearRangeCheckBitDecompositionAir<T> {
     pub and_most_sig_byte_decomp_4_to_1: T,
 }
 
-// Baby Bear Modulus in big endian format
+impl<F: Field> BabyBearRangeCheckBitDecompositionAir<F> {
+    pub const WIDTH: usize = 32;
+
+    // The AIR for `value` with the AND helpers derived from its bits, so they always match `generate_trace_and_inputs`.
+    pub fn new(value: u32) -> Self {
+        let (and_most_sig_byte_decomp_4_to_3, and_most_sig_byte_decomp_4_to_2, and_most_sig_byte_decomp_4_to_1) =
+            and_inputs(value);
+        Self {
+            value,
+            and_most_sig_byte_decomp_4_to_3,
+            and_most_sig_byte_decomp_4_to_2,
+            and_most_sig_byte_decomp_4_to_1,
+        }
+    }
+
+    /// Returns `Error::InconsistentWitness` unless the AND helpers are the products of the top bits committed in the
+    /// first row of `trace`. The helpers are constants of the AIR, so mismatched ones make every proof unverifiable.
+    pub fn check_witness(&self, trace: &RowMajorMatrix<F>) -> Result<(), Error> {
+        if trace.width() != 32 || trace.height() == 0 {
+            return Err(Error::InconsistentWitness(format!("trace is {}x{}", trace.height(), trace.width())));
+        }
+        let bits = &trace.values[..32];
+        let expected_4_to_3 = bits[TAIL - 1] * bits[TAIL - 2];
+        let expected_4_to_2 = expected_4_to_3 * bits[TAIL - 3];
+        let expected_4_to_1 = expected_4_to_2 * bits[TAIL - 4];
+        let helpers = [
+            (self.and_most_sig_byte_decomp_4_to_3, expected_4_to_3, "4_to_3"),
+            (self.and_most_sig_byte_decomp_4_to_2, expected_4_to_2, "4_to_2"),
+            (self.and_most_sig_byte_decomp_4_to_1, expected_4_to_1, "4_to_1"),
+        ];
+        for (helper, expected, name) in helpers {
+            if helper != expected {
+                return Err(Error::InconsistentWitness(format!(
+                    "and_most_sig_byte_decomp_{} is {} but the trace bits give {}",
+                    name, helper, expected
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+// Baby Bear Modulus in big endian format, see `modulus::BABYBEAR`
 // 01111000 00000000 00000000 00000001
 impl<F: Field> BaseAir<F> for BabyBearRangeCheckBitDecompositionAir<F> {
     fn width(&self) -> usize {
-        32
+        Self::WIDTH
     }
 }
 
@@ -50,16 +100,18 @@ where
         let main = builder.main();
         let current_row = main.row_slice(0);
 
-        // Assert that the most significant bit is zero
-        builder.assert_eq(current_row[0], AB::Expr::zero());
+        // Assert that the leading bits are zero
+        for &bit in &current_row[..BABYBEAR.leading_zeros] {
+            builder.assert_eq(bit, AB::Expr::zero());
+        }
 
         // Value to check if the 1st to 4th bits are all one
-        builder.assert_eq(AB::Expr::from(self.and_most_sig_byte_decomp_4_to_3), current_row[4] * current_row[3]);
-        builder.assert_eq(AB::Expr::from(self.and_most_sig_byte_decomp_4_to_2), AB::Expr::from(self.and_most_sig_byte_decomp_4_to_3) * current_row[2]);
-        builder.assert_eq(AB::Expr::from(self.and_most_sig_byte_decomp_4_to_1), AB::Expr::from(self.and_most_sig_byte_decomp_4_to_2) * current_row[1]);
+        builder.assert_eq(AB::Expr::from(self.and_most_sig_byte_decomp_4_to_3), current_row[TAIL - 1] * current_row[TAIL - 2]);
+        builder.assert_eq(AB::Expr::from(self.and_most_sig_byte_decomp_4_to_2), AB::Expr::from(self.and_most_sig_byte_decomp_4_to_3) * current_row[TAIL - 3]);
+        builder.assert_eq(AB::Expr::from(self.and_most_sig_byte_decomp_4_to_1), AB::Expr::from(self.and_most_sig_byte_decomp_4_to_2) * current_row[TAIL - 4]);
 
         // Value to check if the sum of the remaining bits is zero, only if `and_most_sig_byte_decomp_4_to_1` is 1.
-        let remaining_bits_sum = current_row[5..32].iter().map(|&bit| bit.into()).sum::<AB::Expr>();
+        let remaining_bits_sum = current_row[TAIL..BABYBEAR.width].iter().map(|&bit| bit.into()).sum::<AB::Expr>();
 
         // Assert if the 2nd to 5th bits are all one, then `remaining_bits_sum` has to be zero.
         builder.when(AB::Expr::from(self.and_most_sig_byte_decomp_4_to_1)).assert_zero(remaining_bits_sum);
@@ -89,61 +141,198 @@ pub fn generate_trace_and_inputs<F: Field>(value: u32) -> (RowMajorMatrix<F>, F,
     let bits_clone = bits.clone();
     (
         RowMajorMatrix::new(bits, 32), 
-        bits_clone[4] * bits_clone[3], 
-        bits_clone[4] * bits_clone[3] * bits_clone[2], 
-        bits_clone[4] * bits_clone[3] * bits_clone[2] * bits_clone[1]
+        bits_clone[TAIL - 1] * bits_clone[TAIL - 2], 
+        bits_clone[TAIL - 1] * bits_clone[TAIL - 2] * bits_clone[TAIL - 3], 
+        bits_clone[TAIL - 1] * bits_clone[TAIL - 2] * bits_clone[TAIL - 3] * bits_clone[TAIL - 4]
     )
 }
-pub fn prove_and_verify<F: Field>(value: u32) {
-    let env_filter = EnvFilter::builder()
-        .with_default_directive(LevelFilter::INFO.into())
-        .from_env_lossy();
 
-    Registry::default()
-        .with(env_filter)
-        .with(ForestLayer::default())
-        .init();
+// Like `generate_trace_and_inputs`, but rejects values at or above the field order instead of building bits
+// that can't verify.
+pub fn try_generate_trace_and_inputs<F: PrimeField32>(value: u32) -> Result<(RowMajorMatrix<F>, F, F, F), Error> {
+    if value >= F::ORDER_U32 {
+        return Err(Error::ValueOutOfField { field: FieldKind::BabyBear, value: u64::from(value) });
+    }
+    Ok(generate_trace_and_inputs(value))
+}
+
+// The AND helpers only depend on the value, so the verifier can derive them without building the trace.
+fn and_inputs<F: Field>(value: u32) -> (F, F, F) {
+    let bit = |i: usize| F::from_bool((value >> (31 - i)) & 1 == 1);
+    let and_most_sig_byte_decomp_4_to_3 = bit(TAIL - 1) * bit(TAIL - 2);
+    let and_most_sig_byte_decomp_4_to_2 = and_most_sig_byte_decomp_4_to_3 * bit(TAIL - 3);
+    let and_most_sig_byte_decomp_4_to_1 = and_most_sig_byte_decomp_4_to_2 * bit(TAIL - 4);
+    (and_most_sig_byte_decomp_4_to_3, and_most_sig_byte_decomp_4_to_2, and_most_sig_byte_decomp_4_to_1)
+}
 
-    type Val = BabyBear;
-    type Challenge = BinomialExtensionField<Val, 4>;
+pub const DEFAULT_FRI_PARAMS: FriParams = FriParams {
+    log_blowup: 1,
+    num_queries: 100,
+    proof_of_work_bits: 16,
+};
 
-    type ByteHash = Keccak256Hash;
-    type FieldHash = SerializingHasher32<ByteHash>;
+pub type Val = BabyBear;
+pub type Challenge = BinomialExtensionField<Val, 4>;
+
+pub type ByteHash = Keccak256Hash;
+pub type FieldHash = SerializingHasher32<ByteHash>;
+pub type MyCompress = CompressionFunctionFromHasher<ByteHash, MERKLE_ARITY, 32>;
+pub type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+pub type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+pub type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+pub type Dft = RecursiveDft<Val>;
+pub type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+pub type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+// `trace_height` is only used to size the DFT twiddles, the verifier never runs a DFT.
+pub fn config(trace_height: usize, params: &FriParams) -> MyConfig {
     let byte_hash = ByteHash {};
     let field_hash = FieldHash::new(Keccak256Hash {});
-
-    type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
     let compress = MyCompress::new(byte_hash);
-
-    type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
     let val_mmcs = ValMmcs::new(field_hash, compress);
-
-    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
     let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
 
-    type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
-    
-    let (trace, and_most_sig_byte_decomp_4_to_3, and_most_sig_byte_decomp_4_to_2, and_most_sig_byte_decomp_4_to_1) = generate_trace_and_inputs::<Val>(value);
+    let fri_config = params.fri_config(challenge_mmcs);
+    let dft = Dft::new(trace_height << fri_config.log_blowup);
+
+    let pcs = Pcs::new(dft, val_mmcs, fri_config);
+    MyConfig::new(pcs)
+}
+
+// A fresh challenger with an empty transcript.
+pub fn challenger() -> Challenger {
+    Challenger::from_hasher(vec![], ByteHash {})
+}
+
+pub fn prove(value: u32) -> Proof<MyConfig> {
+    prove_with_params(value, &DEFAULT_FRI_PARAMS)
+}
+
+pub fn prove_with_params(value: u32, params: &FriParams) -> Proof<MyConfig> {
+    prove_with_challenger(value, params, &mut challenger())
+}
+
+// Continues the transcript of `challenger`, binding the proof to everything observed before.
+pub fn prove_with_challenger(value: u32, params: &FriParams, challenger: &mut Challenger) -> Proof<MyConfig> {
+    prove_with_trace_hook(value, params, challenger, None)
+}
+
+// `trace_hook` is called with the generated trace right before it is committed to.
+pub fn prove_with_trace_hook(
+    value: u32,
+    params: &FriParams,
+    challenger: &mut Challenger,
+    trace_hook: Option<&mut dyn FnMut(&RowMajorMatrix<Val>)>,
+) -> Proof<MyConfig> {
+    let (trace, and_most_sig_byte_decomp_4_to_3, and_most_sig_byte_decomp_4_to_2, and_most_sig_byte_decomp_4_to_1) = info_span!("generate_trace").in_scope(|| generate_trace_and_inputs::<Val>(value));
     let air = BabyBearRangeCheckBitDecompositionAir { value, and_most_sig_byte_decomp_4_to_3, and_most_sig_byte_decomp_4_to_2, and_most_sig_byte_decomp_4_to_1 };
+    let config = config(trace.height(), params);
 
-    let fri_config = FriConfig {
-        log_blowup: 1,
-        num_queries: 100,
-        proof_of_work_bits: 16,
-        mmcs: challenge_mmcs,
-    };
-    type Dft = RecursiveDft<Val>;
-    let dft = Dft::new(trace.height() << fri_config.log_blowup);
+    if let Some(trace_hook) = trace_hook {
+        trace_hook(&trace);
+    }
 
-    type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
-    let pcs = Pcs::new(dft, val_mmcs, fri_config);
+    info_span!("commit_trace").in_scope(|| p3_uni_stark::prove(&config, &air, challenger, trace, &vec![]))
+}
+
+/// Proves `air` over a trace built by the caller, after checking that its AND helpers match the trace.
+pub fn prove_with_witness(
+    air: &BabyBearRangeCheckBitDecompositionAir<Val>,
+    trace: RowMajorMatrix<Val>,
+    params: &FriParams,
+    challenger: &mut Challenger,
+) -> Result<Proof<MyConfig>, Error> {
+    air.check_witness(&trace)?;
+    let config = config(trace.height(), params);
+    Ok(info_span!("commit_trace").in_scope(|| p3_uni_stark::prove(&config, air, challenger, trace, &vec![])))
+}
+
+pub fn verify(value: u32, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    verify_with_params(value, proof, &DEFAULT_FRI_PARAMS)
+}
+
+pub fn verify_with_params(
+    value: u32,
+    proof: &Proof<MyConfig>,
+    params: &FriParams,
+) -> Result<(), Error> {
+    verify_with_challenger(value, proof, params, &mut challenger())
+}
+
+// `challenger` has to be in the same state the prover's challenger was in.
+pub fn verify_with_challenger(
+    value: u32,
+    proof: &Proof<MyConfig>,
+    params: &FriParams,
+    challenger: &mut Challenger,
+) -> Result<(), Error> {
+    check_value_in_field(FieldKind::BabyBear, u64::from(value))?;
+    let air = BabyBearRangeCheckBitDecompositionAir::<Val>::new(value);
+    let config = config(1, params);
+
+    p3_uni_stark::verify(&config, &air, challenger, proof, &vec![])
+        .map_err(|e| Error::verification_failed(FieldKind::BabyBear, u64::from(value), e))
+}
+
+// Returns the proof of work stats of the proof, which are also logged, or why the proof didn't verify.
+pub fn prove_and_verify(value: u32) -> Result<ProofStats, Error> {
+    prove_and_verify_with_params(value, &DEFAULT_FRI_PARAMS)
+}
 
-    type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
-    let config = MyConfig::new(pcs);
+pub fn prove_and_verify_with_params(value: u32, params: &FriParams) -> Result<ProofStats, Error> {
+    timings::init_tracing();
 
-    let mut challenger = Challenger::from_hasher(vec![], byte_hash);
-    let proof = prove(&config, &air, &mut challenger, trace, &vec![]);
+    let proof = info_span!("prove").in_scope(|| prove_with_params(value, params));
+    info_span!("verify")
+        .in_scope(|| verify_with_params(value, &proof, params))?;
 
-    let mut challenger = Challenger::from_hasher(vec![], byte_hash);
-    let _ = verify(&config, &air, &mut challenger, &proof, &vec![]).expect("verification failed");
-}
\ No newline at end of file
+    let stats = ProofStats::from_proof(&proof, params)?;
+    tracing::info!("{}", stats);
+    Ok(stats)
+}
+
+/// Returns the proof size and the proving and verifying times, or why the proof didn't verify.
+pub fn prove_and_verify_with_stats(value: u32) -> Result<ProveStats, Error> {
+    ProveStats::measure(
+        || Ok(prove_with_params(value, &DEFAULT_FRI_PARAMS)),
+        |proof| verify_with_params(value, proof, &DEFAULT_FRI_PARAMS),
+    )
+}
+
+/// Whether `value` is in range with this module's AIR, like `prover::check` for the `_v1` provers.
+pub fn check(value: u64) -> bool {
+    if check_value_in_field(FieldKind::BabyBear, value).is_err() {
+        return false;
+    }
+    let value = value as u32;
+    verify_with_params(value, &prove_with_params(value, &DEFAULT_FRI_PARAMS), &DEFAULT_FRI_PARAMS).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn width_matches_the_const() {
+        let air = BabyBearRangeCheckBitDecompositionAir::<Val>::new(0);
+        assert_eq!(BaseAir::<Val>::width(&air), BabyBearRangeCheckBitDecompositionAir::<Val>::WIDTH);
+    }
+
+    #[test]
+    fn mismatched_and_helpers_are_rejected_before_proving() {
+        // The top four one bits are set, so every helper is 1
+        let value = (1 << 31) - (1 << 27);
+        let (trace, _, _, _) = generate_trace_and_inputs::<Val>(value);
+        let air = BabyBearRangeCheckBitDecompositionAir::<Val>::new(value);
+        assert!(air.check_witness(&trace).is_ok());
+
+        // The helpers of another value with a trace of `value`
+        let air = BabyBearRangeCheckBitDecompositionAir::<Val>::new(100);
+        let proof = prove_with_witness(&air, trace, &DEFAULT_FRI_PARAMS, &mut challenger());
+        assert!(matches!(proof, Err(Error::InconsistentWitness(_))));
+
+        let (trace, _, _, _) = generate_trace_and_inputs::<Val>(100);
+        let proof = prove_with_witness(&air, trace, &DEFAULT_FRI_PARAMS, &mut challenger()).unwrap();
+        assert!(verify(100, &proof).is_ok());
+    }
+}
@@ -0,0 +1,532 @@
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_challenger::{CanObserve, FieldChallenger};
+use p3_commit::{OpenedValues, Pcs as _};
+use p3_field::{AbstractExtensionField, AbstractField, Field, PrimeField32};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::{Com, PcsProof, Proof, StarkGenericConfig};
+use serde::{Deserialize, Serialize};
+use tracing::info_span;
+
+use crate::babybear_v1::{
+    self, bit_weights, eval_value_columns, value_columns, Challenge, Challenger, MyConfig, DEFAULT_FRI_PARAMS, WIDTH,
+};
+use crate::error::{Error, VerificationFailure};
+use crate::field::FieldKind;
+
+// `Challenge` is `BabyBear[x] / (x^4 - W)`, its elements take 4 columns or public values.
+const EXT_DEGREE: usize = 4;
+const EXT_W: u32 = 11;
+
+// Index of the accumulator columns, right after the value columns.
+const ACC: usize = WIDTH;
+const TRACE_WIDTH: usize = WIDTH + EXT_DEGREE;
+
+// Range checks any number of values with a fixed width trace, one value per row:
+// | value bits | value ANDs | acc |
+// The value columns are the ones of `babybear_v1`. `acc` is a `Challenge` that starts at the first value and folds
+// in every next row's value with `acc' = acc * r + value`, so the last row holds `sum(value_i * r^(h - 1 - i))`
+// over the `h` rows. Padding rows, up to the next power of two, range check 0. Public values are the coefficients
+// of `r` and of that fingerprint, which the verifier recomputes from the values.
+//
+// The fold only binds the rows to the values if `r` is unknown while the rows are chosen, but `p3_uni_stark`
+// commits `acc` together with the rows. So the value columns are committed on their own first and `r` is sampled
+// after observing that commitment. Once the range check is proven, both commitments are opened at a random point
+// where the value columns have to agree, which makes the proven rows the ones committed before `r`.
+//
+// That commitment is also what the committed mode verifies against: it's a Merkle root of the value columns, so a
+// verifier holding only the root checks the proof without the values, with the fingerprint taken from the proof.
+pub struct BabyBearBatchRangeCheckAir;
+
+impl<F: Field> BaseAir<F> for BabyBearBatchRangeCheckAir {
+    fn width(&self) -> usize {
+        TRACE_WIDTH
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for BabyBearBatchRangeCheckAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let next = main.row_slice(1);
+        let public_values: Vec<AB::Expr> = builder.public_values().iter().map(|&value| value.into()).collect();
+        let (r, fingerprint) = public_values.split_at(EXT_DEGREE);
+        let acc: Vec<AB::Expr> = local[ACC..].iter().map(|&coefficient| coefficient.into()).collect();
+
+        let value = eval_value_columns(builder, &local[..WIDTH]);
+        let mut first_row = builder.when_first_row();
+        first_row.assert_eq(acc[0].clone(), value);
+        for coefficient in &acc[1..] {
+            first_row.assert_zero(coefficient.clone());
+        }
+
+        // The next row's bits are constrained when that row is evaluated, here they're only folded in
+        let mut next_value = AB::Expr::zero();
+        for (&bit, weight) in next[..32].iter().zip(bit_weights::<AB::F>()) {
+            next_value += AB::Expr::from(bit) * weight;
+        }
+        let mut folded = ext_mul(&acc, r);
+        folded[0] += next_value;
+        let mut transition = builder.when_transition();
+        for (&coefficient, folded) in next[ACC..].iter().zip(folded) {
+            transition.assert_eq(coefficient, folded);
+        }
+
+        let mut last_row = builder.when_last_row();
+        for (coefficient, expected) in acc.into_iter().zip(fingerprint) {
+            last_row.assert_eq(coefficient, expected.clone());
+        }
+    }
+}
+
+// `a * b` of two `Challenge`s given by their coefficients, reducing `x^4` to `W`.
+fn ext_mul<E: AbstractField>(a: &[E], b: &[E]) -> [E; EXT_DEGREE] {
+    let w = E::from_canonical_u32(EXT_W);
+    let mut product: [E; EXT_DEGREE] = core::array::from_fn(|_| E::zero());
+    for (i, a) in a.iter().enumerate() {
+        for (j, b) in b.iter().enumerate() {
+            let term = a.clone() * b.clone();
+            if i + j < EXT_DEGREE {
+                product[i + j] += term;
+            } else {
+                product[i + j - EXT_DEGREE] += term * w.clone();
+            }
+        }
+    }
+    product
+}
+
+// The values padded with zeros up to the trace height.
+fn padded(values: &[u32]) -> impl Iterator<Item = u32> + '_ {
+    let height = values.len().next_power_of_two();
+    values.iter().copied().chain(std::iter::repeat(0)).take(height)
+}
+
+// Only the value columns, committed before `r` is sampled.
+fn value_trace(values: &[u32]) -> RowMajorMatrix<BabyBear> {
+    RowMajorMatrix::new(padded(values).flat_map(value_columns::<BabyBear>).collect(), WIDTH)
+}
+
+pub fn generate_trace(values: &[u32], r: Challenge) -> RowMajorMatrix<BabyBear> {
+    let mut rows = Vec::with_capacity(values.len().next_power_of_two() * TRACE_WIDTH);
+    let mut acc = Challenge::zero();
+    for value in padded(values) {
+        acc = acc * r + BabyBear::from_canonical_u32(value);
+        rows.extend(value_columns::<BabyBear>(value));
+        rows.extend_from_slice(acc.as_base_slice());
+    }
+    RowMajorMatrix::new(rows, TRACE_WIDTH)
+}
+
+fn fingerprint(values: &[u32], r: Challenge) -> Challenge {
+    padded(values).fold(Challenge::zero(), |acc, value| acc * r + BabyBear::from_canonical_u32(value))
+}
+
+fn public_values(r: Challenge, fingerprint: Challenge) -> Vec<BabyBear> {
+    [r, fingerprint].iter().flat_map(|element| element.as_base_slice().to_vec()).collect()
+}
+
+/// Checks every value on its own, `Err(Error::ValueOutOfField)` for each one at or above the modulus, in the order
+/// of `values`. `prove` runs the same checks and only reports the first failure.
+pub fn validate(values: &[u32]) -> Vec<Result<(), Error>> {
+    values
+        .iter()
+        .map(|&value| {
+            if value >= BabyBear::ORDER_U32 {
+                return Err(Error::ValueOutOfField { field: FieldKind::BabyBear, value: u64::from(value) });
+            }
+            Ok(())
+        })
+        .collect()
+}
+
+// The values are observed and folded as field elements, so values above the modulus are rejected first.
+fn check_values(values: &[u32]) -> Result<(), Error> {
+    if values.is_empty() {
+        return Err(Error::InvalidInput("no values to range check".to_string()));
+    }
+    validate(values).into_iter().collect::<Result<Vec<_>, _>>()?;
+    Ok(())
+}
+
+// The statement starts the transcript, so `r`, and with it the fingerprint, depends on the values and their shape.
+fn observe_statement(challenger: &mut Challenger, values: &[u32], shape: [usize; 2]) {
+    for dimension in shape {
+        challenger.observe(BabyBear::from_canonical_usize(dimension));
+    }
+    for &value in values {
+        challenger.observe(BabyBear::from_canonical_u32(value));
+    }
+}
+
+/// A `BabyBearBatchRangeCheckAir` proof together with the commitment to the value columns it is tied to.
+#[derive(Serialize, Deserialize)]
+pub struct BatchProof {
+    values_commitment: Com<MyConfig>,
+    stark: Proof<MyConfig>,
+    // The columns of both commitments at the point sampled after the range check, and their opening proof.
+    opened_values: OpenedValues<Challenge>,
+    opening_proof: PcsProof<MyConfig>,
+}
+
+/// Proves that every one of `values` is in range with a single proof, one value per trace row.
+pub fn prove(values: &[u32]) -> Result<BatchProof, Error> {
+    prove_shaped(values, [1, values.len()])
+}
+
+// Like `prove`, for `values` read row by row from a `shape[0] x shape[1]` grid.
+pub(crate) fn prove_shaped(values: &[u32], shape: [usize; 2]) -> Result<BatchProof, Error> {
+    prove_rows(values, values, shape, None).map(|(proof, _)| proof)
+}
+
+/// The root `verify_committed` checks a `CommittedBatchProof` of `values` against: the Merkle commitment to their
+/// value columns.
+pub fn values_root(values: &[u32]) -> Result<Com<MyConfig>, Error> {
+    check_values(values)?;
+    let height = values.len().next_power_of_two();
+    let config = babybear_v1::config(height, &DEFAULT_FRI_PARAMS);
+    let pcs = config.pcs();
+    let domain = p3_commit::Pcs::<Challenge, Challenger>::natural_domain_for_degree(pcs, height);
+    Ok(p3_commit::Pcs::<Challenge, Challenger>::commit(pcs, vec![(domain, value_trace(values))]).0)
+}
+
+/// A `BatchProof` checked against the root of the values instead of the values, with the fingerprint the verifier
+/// can't recompute without them.
+#[derive(Serialize, Deserialize)]
+pub struct CommittedBatchProof {
+    proof: BatchProof,
+    fingerprint: Challenge,
+}
+
+/// Proves that every one of `values` is in range for a verifier that only knows `values_root(values)` and how many
+/// values there are.
+pub fn prove_committed(values: &[u32]) -> Result<CommittedBatchProof, Error> {
+    let (proof, r) = prove_rows(values, &[], [0, values.len()], None)?;
+    Ok(CommittedBatchProof { proof, fingerprint: fingerprint(values, r) })
+}
+
+// Proves the rows of `values` with a transcript that starts with `observed` and `shape`, returns the proof and `r`.
+// The committed mode observes no values and a shape of no rows, which no grid has. `trace_hook` lets tests change
+// the trace after the value columns are committed and `r` is known, like a cheating prover.
+fn prove_rows(
+    values: &[u32],
+    observed: &[u32],
+    shape: [usize; 2],
+    trace_hook: Option<&dyn Fn(&mut RowMajorMatrix<BabyBear>, Challenge)>,
+) -> Result<(BatchProof, Challenge), Error> {
+    check_values(values)?;
+    let height = values.len().next_power_of_two();
+    let config = babybear_v1::config(height, &DEFAULT_FRI_PARAMS);
+    let pcs = config.pcs();
+    let domain = p3_commit::Pcs::<Challenge, Challenger>::natural_domain_for_degree(pcs, height);
+
+    let mut challenger = babybear_v1::challenger();
+    observe_statement(&mut challenger, observed, shape);
+    let (values_commitment, values_data) = info_span!("commit_values")
+        .in_scope(|| p3_commit::Pcs::<Challenge, Challenger>::commit(pcs, vec![(domain, value_trace(values))]));
+    challenger.observe(values_commitment.clone());
+    let r: Challenge = challenger.sample_ext_element();
+
+    let mut trace = info_span!("generate_trace").in_scope(|| generate_trace(values, r));
+    if let Some(hook) = trace_hook {
+        hook(&mut trace, r);
+    }
+    // `p3_uni_stark` keeps its prover data to itself, committing the same trace again gives the same commitment
+    let (_, trace_data) = p3_commit::Pcs::<Challenge, Challenger>::commit(pcs, vec![(domain, trace.clone())]);
+    let public_values = public_values(r, fingerprint(values, r));
+    let stark = info_span!("commit_trace").in_scope(|| {
+        p3_uni_stark::prove(&config, &BabyBearBatchRangeCheckAir, &mut challenger, trace, &public_values)
+    });
+
+    let zeta: Challenge = challenger.sample_ext_element();
+    let (opened_values, opening_proof) = info_span!("open_values").in_scope(|| {
+        pcs.open(vec![(&values_data, vec![vec![zeta]]), (&trace_data, vec![vec![zeta]])], &mut challenger)
+    });
+    Ok((BatchProof { values_commitment, stark, opened_values, opening_proof }, r))
+}
+
+// Borrows the proof like every other verifier, so it can be checked any number of times without a clone.
+pub fn verify(values: &[u32], proof: &BatchProof) -> Result<(), Error> {
+    verify_shaped(values, [1, values.len()], proof)
+}
+
+pub(crate) fn verify_shaped(values: &[u32], shape: [usize; 2], proof: &BatchProof) -> Result<(), Error> {
+    check_values(values)?;
+    let value = u64::from(values[0]);
+    verify_rows(values.len(), values, shape, proof, |r| fingerprint(values, r), |failure| {
+        Error::VerificationFailed { field: FieldKind::BabyBear, value, failure }
+    })
+}
+
+/// Checks a `prove_committed` proof for `count` values against `root`, without the values.
+pub fn verify_committed(root: &Com<MyConfig>, count: usize, proof: &CommittedBatchProof) -> Result<(), Error> {
+    if count == 0 {
+        return Err(Error::InvalidInput("no values to range check".to_string()));
+    }
+    if proof.proof.values_commitment != *root {
+        return Err(Error::Verification("proof isn't for the committed values".to_string()));
+    }
+    verify_rows(count, &[], [0, count], &proof.proof, |_| proof.fingerprint, |failure| {
+        Error::Verification(format!("committed batch proof: {}", failure))
+    })
+}
+
+// Verifies the rows of `proof` against the transcript `prove_rows` started with `observed` and `shape`.
+// `fingerprint` gives the expected fingerprint for `r`, `stark_error` reports a range check that doesn't verify.
+fn verify_rows(
+    count: usize,
+    observed: &[u32],
+    shape: [usize; 2],
+    proof: &BatchProof,
+    fingerprint: impl FnOnce(Challenge) -> Challenge,
+    stark_error: impl FnOnce(VerificationFailure) -> Error,
+) -> Result<(), Error> {
+    let height = count.next_power_of_two();
+    let config = babybear_v1::config(1, &DEFAULT_FRI_PARAMS);
+    let pcs = config.pcs();
+    let domain = p3_commit::Pcs::<Challenge, Challenger>::natural_domain_for_degree(pcs, height);
+
+    let mut challenger = babybear_v1::challenger();
+    observe_statement(&mut challenger, observed, shape);
+    challenger.observe(proof.values_commitment.clone());
+    let r: Challenge = challenger.sample_ext_element();
+    let public_values = public_values(r, fingerprint(r));
+    p3_uni_stark::verify(&config, &BabyBearBatchRangeCheckAir, &mut challenger, &proof.stark, &public_values)
+        .map_err(|e| stark_error(e.into()))?;
+
+    // The value columns have to be the same polynomials in both commitments
+    let committed = opened_columns(&proof.opened_values, 0)?;
+    let proven = opened_columns(&proof.opened_values, 1)?;
+    if committed.len() != WIDTH || proven.len() != TRACE_WIDTH || committed[..] != proven[..WIDTH] {
+        return Err(Error::Verification("proven rows aren't the committed values".to_string()));
+    }
+    let zeta: Challenge = challenger.sample_ext_element();
+    let rounds = vec![
+        (proof.values_commitment.clone(), vec![(domain, vec![(zeta, committed)])]),
+        (trace_commitment(&proof.stark)?, vec![(domain, vec![(zeta, proven)])]),
+    ];
+    pcs.verify(rounds, &proof.opening_proof, &mut challenger)
+        .map_err(|e| Error::Verification(format!("value columns don't open: {:?}", e)))
+}
+
+// The columns of the one matrix of `round`, opened at the one point.
+fn opened_columns(opened_values: &OpenedValues<Challenge>, round: usize) -> Result<Vec<Challenge>, Error> {
+    match opened_values.get(round).map(Vec::as_slice) {
+        Some([points]) if points.len() == 1 => Ok(points[0].clone()),
+        _ => Err(Error::Verification(format!("opening round {} isn't one matrix at one point", round))),
+    }
+}
+
+// The fields of `p3_uni_stark::Proof` aren't public, so the trace commitment is read from its serde representation
+// like `proof_info` does.
+fn trace_commitment(proof: &Proof<MyConfig>) -> Result<Com<MyConfig>, Error> {
+    let value = serde_json::to_value(proof).map_err(|e| Error::Serialization(e.to_string()))?;
+    let trace = value
+        .get("commitments")
+        .and_then(|commitments| commitments.get("trace"))
+        .ok_or_else(|| Error::Serialization("proof has no `trace` commitment".to_string()))?;
+    serde_json::from_value(trace.clone()).map_err(|e| Error::Serialization(e.to_string()))
+}
+
+/// A BabyBear range check proof for one or more values.
+///
+/// `Single` is a `babybear_v1` proof, with the value as its one public value. `Batch` is a `BatchProof`, one value
+/// per row, tied to the values by a fingerprint.
+pub enum BabyBearRangeProof {
+    Single(Proof<MyConfig>),
+    Batch(BatchProof),
+}
+
+/// Proves that all `values` are in range, with the single value AIR for one value and the batch AIR otherwise.
+pub fn range_check_babybear(values: &[u32]) -> Result<BabyBearRangeProof, Error> {
+    match values {
+        [value] => {
+            check_values(values)?;
+            Ok(BabyBearRangeProof::Single(babybear_v1::prove(*value)))
+        }
+        _ => prove(values).map(BabyBearRangeProof::Batch),
+    }
+}
+
+pub fn verify_range_check_babybear(values: &[u32], proof: &BabyBearRangeProof) -> Result<(), Error> {
+    match (values, proof) {
+        ([value], BabyBearRangeProof::Single(proof)) => babybear_v1::verify(*value, proof),
+        (_, BabyBearRangeProof::Batch(proof)) if values.len() != 1 => verify(values, proof),
+        _ => Err(Error::InvalidInput(format!("proof doesn't match the number of values, {}", values.len()))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_field::extension::BinomiallyExtendable;
+
+    use super::*;
+    use crate::debug::{assert_proof_fails, check_constraints};
+
+    fn violations(trace: &RowMajorMatrix<BabyBear>, values: &[u32], r: Challenge) -> usize {
+        check_constraints(&BabyBearBatchRangeCheckAir, trace, &public_values(r, fingerprint(values, r))).len()
+    }
+
+    fn some_challenge() -> Challenge {
+        Challenge::from_base_slice(&[2, 3, 5, 7].map(BabyBear::from_canonical_u32))
+    }
+
+    // Rows that differ from `values` but fold to the same fingerprint under `r`. The minimal polynomial `m` of `r`
+    // has base field coefficients, adding them to the first 5 rows adds `r^(h - 5) * m(r) = 0` to the fingerprint.
+    fn forged_rows(values: &[u32], r: Challenge) -> Vec<u32> {
+        // `m` is the product of `x - c` over the conjugates `c = r^(p^i)`, lowest coefficient first
+        let mut minimal = vec![Challenge::one()];
+        let mut conjugate = r;
+        for _ in 0..EXT_DEGREE {
+            let mut product = vec![Challenge::zero(); minimal.len() + 1];
+            for (k, &coefficient) in minimal.iter().enumerate() {
+                product[k + 1] += coefficient;
+                product[k] -= conjugate * coefficient;
+            }
+            minimal = product;
+            conjugate = conjugate.exp_u64(u64::from(BabyBear::ORDER_U32));
+        }
+
+        let mut forged = values.to_vec();
+        for (row, coefficient) in forged.iter_mut().zip(minimal.iter().rev()) {
+            let coefficients = coefficient.as_base_slice();
+            assert!(coefficients[1..].iter().all(BabyBear::is_zero));
+            *row = (BabyBear::from_canonical_u32(*row) + coefficients[0]).as_canonical_u32();
+        }
+        forged
+    }
+
+    #[test]
+    fn batch_trace_satisfies_constraints() {
+        let values = [0, 1, 100, BabyBear::ORDER_U32 - 1, 7, 8];
+        let trace = generate_trace(&values, some_challenge());
+        assert_eq!(trace.height(), 8);
+        assert_eq!(violations(&trace, &values, some_challenge()), 0);
+        assert_ne!(violations(&trace, &[0, 1, 101, BabyBear::ORDER_U32 - 1, 7, 8], some_challenge()), 0);
+    }
+
+    #[test]
+    fn width_doesnt_grow_with_the_values() {
+        assert_eq!(BaseAir::<BabyBear>::width(&BabyBearBatchRangeCheckAir), WIDTH + EXT_DEGREE);
+        let values: Vec<u32> = (0..1000).collect();
+        let trace = generate_trace(&values, some_challenge());
+        assert_eq!((trace.width(), trace.height()), (TRACE_WIDTH, 1024));
+    }
+
+    #[test]
+    fn ext_mul_is_challenge_multiplication() {
+        assert_eq!(<BabyBear as BinomiallyExtendable<EXT_DEGREE>>::W, BabyBear::from_canonical_u32(EXT_W));
+        let a = some_challenge();
+        let b = a.exp_u64(1_000_003);
+        assert_eq!(ext_mul(a.as_base_slice(), b.as_base_slice()), (a * b).as_base_slice());
+    }
+
+    #[test]
+    fn rows_folding_to_the_same_fingerprint_are_rejected() {
+        let values = [1, 2, 3, 4, 5, 6, 7, 8];
+        let forge = |trace: &mut RowMajorMatrix<BabyBear>, r: Challenge| {
+            let forged = forged_rows(&values, r);
+            assert_ne!(forged, values);
+            assert_eq!(fingerprint(&forged, r), fingerprint(&values, r));
+            // The forged rows satisfy the AIR, only the value columns committed before `r` give them away
+            *trace = generate_trace(&forged, r);
+            assert_eq!(violations(trace, &values, r), 0);
+        };
+        let proof = prove_rows(&values, &values, [1, values.len()], Some(&forge)).unwrap().0;
+        assert!(verify(&values, &proof).is_err());
+    }
+
+    #[test]
+    fn wrapping_non_boolean_cell_is_rejected() {
+        // The second row's bits are all zero except the weight 2 cell, which holds `(p + 7) / 2` so the
+        // reconstruction wraps around to 7 and the fingerprint still matches
+        let forge = |trace: &mut RowMajorMatrix<BabyBear>, r: Challenge| {
+            let row = &mut trace.values[TRACE_WIDTH..TRACE_WIDTH + 32];
+            row.fill(BabyBear::zero());
+            row[30] = BabyBear::from_canonical_u32((BabyBear::ORDER_U32 + 7) / 2);
+
+            // Booleanity of the forged cell is the only constraint that catches it
+            assert_eq!(violations(trace, &[5, 7], r), 1);
+        };
+
+        assert_proof_fails(
+            || prove_rows(&[5, 7], &[5, 7], [1, 2], Some(&forge)).unwrap().0,
+            |proof| verify(&[5, 7], proof).is_ok(),
+        );
+    }
+
+    #[test]
+    fn range_check_selects_the_air() {
+        let single = range_check_babybear(&[100]).unwrap();
+        assert!(matches!(single, BabyBearRangeProof::Single(_)));
+        assert!(verify_range_check_babybear(&[100], &single).is_ok());
+
+        let batch = range_check_babybear(&[100, 200]).unwrap();
+        assert!(matches!(batch, BabyBearRangeProof::Batch(_)));
+        assert!(verify_range_check_babybear(&[100, 200], &batch).is_ok());
+        assert!(verify_range_check_babybear(&[100], &batch).is_err());
+    }
+
+    #[test]
+    fn one_proof_verifies_repeatedly_by_reference() {
+        let proof = prove(&[100, 200]).unwrap();
+        for _ in 0..3 {
+            verify(&[100, 200], &proof).unwrap();
+        }
+        let proof = range_check_babybear(&[100]).unwrap();
+        for _ in 0..3 {
+            verify_range_check_babybear(&[100], &proof).unwrap();
+        }
+    }
+
+    #[test]
+    fn validation_reports_every_bad_value() {
+        let order = BabyBear::ORDER_U32;
+        let results = validate(&[1, order, 2, u32::MAX]);
+        assert!(results[0].is_ok() && results[2].is_ok());
+        assert!(matches!(results[1], Err(Error::ValueOutOfField { value, .. }) if value == u64::from(order)));
+        assert!(matches!(results[3], Err(Error::ValueOutOfField { value, .. }) if value == u64::from(u32::MAX)));
+    }
+
+    #[test]
+    fn values_above_the_modulus_are_rejected() {
+        assert!(matches!(
+            range_check_babybear(&[1, BabyBear::ORDER_U32]),
+            Err(Error::ValueOutOfField { .. })
+        ));
+    }
+
+    #[test]
+    fn one_proof_for_many_values_is_far_smaller_than_one_per_value() {
+        let values: Vec<u32> = (0..1000).map(|i| i * 2_000_003 % BabyBear::ORDER_U32).collect();
+        let proof = prove(&values).unwrap();
+        verify(&values, &proof).unwrap();
+        let mut tampered = values.clone();
+        tampered[999] += 1;
+        assert!(verify(&tampered, &proof).is_err());
+
+        // Single value proofs all have the same shape, so one stands in for the 1000 separate ones
+        let batch_size = bincode::serialized_size(&proof).unwrap();
+        let single_size = bincode::serialized_size(&babybear_v1::prove(values[1])).unwrap();
+        assert!(batch_size * 10 < single_size * 1000, "{} vs 1000 x {}", batch_size, single_size);
+    }
+
+    #[test]
+    fn committed_values_verify_against_their_root() {
+        let values: Vec<u32> = (0..100).map(|i| i * 7_000_003 % BabyBear::ORDER_U32).collect();
+        let root = values_root(&values).unwrap();
+        let proof = prove_committed(&values).unwrap();
+        verify_committed(&root, values.len(), &proof).unwrap();
+        assert!(verify_committed(&root, 1000, &proof).is_err());
+
+        let mut other = values.clone();
+        other[42] += 1;
+        assert!(matches!(verify_committed(&values_root(&other).unwrap(), 100, &proof), Err(Error::Verification(_))));
+
+        // A committed proof doesn't pass as a proof for the values, the transcripts start differently
+        assert!(verify(&values, &proof.proof).is_err());
+        let fingerprinted = CommittedBatchProof { proof: prove(&values).unwrap(), fingerprint: proof.fingerprint };
+        assert!(verify_committed(&root, values.len(), &fingerprinted).is_err());
+    }
+}
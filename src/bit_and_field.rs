@@ -0,0 +1,173 @@
+use p3_air::{Air, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, Field};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::babybear_v1::{self, eval_value_columns, value_columns, MyConfig, DEFAULT_FRI_PARAMS, WIDTH};
+use crate::bits::{reconstruct, write_bits};
+use crate::error::Error;
+use crate::field::FieldKind;
+use crate::short_range::SHORT_BITS;
+
+// Largest supported bit length, every `u32`.
+pub const MAX_BITS: u32 = 32;
+
+// Proves `value < 2^k` and `value < p` in one proof, with a single row of the 32 bits of `value` in big endian
+// format. The bits above `k` are zero.
+// For `k <= SHORT_BITS` every `k` bit value is below the modulus, so the row is only the bits and the modulus
+// boundary is elided, as in `short_range`. Otherwise the row has the AND columns of `babybear_v1` and the boundary
+// applies too. Since `p < 2^31`, it then also implies the bit length, both constraints hold but the field range is
+// the tighter one for `k` of 31 and 32.
+// Public values are `[value, k]`, and have to match the statement the AIR is built for.
+pub struct BabyBearBitAndFieldRangeAir {
+    pub value: u32,
+    pub k: u32,
+}
+
+impl BabyBearBitAndFieldRangeAir {
+    fn needs_field_check(&self) -> bool {
+        self.k as usize > SHORT_BITS
+    }
+}
+
+impl<F: Field> BaseAir<F> for BabyBearBitAndFieldRangeAir {
+    fn width(&self) -> usize {
+        if self.needs_field_check() {
+            WIDTH
+        } else {
+            32
+        }
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for BabyBearBitAndFieldRangeAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let current_row = main.row_slice(0);
+
+        let public_values = builder.public_values();
+        let value: AB::Expr = public_values[0].into();
+        let k: AB::Expr = public_values[1].into();
+
+        builder.assert_eq(value.clone(), AB::Expr::from_canonical_u32(self.value));
+        builder.assert_eq(k, AB::Expr::from_canonical_u32(self.k));
+
+        let reconstructed_value = if self.needs_field_check() {
+            // Booleanity, the modulus boundary and the reconstruction
+            eval_value_columns(builder, &current_row)
+        } else {
+            // Making sure every bit is either 0 or 1
+            for &bit in current_row.iter() {
+                builder.assert_bool(bit);
+            }
+            reconstruct::<AB>(&current_row)
+        };
+        builder.assert_eq(value, reconstructed_value);
+
+        // Bit `i` has weight `2^(31 - i)`, the bits above `k` are the first `32 - k`
+        for &bit in &current_row[..32 - self.k as usize] {
+            builder.assert_zero(bit);
+        }
+    }
+}
+
+pub fn generate_trace<F: Field>(value: u32, k: u32) -> RowMajorMatrix<F> {
+    if k as usize > SHORT_BITS {
+        return RowMajorMatrix::new(value_columns(value), WIDTH);
+    }
+    let mut row = vec![F::zero(); 32];
+    write_bits(&mut row, u64::from(value));
+    RowMajorMatrix::new(row, 32)
+}
+
+fn check_k(k: u32) -> Result<(), Error> {
+    if k == 0 || k > MAX_BITS {
+        return Err(Error::InvalidInput(format!("bit length {} has to be in [1, {}]", k, MAX_BITS)));
+    }
+    Ok(())
+}
+
+fn public_values(value: u32, k: u32) -> Vec<BabyBear> {
+    vec![BabyBear::from_canonical_u32(value), BabyBear::from_canonical_u32(k)]
+}
+
+/// Proves that `value` fits in `k` bits and is a canonical BabyBear element.
+pub fn prove_bit_and_field_range(value: u32, k: u32) -> Result<Proof<MyConfig>, Error> {
+    check_k(k)?;
+    babybear_v1::try_generate_trace::<BabyBear>(value)?;
+    if u64::from(value) >> k != 0 {
+        return Err(Error::InvalidInput(format!("{} doesn't fit in {} bits", value, k)));
+    }
+
+    let air = BabyBearBitAndFieldRangeAir { value, k };
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<BabyBear>(value, k));
+    let config = babybear_v1::config(trace.height(), &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    Ok(info_span!("commit_trace")
+        .in_scope(|| p3_uni_stark::prove(&config, &air, &mut challenger, trace, &public_values(value, k))))
+}
+
+pub fn verify_bit_and_field_range(value: u32, k: u32, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    check_k(k)?;
+    babybear_v1::try_generate_trace::<BabyBear>(value)?;
+    let air = BabyBearBitAndFieldRangeAir { value, k };
+    let config = babybear_v1::config(1, &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    p3_uni_stark::verify(&config, &air, &mut challenger, proof, &public_values(value, k))
+        .map_err(|e| Error::verification_failed(FieldKind::BabyBear, u64::from(value), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p3_field::PrimeField32;
+
+    use crate::debug::satisfies;
+
+    // `value` is written with the layout of `k`, and has to be canonical to be a public value.
+    fn satisfied(value: u32, k: u32) -> bool {
+        let air = BabyBearBitAndFieldRangeAir { value, k };
+        satisfies(&air, &generate_trace::<BabyBear>(value, k), &public_values(value, k))
+    }
+
+    #[test]
+    fn short_bit_lengths_skip_the_modulus_boundary() {
+        let air = BabyBearBitAndFieldRangeAir { value: 0, k: 30 };
+        assert_eq!(BaseAir::<BabyBear>::width(&air), 32);
+        assert!(satisfied((1 << 30) - 1, 30));
+        assert!(satisfied(255, 8));
+        assert!(!satisfied(256, 8));
+        assert!(!satisfied(1 << 30, 30));
+    }
+
+    #[test]
+    fn long_bit_lengths_apply_both_checks() {
+        let max = BabyBear::ORDER_U32 - 1;
+        assert!(satisfied(max, 31));
+        assert!(satisfied(max, 32));
+        assert!(satisfied(1 << 30, 31));
+    }
+
+    #[test]
+    fn values_in_range_of_k_but_not_the_field_fail() {
+        // The bits of `p` fit in 31 bits and reconstruct to 0, the modulus boundary rejects them
+        let air = BabyBearBitAndFieldRangeAir { value: 0, k: 31 };
+        let trace = generate_trace::<BabyBear>(BabyBear::ORDER_U32, 31);
+        assert!(!satisfies(&air, &trace, &public_values(0, 31)));
+        assert!(matches!(prove_bit_and_field_range(BabyBear::ORDER_U32, 32), Err(Error::ValueOutOfField { .. })));
+    }
+
+    #[test]
+    fn proof_verifies_for_the_value_and_k() {
+        let proof = prove_bit_and_field_range(200, 8).unwrap();
+        verify_bit_and_field_range(200, 8, &proof).unwrap();
+        assert!(verify_bit_and_field_range(200, 9, &proof).is_err());
+        assert!(matches!(prove_bit_and_field_range(200, 7), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_bit_and_field_range(200, 33), Err(Error::InvalidInput(_))));
+    }
+}
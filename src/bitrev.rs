@@ -0,0 +1,159 @@
+use p3_air::{Air, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, Field};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::babybear_v1::{self, MyConfig, DEFAULT_FRI_PARAMS};
+use crate::bits::{reconstruct, write_bits};
+use crate::error::Error;
+use crate::field::FieldKind;
+
+// Largest supported index width. With `bits <= 29`, `slack + value < 2^30` can't wrap around the modulus.
+pub const MAX_BITREV_BITS: u32 = 29;
+
+// Proves `value < bound` and `bitrev(value) < bound` for a `bits` bit index, with a single row:
+// | value bits | value slack bits | bitrev slack bits |
+// The value bits are committed once and read in both orders. The slacks are `bound - 1 - value` and
+// `bound - 1 - bitrev(value)`. Public values are `[value, bitrev, bound]`.
+pub struct BabyBearBitrevAir {
+    pub bits: usize,
+}
+
+impl<F: Field> BaseAir<F> for BabyBearBitrevAir {
+    fn width(&self) -> usize {
+        3 * self.bits
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for BabyBearBitrevAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let n = self.bits;
+
+        let public_values = builder.public_values();
+        let value: AB::Expr = public_values[0].into();
+        let bitrev: AB::Expr = public_values[1].into();
+        let bound: AB::Expr = public_values[2].into();
+        let max = bound - AB::Expr::one();
+
+        // Making sure every bit is either 0 or 1
+        for &bit in local.iter() {
+            builder.assert_bool(bit);
+        }
+
+        let value_bits = &local[..n];
+        let reversed_bits = value_bits.iter().rev().copied().collect::<Vec<_>>();
+        let value_slack = reconstruct::<AB>(&local[n..2 * n]);
+        let bitrev_slack = reconstruct::<AB>(&local[2 * n..]);
+
+        // Both interpretations of the same bits match the public values
+        builder.assert_eq(reconstruct::<AB>(value_bits), value.clone());
+        builder.assert_eq(reconstruct::<AB>(&reversed_bits), bitrev.clone());
+
+        // Both are below the bound
+        builder.assert_eq(value + value_slack, max.clone());
+        builder.assert_eq(bitrev + bitrev_slack, max);
+    }
+}
+
+pub fn bitrev(value: u32, bits: u32) -> u32 {
+    value.reverse_bits() >> (32 - bits)
+}
+
+pub fn generate_trace<F: Field>(value: u32, bits: u32, bound: u32) -> RowMajorMatrix<F> {
+    let n = bits as usize;
+    let mut row = vec![F::zero(); 3 * n];
+    write_bits(&mut row[..n], u64::from(value));
+    write_bits(&mut row[n..2 * n], u64::from(bound - 1 - value));
+    write_bits(&mut row[2 * n..], u64::from(bound - 1 - bitrev(value, bits)));
+    RowMajorMatrix::new(row, 3 * n)
+}
+
+fn check_parameters(bits: u32, bound: u32) -> Result<(), Error> {
+    if bits == 0 || bits > MAX_BITREV_BITS {
+        return Err(Error::InvalidInput(format!("index width {} has to be in [1, {}]", bits, MAX_BITREV_BITS)));
+    }
+    if bound == 0 || bound > 1 << bits {
+        return Err(Error::InvalidInput(format!("bound {} has to be in [1, 2^{}]", bound, bits)));
+    }
+    Ok(())
+}
+
+fn public_values(value: u32, bitrev: u32, bound: u32) -> Vec<BabyBear> {
+    vec![
+        BabyBear::from_canonical_u32(value),
+        BabyBear::from_canonical_u32(bitrev),
+        BabyBear::from_canonical_u32(bound),
+    ]
+}
+
+/// Proves that `value` and its `bits` bit reversal are both below `bound`, returning the reversal with the proof.
+pub fn prove_value_and_bitrev(value: u32, bits: u32, bound: u32) -> Result<(u32, Proof<MyConfig>), Error> {
+    check_parameters(bits, bound)?;
+    let reversed = bitrev(value, bits);
+    if value >= bound || reversed >= bound {
+        return Err(Error::InvalidInput(format!(
+            "{} or its bit reversal {} isn't below {}",
+            value, reversed, bound
+        )));
+    }
+
+    let air = BabyBearBitrevAir { bits: bits as usize };
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<BabyBear>(value, bits, bound));
+    let config = babybear_v1::config(trace.height(), &DEFAULT_FRI_PARAMS);
+    let public_values = public_values(value, reversed, bound);
+
+    let mut challenger = babybear_v1::challenger();
+    let proof = info_span!("commit_trace")
+        .in_scope(|| p3_uni_stark::prove(&config, &air, &mut challenger, trace, &public_values));
+    Ok((reversed, proof))
+}
+
+pub fn verify_value_and_bitrev(
+    value: u32,
+    bitrev: u32,
+    bits: u32,
+    bound: u32,
+    proof: &Proof<MyConfig>,
+) -> Result<(), Error> {
+    check_parameters(bits, bound)?;
+    let air = BabyBearBitrevAir { bits: bits as usize };
+    let config = babybear_v1::config(1, &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    p3_uni_stark::verify(&config, &air, &mut challenger, proof, &public_values(value, bitrev, bound))
+        .map_err(|e| Error::verification_failed(FieldKind::BabyBear, u64::from(value), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::debug::satisfies;
+
+    #[test]
+    fn bitrev_reverses_the_low_bits() {
+        assert_eq!(bitrev(0b0011, 4), 0b1100);
+        assert_eq!(bitrev(1, 1), 1);
+        assert_eq!(bitrev(1, 29), 1 << 28);
+    }
+
+    #[test]
+    fn value_and_bitrev_below_the_bound_pass() {
+        // 3 = 0b011 and 6 = 0b110 are both below 7
+        let air = BabyBearBitrevAir { bits: 3 };
+        let trace = generate_trace::<BabyBear>(3, 3, 7);
+        assert!(satisfies(&air, &trace, &public_values(3, 6, 7)));
+        assert!(!satisfies(&air, &trace, &public_values(3, 5, 7)));
+    }
+
+    #[test]
+    fn bitrev_above_the_bound_is_rejected() {
+        // 1 = 0b001 is below 3, but 4 = 0b100 isn't
+        assert!(matches!(prove_value_and_bitrev(1, 3, 3), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_value_and_bitrev(0, 30, 1), Err(Error::InvalidInput(_))));
+    }
+}
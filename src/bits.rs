@@ -0,0 +1,15 @@
+use p3_air::AirBuilder;
+use p3_field::{AbstractField, Field};
+
+// Reconstructs a big endian bit decomposition.
+pub(crate) fn reconstruct<AB: AirBuilder>(bits: &[AB::Var]) -> AB::Expr {
+    bits.iter().fold(AB::Expr::zero(), |acc, &bit| acc * AB::F::two() + bit)
+}
+
+// Writes the low `bits.len()` bits of `value` in big endian order.
+pub(crate) fn write_bits<F: Field>(bits: &mut [F], value: u64) {
+    let n = bits.len();
+    for (i, bit) in bits.iter_mut().enumerate() {
+        *bit = F::from_bool((value >> (n - 1 - i)) & 1 == 1);
+    }
+}
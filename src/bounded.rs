@@ -0,0 +1,169 @@
+use p3_air::{Air, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, Field};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::babybear_v1::{self, MyConfig, DEFAULT_FRI_PARAMS};
+use crate::bits::{reconstruct, write_bits};
+use crate::error::Error;
+use crate::field::FieldKind;
+use crate::proof_info::ProofStats;
+use crate::timings;
+
+// Bit width of the differences to the bounds. Inputs are below `2^BOUNDED_BITS`, so an honest difference fits in
+// the bits, while a negative one wraps around the modulus to at least `p - 2^29 > 2^30` and can't be decomposed.
+pub const BOUNDED_BITS: usize = 29;
+
+const WIDTH: usize = 2 * BOUNDED_BITS;
+
+// Proves `min <= value <= max`, with a single row:
+// | value - min bits | max - value bits |
+// Public values are `[value, min, max]`, and have to match the statement the AIR is built for.
+pub struct BoundedRangeCheckAir {
+    pub value: u32,
+    pub min: u32,
+    pub max: u32,
+}
+
+impl<F: Field> BaseAir<F> for BoundedRangeCheckAir {
+    fn width(&self) -> usize {
+        WIDTH
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for BoundedRangeCheckAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+
+        let public_values = builder.public_values();
+        let value: AB::Expr = public_values[0].into();
+        let min: AB::Expr = public_values[1].into();
+        let max: AB::Expr = public_values[2].into();
+
+        builder.assert_eq(value.clone(), AB::Expr::from_canonical_u32(self.value));
+        builder.assert_eq(min.clone(), AB::Expr::from_canonical_u32(self.min));
+        builder.assert_eq(max.clone(), AB::Expr::from_canonical_u32(self.max));
+
+        // Making sure every bit is either 0 or 1
+        for &bit in local.iter() {
+            builder.assert_bool(bit);
+        }
+
+        let above_min = reconstruct::<AB>(&local[..BOUNDED_BITS]);
+        let below_max = reconstruct::<AB>(&local[BOUNDED_BITS..]);
+        builder.assert_eq(above_min, value.clone() - min);
+        builder.assert_eq(below_max, max - value);
+    }
+}
+
+// Requires `min <= value <= max`.
+pub fn generate_trace<F: Field>(value: u32, min: u32, max: u32) -> RowMajorMatrix<F> {
+    let mut row = vec![F::zero(); WIDTH];
+    write_bits(&mut row[..BOUNDED_BITS], u64::from(value - min));
+    write_bits(&mut row[BOUNDED_BITS..], u64::from(max - value));
+    RowMajorMatrix::new(row, WIDTH)
+}
+
+fn public_values(value: u32, min: u32, max: u32) -> Vec<BabyBear> {
+    vec![BabyBear::from_canonical_u32(value), BabyBear::from_canonical_u32(min), BabyBear::from_canonical_u32(max)]
+}
+
+fn check_inputs(value: u32, min: u32, max: u32) -> Result<(), Error> {
+    for input in [value, min, max] {
+        if input >> BOUNDED_BITS != 0 {
+            return Err(Error::InvalidInput(format!("{} doesn't fit in {} bits", input, BOUNDED_BITS)));
+        }
+    }
+    if min > max {
+        return Err(Error::InvalidInput(format!("[{}, {}] is empty", min, max)));
+    }
+    Ok(())
+}
+
+/// Proves that `min <= value <= max`, both bounds included.
+pub fn prove_bounded(value: u32, min: u32, max: u32) -> Result<Proof<MyConfig>, Error> {
+    check_inputs(value, min, max)?;
+    if value < min || value > max {
+        return Err(Error::InvalidInput(format!("{} isn't in [{}, {}]", value, min, max)));
+    }
+
+    let air = BoundedRangeCheckAir { value, min, max };
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<BabyBear>(value, min, max));
+    let config = babybear_v1::config(trace.height(), &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    Ok(info_span!("commit_trace")
+        .in_scope(|| p3_uni_stark::prove(&config, &air, &mut challenger, trace, &public_values(value, min, max))))
+}
+
+pub fn verify_bounded(value: u32, min: u32, max: u32, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    check_inputs(value, min, max)?;
+    let air = BoundedRangeCheckAir { value, min, max };
+    let config = babybear_v1::config(1, &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    p3_uni_stark::verify(&config, &air, &mut challenger, proof, &public_values(value, min, max))
+        .map_err(|e| Error::verification_failed(FieldKind::BabyBear, u64::from(value), e))
+}
+
+/// Proves and verifies `min <= value <= max`, and returns the proof of work stats of the proof, which are also
+/// logged.
+pub fn prove_and_verify_bounded(value: u32, min: u32, max: u32) -> Result<ProofStats, Error> {
+    timings::init_tracing();
+
+    let proof = info_span!("prove").in_scope(|| prove_bounded(value, min, max))?;
+    info_span!("verify").in_scope(|| verify_bounded(value, min, max, &proof))?;
+
+    let stats = ProofStats::from_proof(&proof, &DEFAULT_FRI_PARAMS)?;
+    tracing::info!("{}", stats);
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::debug::{babybear_difference, satisfies};
+
+    // The trace for any claim, an out of bounds value has one of its differences wrapped.
+    fn satisfied_forged(value: u32, min: u32, max: u32) -> bool {
+        let mut row = vec![BabyBear::zero(); WIDTH];
+        write_bits(&mut row[..BOUNDED_BITS], babybear_difference(value, min));
+        write_bits(&mut row[BOUNDED_BITS..], babybear_difference(max, value));
+        let air = BoundedRangeCheckAir { value, min, max };
+        satisfies(&air, &RowMajorMatrix::new(row, WIDTH), &public_values(value, min, max))
+    }
+
+    #[test]
+    fn values_within_the_bounds_pass() {
+        for value in [10, 11, 19, 20] {
+            assert!(satisfied_forged(value, 10, 20), "{}", value);
+        }
+        assert!(satisfied_forged(7, 7, 7));
+        assert!(satisfied_forged((1 << 29) - 1, 0, (1 << 29) - 1));
+    }
+
+    #[test]
+    fn values_outside_the_bounds_fail() {
+        for value in [0, 9, 21, (1 << 29) - 1] {
+            assert!(!satisfied_forged(value, 10, 20), "{}", value);
+        }
+    }
+
+    #[test]
+    fn bounds_are_inclusive() {
+        for value in [10, 20] {
+            let proof = prove_bounded(value, 10, 20).unwrap();
+            verify_bounded(value, 10, 20, &proof).unwrap();
+        }
+        let proof = prove_bounded(10, 10, 20).unwrap();
+        assert!(verify_bounded(10, 11, 20, &proof).is_err());
+        assert!(matches!(prove_bounded(21, 10, 20), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_bounded(15, 20, 10), Err(Error::InvalidInput(_))));
+        assert!(prove_and_verify_bounded(15, 10, 20).is_ok());
+    }
+}
@@ -0,0 +1,319 @@
+#[cfg(any(feature = "babybear", feature = "goldilocks", feature = "koalabear", feature = "mersenne31"))]
+use p3_keccak::Keccak256Hash;
+
+use crate::config::FriParams;
+use crate::error::Error;
+use crate::field::{check_value_in_field, FieldKind};
+use crate::serialize::{self, default_params};
+#[cfg(any(feature = "babybear", feature = "koalabear", feature = "mersenne31"))]
+use crate::serialize::to_u32;
+#[cfg(feature = "babybear")]
+use crate::babybear_recursion;
+#[cfg(feature = "babybear")]
+use crate::babybear_v1;
+#[cfg(feature = "goldilocks")]
+use crate::goldilocks_v1;
+#[cfg(feature = "koalabear")]
+use crate::koalabear;
+#[cfg(feature = "mersenne31")]
+use crate::m31;
+
+/// Hash used for the Merkle commitments and the Fiat-Shamir transcript.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashBackend {
+    Keccak,
+    /// BabyBear only, with the configuration of `babybear_recursion`.
+    Poseidon2,
+}
+
+/// Fluent configuration of a `RangeCheckProver`, e.g.
+/// `RangeCheckBuilder::babybear().log_blowup(1).num_queries(80).domain(b"myapp").build()?.prove(value)`.
+///
+/// Starts from the field's default FRI parameters, Keccak and an empty domain.
+#[derive(Clone, Debug)]
+pub struct RangeCheckBuilder {
+    field: FieldKind,
+    params: FriParams,
+    hash: HashBackend,
+    domain: Vec<u8>,
+}
+
+impl RangeCheckBuilder {
+    /// Returns `Error::UnsupportedField` if the field's feature is disabled.
+    pub fn new(field: FieldKind) -> Result<Self, Error> {
+        Ok(Self {
+            field,
+            params: default_params(field)?,
+            hash: HashBackend::Keccak,
+            domain: vec![],
+        })
+    }
+
+    #[cfg(feature = "mersenne31")]
+    pub fn mersenne31() -> Self {
+        Self::new(FieldKind::Mersenne31).unwrap()
+    }
+
+    #[cfg(feature = "babybear")]
+    pub fn babybear() -> Self {
+        Self::new(FieldKind::BabyBear).unwrap()
+    }
+
+    #[cfg(feature = "goldilocks")]
+    pub fn goldilocks() -> Self {
+        Self::new(FieldKind::Goldilocks).unwrap()
+    }
+
+    #[cfg(feature = "koalabear")]
+    pub fn koalabear() -> Self {
+        Self::new(FieldKind::KoalaBear).unwrap()
+    }
+
+    pub fn params(mut self, params: FriParams) -> Self {
+        self.params = params;
+        self
+    }
+
+    pub fn log_blowup(mut self, log_blowup: usize) -> Self {
+        self.params.log_blowup = log_blowup;
+        self
+    }
+
+    pub fn num_queries(mut self, num_queries: usize) -> Self {
+        self.params.num_queries = num_queries;
+        self
+    }
+
+    pub fn proof_of_work_bits(mut self, proof_of_work_bits: usize) -> Self {
+        self.params.proof_of_work_bits = proof_of_work_bits;
+        self
+    }
+
+    pub fn hash(mut self, hash: HashBackend) -> Self {
+        self.hash = hash;
+        self
+    }
+
+    // Seeds the transcript, so proofs made under one domain don't verify under another.
+    pub fn domain(mut self, domain: &[u8]) -> Self {
+        self.domain = domain.to_vec();
+        self
+    }
+
+    /// Returns `Error::InvalidInput` for `HashBackend::Poseidon2` with any field but BabyBear.
+    pub fn build(self) -> Result<RangeCheckProver, Error> {
+        if self.hash == HashBackend::Poseidon2 && self.field != FieldKind::BabyBear {
+            return Err(Error::InvalidInput(format!("{:?} is only supported for BabyBear", self.hash)));
+        }
+        Ok(RangeCheckProver {
+            field: self.field,
+            params: self.params,
+            hash: self.hash,
+            domain: self.domain,
+        })
+    }
+}
+
+/// A configured prover and verifier, reusable for any number of values.
+///
+/// Proofs are the tagged bytes of `serialize`, but are bound to the domain and only verify with a prover of the
+/// same domain and hash.
+#[derive(Clone, Debug)]
+pub struct RangeCheckProver {
+    field: FieldKind,
+    params: FriParams,
+    hash: HashBackend,
+    domain: Vec<u8>,
+}
+
+impl RangeCheckProver {
+    pub fn field(&self) -> FieldKind {
+        self.field
+    }
+
+    pub fn params(&self) -> &FriParams {
+        &self.params
+    }
+
+    pub fn hash(&self) -> HashBackend {
+        self.hash
+    }
+
+    pub fn prove(&self, value: u64) -> Result<Vec<u8>, Error> {
+        let (field, params) = (self.field, &self.params);
+        // Debug builds panic on a trace the AIR rejects, so a value outside the field is an error before proving
+        check_value_in_field(field, value)?;
+        match field {
+            #[cfg(feature = "mersenne31")]
+            FieldKind::Mersenne31 => {
+                let mut challenger = m31::Challenger::from_hasher(self.domain.clone(), Keccak256Hash {});
+                let value = to_u32(field, value)?;
+                let proof = m31::prove_with_challenger(value, &m31::public_values(value), params, &mut challenger);
+                serialize::serialize_tagged(field, params, &proof)
+            }
+            #[cfg(feature = "babybear")]
+            FieldKind::BabyBear if self.hash == HashBackend::Poseidon2 => {
+                let mut challenger = babybear_recursion::domain_challenger(&self.domain);
+                let value = to_u32(field, value)?;
+                let proof = babybear_recursion::prove_with_challenger(value, params, &mut challenger);
+                serialize::serialize_tagged(field, params, &proof)
+            }
+            #[cfg(feature = "babybear")]
+            FieldKind::BabyBear => {
+                let mut challenger = babybear_v1::Challenger::from_hasher(self.domain.clone(), Keccak256Hash {});
+                let value = to_u32(field, value)?;
+                let public_values = babybear_v1::public_values(value);
+                let proof = babybear_v1::prove_with_challenger(value, &public_values, params, &mut challenger);
+                serialize::serialize_tagged(field, params, &proof)
+            }
+            #[cfg(feature = "goldilocks")]
+            FieldKind::Goldilocks => {
+                let mut challenger = goldilocks_v1::Challenger::from_hasher(self.domain.clone(), Keccak256Hash {});
+                let public_values = goldilocks_v1::public_values(value);
+                let proof = goldilocks_v1::prove_with_challenger(value, &public_values, params, &mut challenger);
+                serialize::serialize_tagged(field, params, &proof)
+            }
+            #[cfg(feature = "koalabear")]
+            FieldKind::KoalaBear => {
+                let mut challenger = koalabear::Challenger::from_hasher(self.domain.clone(), Keccak256Hash {});
+                let value = to_u32(field, value)?;
+                let public_values = koalabear::public_values(value);
+                let proof = koalabear::prove_with_challenger(value, &public_values, params, &mut challenger);
+                serialize::serialize_tagged(field, params, &proof)
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(Error::UnsupportedField(field)),
+        }
+    }
+
+    /// Returns `Error::FieldMismatch` or `Error::ParameterMismatch` if the proof wasn't made by this configuration.
+    pub fn verify(&self, bytes: &[u8], value: u64) -> Result<(), Error> {
+        let (found, found_params) = serialize::read_header(bytes)?;
+        if found != self.field {
+            return Err(Error::FieldMismatch { expected: self.field, found });
+        }
+        if found_params != self.params {
+            return Err(Error::ParameterMismatch {
+                expected: self.params,
+                found: found_params,
+            });
+        }
+        check_value_in_field(found, value)?;
+
+        let params = &self.params;
+        match found {
+            #[cfg(feature = "mersenne31")]
+            FieldKind::Mersenne31 => {
+                let (_, _, proof) = serialize::deserialize_tagged(bytes)?;
+                let mut challenger = m31::Challenger::from_hasher(self.domain.clone(), Keccak256Hash {});
+                let value = to_u32(found, value)?;
+                m31::verify_with_challenger(value, &m31::public_values(value), &proof, params, &mut challenger)
+            }
+            #[cfg(feature = "babybear")]
+            FieldKind::BabyBear if self.hash == HashBackend::Poseidon2 => {
+                let (_, _, proof) = serialize::deserialize_tagged(bytes)?;
+                let mut challenger = babybear_recursion::domain_challenger(&self.domain);
+                babybear_recursion::verify_with_challenger(to_u32(found, value)?, &proof, params, &mut challenger)
+            }
+            #[cfg(feature = "babybear")]
+            FieldKind::BabyBear => {
+                let (_, _, proof) = serialize::deserialize_tagged(bytes)?;
+                let mut challenger = babybear_v1::Challenger::from_hasher(self.domain.clone(), Keccak256Hash {});
+                let value = to_u32(found, value)?;
+                let public_values = babybear_v1::public_values(value);
+                babybear_v1::verify_with_challenger(value, &public_values, &proof, params, &mut challenger)
+            }
+            #[cfg(feature = "goldilocks")]
+            FieldKind::Goldilocks => {
+                let (_, _, proof) = serialize::deserialize_tagged(bytes)?;
+                let mut challenger = goldilocks_v1::Challenger::from_hasher(self.domain.clone(), Keccak256Hash {});
+                let public_values = goldilocks_v1::public_values(value);
+                goldilocks_v1::verify_with_challenger(value, &public_values, &proof, params, &mut challenger)
+            }
+            #[cfg(feature = "koalabear")]
+            FieldKind::KoalaBear => {
+                let (_, _, proof) = serialize::deserialize_tagged(bytes)?;
+                let mut challenger = koalabear::Challenger::from_hasher(self.domain.clone(), Keccak256Hash {});
+                let value = to_u32(found, value)?;
+                let public_values = koalabear::public_values(value);
+                koalabear::verify_with_challenger(value, &public_values, &proof, params, &mut challenger)
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(Error::UnsupportedField(found)),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "babybear"))]
+mod tests {
+    use super::*;
+    use crate::field::max_provable_value;
+
+    #[test]
+    fn builder_sets_the_parameters() {
+        let prover = RangeCheckBuilder::babybear().log_blowup(2).num_queries(80).build().unwrap();
+        assert_eq!(prover.params().log_blowup, 2);
+        assert_eq!(prover.params().num_queries, 80);
+        assert_eq!(prover.params().proof_of_work_bits, babybear_v1::DEFAULT_FRI_PARAMS.proof_of_work_bits);
+
+        let bytes = prover.prove(100).unwrap();
+        prover.verify(&bytes, 100).unwrap();
+        assert!(prover.verify(&bytes, 101).is_err());
+    }
+
+    #[test]
+    fn proofs_are_bound_to_the_domain() {
+        let prover = RangeCheckBuilder::babybear().domain(b"myapp").build().unwrap();
+        let bytes = prover.prove(100).unwrap();
+        prover.verify(&bytes, 100).unwrap();
+
+        let other = RangeCheckBuilder::babybear().domain(b"otherapp").build().unwrap();
+        assert!(matches!(
+            other.verify(&bytes, 100),
+            Err(Error::VerificationFailed { field: FieldKind::BabyBear, value: 100, .. })
+        ));
+    }
+
+    #[test]
+    fn every_enabled_field_proves_and_verifies() {
+        for &field in FieldKind::all().iter().filter(|field| field.is_enabled()) {
+            let prover = RangeCheckBuilder::new(field).unwrap().domain(b"myapp").build().unwrap();
+            let bytes = prover.prove(100).unwrap();
+            prover.verify(&bytes, 100).unwrap();
+            assert!(prover.verify(&bytes, 101).is_err());
+        }
+    }
+
+    #[test]
+    fn values_outside_the_field_are_rejected_before_proving() {
+        for &field in FieldKind::all().iter().filter(|field| field.is_enabled()) {
+            let prover = RangeCheckBuilder::new(field).unwrap().build().unwrap();
+            let first_outside = u64::try_from(max_provable_value(field) + 1).unwrap_or(u64::MAX);
+            for value in [first_outside, u64::MAX] {
+                assert!(matches!(prover.prove(value), Err(Error::ValueOutOfField { .. })), "{:?} {}", field, value);
+            }
+        }
+        let poseidon2 = RangeCheckBuilder::babybear().hash(HashBackend::Poseidon2).build().unwrap();
+        assert!(matches!(poseidon2.prove(u64::MAX), Err(Error::ValueOutOfField { .. })));
+    }
+
+    #[test]
+    fn poseidon2_proofs_verify_with_a_poseidon2_prover() {
+        let prover = RangeCheckBuilder::babybear().hash(HashBackend::Poseidon2).domain(b"myapp").build().unwrap();
+        let bytes = prover.prove(100).unwrap();
+        prover.verify(&bytes, 100).unwrap();
+        assert!(prover.verify(&bytes, 101).is_err());
+
+        let keccak = RangeCheckBuilder::babybear().domain(b"myapp").build().unwrap();
+        assert!(keccak.verify(&bytes, 100).is_err());
+        let other = RangeCheckBuilder::babybear().hash(HashBackend::Poseidon2).domain(b"otherapp").build().unwrap();
+        assert!(other.verify(&bytes, 100).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "goldilocks")]
+    fn poseidon2_is_rejected_for_other_fields() {
+        let result = RangeCheckBuilder::goldilocks().hash(HashBackend::Poseidon2).build();
+        assert!(matches!(result, Err(Error::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::FriParams;
+use crate::error::Error;
+use crate::field::{check_value_in_field, FieldKind};
+use crate::serialize;
+
+/// Everything needed to verify a range proof, so that the bundle bytes alone can be checked with `verify_bundle`.
+///
+/// `proof` holds the tagged proof bytes of `serialize`, whose header has to agree with `field` and `fri_params`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bundle {
+    pub field: FieldKind,
+    pub value: u64,
+    pub fri_params: FriParams,
+    pub proof: Vec<u8>,
+}
+
+impl Bundle {
+    /// Proves `value` is in range of `field` with the default FRI parameters of the field.
+    pub fn prove(field: FieldKind, value: u64) -> Result<Self, Error> {
+        Self::prove_with_params(field, value, &serialize::default_params(field)?)
+    }
+
+    /// Returns `Error::ValueOutOfField` if `value` isn't below the order of `field`.
+    pub fn prove_with_params(field: FieldKind, value: u64, params: &FriParams) -> Result<Self, Error> {
+        check_value_in_field(field, value)?;
+        let proof = serialize::prove_with_params(field, value, params)?;
+        Ok(Bundle { field, value, fri_params: *params, proof })
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        bincode::serialize(self).map_err(|e| Error::Serialization(e.to_string()))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        bincode::deserialize(bytes).map_err(|e| Error::Serialization(e.to_string()))
+    }
+
+    /// Verifies the proof for the bundled value, field and FRI parameters.
+    ///
+    /// The parameters come with the bundle, so they are rejected with `Error::InsufficientSecurity` if they are
+    /// too weak to trust.
+    pub fn verify(&self) -> Result<(), Error> {
+        self.fri_params.check_security()?;
+        serialize::verify_with_params(self.field, &self.proof, self.value, &self.fri_params)
+    }
+}
+
+/// Proves `value` is in range of `field` and returns the serialized `Bundle`.
+pub fn prove_bundle(field: FieldKind, value: u64) -> Result<Vec<u8>, Error> {
+    Bundle::prove(field, value)?.to_bytes()
+}
+
+/// Verifies a serialized `Bundle`, trusting nothing but the bytes and the crate.
+///
+/// Returns `Error::FieldMismatch` or `Error::ParameterMismatch` if the proof's own header disagrees with the
+/// bundle, so a bundle can't claim a stronger parameter set than its proof was made with.
+pub fn verify_bundle(bytes: &[u8]) -> Result<(), Error> {
+    Bundle::from_bytes(bytes)?.verify()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundles_round_trip_for_every_field() {
+        for &field in FieldKind::all().iter().filter(|field| field.is_enabled()) {
+            let bytes = prove_bundle(field, 100).unwrap();
+            let bundle = Bundle::from_bytes(&bytes).unwrap();
+            assert_eq!(bundle.field, field);
+            assert_eq!(bundle.value, 100);
+            assert_eq!(bundle.fri_params, serialize::default_params(field).unwrap());
+            assert_eq!(bundle.to_bytes().unwrap(), bytes);
+            verify_bundle(&bytes).unwrap();
+
+            let forged = Bundle { value: 101, ..bundle };
+            assert!(matches!(forged.verify(), Err(Error::VerificationFailed { value: 101, .. })));
+
+            assert!(matches!(prove_bundle(field, u64::MAX), Err(Error::ValueOutOfField { .. })));
+        }
+    }
+
+    #[test]
+    fn bundle_has_to_agree_with_its_proof() {
+        for &field in FieldKind::all().iter().filter(|field| field.is_enabled()) {
+            let bundle = Bundle::prove(field, 7).unwrap();
+            let stronger = FriParams { num_queries: bundle.fri_params.num_queries + 1, ..bundle.fri_params };
+            let forged = Bundle { fri_params: stronger, ..bundle.clone() };
+            assert!(matches!(forged.verify(), Err(Error::ParameterMismatch { .. })));
+
+            if let Some(&other) = FieldKind::all().iter().find(|&&other| other != field) {
+                let forged = Bundle { field: other, ..bundle };
+                assert!(matches!(forged.verify(), Err(Error::FieldMismatch { .. })));
+            }
+        }
+    }
+
+    #[test]
+    fn weak_bundles_are_rejected() {
+        for &field in FieldKind::all().iter().filter(|field| field.is_enabled()) {
+            let defaults = serialize::default_params(field).unwrap();
+            let weak = FriParams { num_queries: 1, proof_of_work_bits: 0, ..defaults };
+            let bytes = Bundle::prove_with_params(field, 100, &weak).unwrap().to_bytes().unwrap();
+            assert!(matches!(verify_bundle(&bytes), Err(Error::InsufficientSecurity { .. })));
+        }
+    }
+
+    #[test]
+    fn garbage_is_a_serialization_error() {
+        assert!(matches!(verify_bundle(&[1, 2, 3]), Err(Error::Serialization(_))));
+    }
+}
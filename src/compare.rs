@@ -0,0 +1,154 @@
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, Field};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::babybear_v1::{self, MyConfig, DEFAULT_FRI_PARAMS};
+use crate::bits::{reconstruct, write_bits};
+use crate::error::{Error, VerificationFailure};
+use crate::proof_info::ProofStats;
+use crate::timings;
+
+// Bit width of the operands and of their difference. With both operands below `2^COMPARE_BITS`, `b - a - 1` fits
+// in the bits iff `a < b`, otherwise it wraps around the modulus to at least `p - 2^29 > 2^30`.
+pub const COMPARE_BITS: usize = 29;
+
+const A: usize = 0;
+const B: usize = COMPARE_BITS;
+const DIFFERENCE: usize = 2 * COMPARE_BITS;
+const WIDTH: usize = 3 * COMPARE_BITS;
+
+// Proves `a < b` for two private operands, with a single row:
+// | a bits | b bits | b - a - 1 bits |
+// The operands are only witness columns, there are no public values and the AIR is the same for every pair, so
+// the statement is that the committed operands compare. `p3_uni_stark` isn't zero knowledge though, the trace
+// openings of the proof still leak information about them.
+pub struct LessThanAir;
+
+impl<F: Field> BaseAir<F> for LessThanAir {
+    fn width(&self) -> usize {
+        WIDTH
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for LessThanAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+
+        // Making sure every bit is either 0 or 1
+        for &bit in local.iter() {
+            builder.assert_bool(bit);
+        }
+
+        let a = reconstruct::<AB>(&local[A..B]);
+        let b = reconstruct::<AB>(&local[B..DIFFERENCE]);
+        let difference = reconstruct::<AB>(&local[DIFFERENCE..]);
+        builder.assert_eq(difference, b - a - AB::Expr::one());
+    }
+}
+
+// Requires `a < b`.
+pub fn generate_trace<F: Field>(a: u32, b: u32) -> RowMajorMatrix<F> {
+    let mut row = vec![F::zero(); WIDTH];
+    write_bits(&mut row[A..B], u64::from(a));
+    write_bits(&mut row[B..DIFFERENCE], u64::from(b));
+    write_bits(&mut row[DIFFERENCE..], u64::from(b - a - 1));
+    RowMajorMatrix::new(row, WIDTH)
+}
+
+fn check_inputs(a: u32, b: u32) -> Result<(), Error> {
+    for input in [a, b] {
+        if input >> COMPARE_BITS != 0 {
+            return Err(Error::InvalidInput(format!("{} doesn't fit in {} bits", input, COMPARE_BITS)));
+        }
+    }
+    if a >= b {
+        return Err(Error::InvalidInput(format!("{} isn't less than {}", a, b)));
+    }
+    Ok(())
+}
+
+/// Proves that `a < b`, for operands below `2^COMPARE_BITS`, without putting either of them in the statement.
+pub fn prove_less_than(a: u32, b: u32) -> Result<Proof<MyConfig>, Error> {
+    check_inputs(a, b)?;
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<BabyBear>(a, b));
+    let config = babybear_v1::config(trace.height(), &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    Ok(info_span!("commit_trace")
+        .in_scope(|| p3_uni_stark::prove(&config, &LessThanAir, &mut challenger, trace, &vec![])))
+}
+
+/// Verifies a `prove_less_than` proof, which needs nothing but the proof since the operands stay private.
+pub fn verify_less_than(proof: &Proof<MyConfig>) -> Result<(), Error> {
+    let config = babybear_v1::config(1, &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    p3_uni_stark::verify(&config, &LessThanAir, &mut challenger, proof, &vec![])
+        .map_err(|e| Error::Verification(format!("less than proof: {}", VerificationFailure::from(e))))
+}
+
+/// Proves and verifies `a < b`, and returns the proof of work stats of the proof, which are also logged.
+pub fn prove_and_verify_less_than(a: u32, b: u32) -> Result<ProofStats, Error> {
+    timings::init_tracing();
+
+    let proof = info_span!("prove").in_scope(|| prove_less_than(a, b))?;
+    info_span!("verify").in_scope(|| verify_less_than(&proof))?;
+
+    let stats = ProofStats::from_proof(&proof, &DEFAULT_FRI_PARAMS)?;
+    tracing::info!("{}", stats);
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::debug::{babybear_difference, satisfies};
+
+    // The trace for any claim, the difference wraps when `a >= b`.
+    fn satisfied_forged(a: u32, b: u32) -> bool {
+        let mut row = vec![BabyBear::zero(); WIDTH];
+        write_bits(&mut row[A..B], u64::from(a));
+        write_bits(&mut row[B..DIFFERENCE], u64::from(b));
+        write_bits(&mut row[DIFFERENCE..], babybear_difference(b, a + 1));
+        let trace = RowMajorMatrix::new(row, WIDTH);
+        satisfies(&LessThanAir, &trace, &[])
+    }
+
+    #[test]
+    fn less_than_passes() {
+        assert!(satisfied_forged(0, 1));
+        assert!(satisfied_forged(5, 100));
+        assert!(satisfied_forged(0, (1 << COMPARE_BITS) - 1));
+        prove_and_verify_less_than(5, 100).unwrap();
+    }
+
+    #[test]
+    fn equal_operands_fail() {
+        assert!(!satisfied_forged(0, 0));
+        assert!(!satisfied_forged(100, 100));
+        assert!(matches!(prove_and_verify_less_than(100, 100), Err(Error::InvalidInput(_))));
+    }
+
+    #[test]
+    fn operands_stay_out_of_the_statement() {
+        // Proofs for different operands are checked by the same verifier, which never sees the operands
+        for (a, b) in [(5, 100), (0, (1 << COMPARE_BITS) - 1)] {
+            let proof = prove_less_than(a, b).unwrap();
+            verify_less_than(&proof).unwrap();
+        }
+    }
+
+    #[test]
+    fn greater_operands_fail() {
+        assert!(!satisfied_forged(1, 0));
+        assert!(!satisfied_forged(101, 100));
+        assert!(!satisfied_forged((1 << COMPARE_BITS) - 1, 0));
+        assert!(matches!(prove_and_verify_less_than(101, 100), Err(Error::InvalidInput(_))));
+    }
+}
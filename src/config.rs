@@ -0,0 +1,242 @@
+use std::marker::PhantomData;
+use std::ops::RangeInclusive;
+
+#[cfg(feature = "babybear")]
+use p3_baby_bear::BabyBear;
+use p3_fri::FriConfig;
+#[cfg(feature = "goldilocks")]
+use p3_goldilocks::Goldilocks;
+#[cfg(feature = "koalabear")]
+use p3_koala_bear::KoalaBear;
+#[cfg(feature = "mersenne31")]
+use p3_mersenne_31::Mersenne31;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+#[cfg(feature = "babybear")]
+use crate::babybear_v1;
+#[cfg(feature = "goldilocks")]
+use crate::goldilocks_v1;
+#[cfg(feature = "koalabear")]
+use crate::koalabear;
+#[cfg(feature = "mersenne31")]
+use crate::m31;
+
+/// Number of children per Merkle tree node, shared by the compression function and the MMCS of every prover.
+///
+/// `p3_merkle_tree::MerkleTreeMmcs` only accepts 2-to-1 compression functions, so this can't be raised until the
+/// MMCS supports wider trees. Keeping it in one place makes sure the compression function and the MMCS agree.
+pub const MERKLE_ARITY: usize = 2;
+
+/// Least conjectured security, in bits, of FRI parameters a verifier takes from a proof instead of choosing them.
+pub const MIN_SECURITY_BITS: usize = 100;
+
+/// The FRI parameters a proof was produced with. The verifier has to use the same ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FriParams {
+    pub log_blowup: usize,
+    pub num_queries: usize,
+    pub proof_of_work_bits: usize,
+}
+
+impl FriParams {
+    // Conjectured security of FRI: every query contributes `log_blowup` bits on top of the grinding bits.
+    pub fn conjectured_security_bits(&self) -> usize {
+        self.log_blowup * self.num_queries + self.proof_of_work_bits
+    }
+
+    /// Returns `Error::InsufficientSecurity` if these parameters give less than `MIN_SECURITY_BITS`.
+    pub fn check_security(&self) -> Result<(), Error> {
+        if self.conjectured_security_bits() < MIN_SECURITY_BITS {
+            return Err(Error::InsufficientSecurity {
+                params: *self,
+                min_bits: MIN_SECURITY_BITS,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn fri_config<M>(&self, mmcs: M) -> FriConfig<M> {
+        FriConfig {
+            log_blowup: self.log_blowup,
+            num_queries: self.num_queries,
+            proof_of_work_bits: self.proof_of_work_bits,
+            mmcs,
+        }
+    }
+}
+
+// Environment variables the CLI reads to override the FRI parameters of the chosen range check.
+pub const LOG_BLOWUP_ENV_VAR: &str = "RANGECHECK_LOG_BLOWUP";
+pub const NUM_QUERIES_ENV_VAR: &str = "RANGECHECK_NUM_QUERIES";
+pub const POW_BITS_ENV_VAR: &str = "RANGECHECK_POW_BITS";
+
+// Reads `name` as a number in `range`, `None` if it isn't set.
+fn read_env_var(name: &str, range: RangeInclusive<usize>) -> Result<Option<usize>, Error> {
+    let Some(raw) = std::env::var_os(name) else {
+        return Ok(None);
+    };
+    let invalid = || {
+        Error::InvalidInput(format!("{}={:?} has to be a number in [{}, {}]", name, raw, range.start(), range.end()))
+    };
+    let value = raw.to_str().and_then(|raw| raw.parse::<usize>().ok()).ok_or_else(invalid)?;
+    if !range.contains(&value) {
+        return Err(invalid());
+    }
+    Ok(Some(value))
+}
+
+/// `defaults` with every parameter whose environment variable is set replaced by its value.
+///
+/// Returns `Error::InvalidInput` for values that aren't numbers or are out of bounds instead of falling back.
+pub fn fri_params_from_env(defaults: FriParams) -> Result<FriParams, Error> {
+    // Grinding needs `proof_of_work_bits` below the bit width of the smallest field.
+    Ok(FriParams {
+        log_blowup: read_env_var(LOG_BLOWUP_ENV_VAR, 1..=16)?.unwrap_or(defaults.log_blowup),
+        num_queries: read_env_var(NUM_QUERIES_ENV_VAR, 1..=1000)?.unwrap_or(defaults.num_queries),
+        proof_of_work_bits: read_env_var(POW_BITS_ENV_VAR, 0..=30)?.unwrap_or(defaults.proof_of_work_bits),
+    })
+}
+
+/// FRI configuration for range check proofs over the field `F`.
+///
+/// `RangeCheckConfig::default()` gives the parameters each field's prover has always used, individual
+/// parameters can be overridden from there and the result passed on as `config.fri`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RangeCheckConfig<F> {
+    pub fri: FriParams,
+    _field: PhantomData<F>,
+}
+
+impl<F> RangeCheckConfig<F> {
+    pub fn new(fri: FriParams) -> Self {
+        Self { fri, _field: PhantomData }
+    }
+
+    pub fn log_blowup(mut self, log_blowup: usize) -> Self {
+        self.fri.log_blowup = log_blowup;
+        self
+    }
+
+    pub fn num_queries(mut self, num_queries: usize) -> Self {
+        self.fri.num_queries = num_queries;
+        self
+    }
+
+    pub fn proof_of_work_bits(mut self, proof_of_work_bits: usize) -> Self {
+        self.fri.proof_of_work_bits = proof_of_work_bits;
+        self
+    }
+}
+
+/// `log_blowup: 1`, `num_queries: 100`, `proof_of_work_bits: 16`.
+#[cfg(feature = "mersenne31")]
+impl Default for RangeCheckConfig<Mersenne31> {
+    fn default() -> Self {
+        Self::new(m31::DEFAULT_FRI_PARAMS)
+    }
+}
+
+/// `log_blowup: 1`, `num_queries: 100`, `proof_of_work_bits: 16`, as used by `babybear_v1`.
+#[cfg(feature = "babybear")]
+impl Default for RangeCheckConfig<BabyBear> {
+    fn default() -> Self {
+        Self::new(babybear_v1::DEFAULT_FRI_PARAMS)
+    }
+}
+
+/// `log_blowup: 5`, `num_queries: 100`, `proof_of_work_bits: 16`.
+#[cfg(feature = "goldilocks")]
+impl Default for RangeCheckConfig<Goldilocks> {
+    fn default() -> Self {
+        Self::new(goldilocks_v1::DEFAULT_FRI_PARAMS)
+    }
+}
+
+/// `log_blowup: 1`, `num_queries: 100`, `proof_of_work_bits: 16`.
+#[cfg(feature = "koalabear")]
+impl Default for RangeCheckConfig<KoalaBear> {
+    fn default() -> Self {
+        Self::new(koalabear::DEFAULT_FRI_PARAMS)
+    }
+}
+
+/// Parameter presets for `RangeCheckConfig::preset`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofSizePreset {
+    /// The parameters of `RangeCheckConfig::default()`.
+    Default,
+    /// The smallest proofs at 100 conjectured bits of security, for posting proofs on-chain.
+    ///
+    /// Proof size is dominated by the FRI queries, each opening a Merkle path per committed matrix. Raising
+    /// `log_blowup` buys the same security with fewer queries: 21 queries at a blowup of 16 instead of 100 at
+    /// a blowup of 2 for Mersenne31 and BabyBear, 14 queries at a blowup of 64 for Goldilocks. Each path gets a
+    /// few levels longer, so the proofs end up at roughly a fifth to a quarter of the default size, while
+    /// proving commits to an 8 to 16 times larger LDE and is slower accordingly.
+    Minimal,
+}
+
+/// Fields with a `ProofSizePreset::Minimal` parameter set.
+pub trait MinimalParams {
+    const MINIMAL_FRI_PARAMS: FriParams;
+}
+
+#[cfg(feature = "mersenne31")]
+impl MinimalParams for Mersenne31 {
+    const MINIMAL_FRI_PARAMS: FriParams = m31::MINIMAL_FRI_PARAMS;
+}
+
+#[cfg(feature = "babybear")]
+impl MinimalParams for BabyBear {
+    const MINIMAL_FRI_PARAMS: FriParams = babybear_v1::MINIMAL_FRI_PARAMS;
+}
+
+#[cfg(feature = "goldilocks")]
+impl MinimalParams for Goldilocks {
+    const MINIMAL_FRI_PARAMS: FriParams = goldilocks_v1::MINIMAL_FRI_PARAMS;
+}
+
+#[cfg(feature = "koalabear")]
+impl MinimalParams for KoalaBear {
+    const MINIMAL_FRI_PARAMS: FriParams = koalabear::MINIMAL_FRI_PARAMS;
+}
+
+impl<F: MinimalParams> RangeCheckConfig<F>
+where
+    Self: Default,
+{
+    pub fn preset(preset: ProofSizePreset) -> Self {
+        match preset {
+            ProofSizePreset::Default => Self::default(),
+            ProofSizePreset::Minimal => Self::new(F::MINIMAL_FRI_PARAMS),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "babybear", feature = "goldilocks", feature = "mersenne31"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minimal_presets_keep_100_bits_of_security() {
+        let mut minimal = vec![
+            RangeCheckConfig::<Mersenne31>::preset(ProofSizePreset::Minimal).fri,
+            RangeCheckConfig::<BabyBear>::preset(ProofSizePreset::Minimal).fri,
+            RangeCheckConfig::<Goldilocks>::preset(ProofSizePreset::Minimal).fri,
+        ];
+        #[cfg(feature = "koalabear")]
+        minimal.push(RangeCheckConfig::<KoalaBear>::preset(ProofSizePreset::Minimal).fri);
+        for params in minimal {
+            assert!(params.check_security().is_ok(), "{:?}", params);
+        }
+    }
+
+    #[test]
+    fn minimal_babybear_proofs_are_smaller() {
+        let default = babybear_v1::prove(100);
+        let minimal = babybear_v1::prove_with_params(100, &babybear_v1::MINIMAL_FRI_PARAMS);
+        assert!(babybear_v1::verify_with_params(100, &minimal, &babybear_v1::MINIMAL_FRI_PARAMS).is_ok());
+        let size = |proof| bincode::serialized_size(proof).unwrap();
+        assert!(size(&minimal) < size(&default));
+    }
+}
@@ -0,0 +1,101 @@
+use p3_baby_bear::BabyBear;
+use p3_challenger::CanObserve;
+use p3_field::AbstractField;
+use p3_uni_stark::Proof;
+
+use crate::babybear_v1::{self, public_values, Challenger, MyConfig, DEFAULT_FRI_PARAMS};
+use crate::error::Error;
+use crate::field::{check_value_in_field, FieldKind};
+
+/// BabyBear range check proofs for two values, proven together under one transcript.
+///
+/// Both halves come from `prove_joint`, existing standalone proofs can't be turned into a `JointProof`.
+///
+/// Both values are observed by the challenger before anything else, and `right` continues the transcript
+/// where `left` ended. The verifier replays the same transcript from the values it is given, so every
+/// challenge of both proofs depends on `(a, b)` in that order and on all of `left`'s commitments. A `left`
+/// or `right` taken from a joint proof over other values, from the opposite order, or from a standalone
+/// proof was made against different challenges and fails verification, so the two halves can't be mixed
+/// and matched. Sharing the transcript doesn't add soundness error beyond that of the two proofs themselves.
+pub struct JointProof {
+    left: Proof<MyConfig>,
+    right: Proof<MyConfig>,
+}
+
+fn observe_value(challenger: &mut Challenger, value: u32) {
+    // Observed as two 16 bit limbs, since the value may not be a canonical field element
+    challenger.observe(BabyBear::from_canonical_u32(value >> 16));
+    challenger.observe(BabyBear::from_canonical_u32(value & 0xFFFF));
+}
+
+fn joint_challenger(values: (u32, u32)) -> Challenger {
+    let mut challenger = babybear_v1::challenger();
+    observe_value(&mut challenger, values.0);
+    observe_value(&mut challenger, values.1);
+    challenger
+}
+
+/// Proves that both values of `values` are in range with a single transcript.
+pub fn prove_joint(values: (u32, u32)) -> JointProof {
+    let mut challenger = joint_challenger(values);
+    let (left, right) = (public_values(values.0), public_values(values.1));
+    let left = babybear_v1::prove_with_challenger(values.0, &left, &DEFAULT_FRI_PARAMS, &mut challenger);
+    let right = babybear_v1::prove_with_challenger(values.1, &right, &DEFAULT_FRI_PARAMS, &mut challenger);
+    JointProof { left, right }
+}
+
+/// Verifies both halves of `joint_proof` against `values` under the shared transcript.
+///
+/// Returns `Error::ValueOutOfField` if either value is at least the BabyBear order.
+pub fn verify_joint(values: (u32, u32), joint_proof: &JointProof) -> Result<(), Error> {
+    check_value_in_field(FieldKind::BabyBear, u64::from(values.0))?;
+    check_value_in_field(FieldKind::BabyBear, u64::from(values.1))?;
+    let mut challenger = joint_challenger(values);
+    let (left, right) = (public_values(values.0), public_values(values.1));
+    babybear_v1::verify_with_challenger(values.0, &left, &joint_proof.left, &DEFAULT_FRI_PARAMS, &mut challenger)?;
+    babybear_v1::verify_with_challenger(values.1, &right, &joint_proof.right, &DEFAULT_FRI_PARAMS, &mut challenger)
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_field::PrimeField32;
+
+    use super::*;
+
+    #[test]
+    fn joint_proofs_verify_for_their_values() {
+        let joint_proof = prove_joint((3, 1000));
+        verify_joint((3, 1000), &joint_proof).unwrap();
+        assert!(verify_joint((3, 1001), &joint_proof).is_err());
+        assert!(verify_joint((4, 1000), &joint_proof).is_err());
+    }
+
+    #[test]
+    fn swapped_values_are_rejected() {
+        let joint_proof = prove_joint((3, 1000));
+        assert!(verify_joint((1000, 3), &joint_proof).is_err());
+        let swapped = JointProof { left: joint_proof.right, right: joint_proof.left };
+        assert!(verify_joint((1000, 3), &swapped).is_err());
+    }
+
+    #[test]
+    fn halves_of_different_joint_proofs_do_not_mix() {
+        let (first, second) = (prove_joint((3, 1000)), prove_joint((3, 2000)));
+        let mixed = JointProof { left: second.left, right: first.right };
+        assert!(verify_joint((3, 1000), &mixed).is_err());
+    }
+
+    #[test]
+    fn standalone_proofs_are_not_a_left_half() {
+        let joint_proof = prove_joint((3, 1000));
+        let standalone = JointProof { left: babybear_v1::prove(3), right: joint_proof.right };
+        assert!(verify_joint((3, 1000), &standalone).is_err());
+    }
+
+    #[test]
+    fn values_outside_the_field_are_rejected() {
+        let joint_proof = prove_joint((3, 1000));
+        let wrapped = (3 + BabyBear::ORDER_U32, 1000);
+        assert!(matches!(verify_joint(wrapped, &joint_proof), Err(Error::ValueOutOfField { .. })));
+    }
+}
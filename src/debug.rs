@@ -0,0 +1,510 @@
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues};
+use p3_field::Field;
+use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView};
+use p3_matrix::stack::VerticalPair;
+use p3_matrix::Matrix;
+use p3_uni_stark::{get_symbolic_constraints, Entry, SymbolicExpression};
+
+use crate::error::Error;
+use crate::field::FieldKind;
+#[cfg(feature = "babybear")]
+use crate::babybear_v1::BabyBearRangeCheckAir;
+#[cfg(feature = "goldilocks")]
+use crate::goldilocks_v1::GoldilocksRangeCheckAir;
+#[cfg(feature = "koalabear")]
+use crate::koalabear::KoalaBearRangeCheckAir;
+#[cfg(feature = "mersenne31")]
+use crate::m31::Mersenne31RangeCheckAir;
+
+// A constraint that did not vanish on a trace. `constraint` counts the `assert_*` calls of `eval` in order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConstraintViolation {
+    pub row: usize,
+    pub constraint: usize,
+}
+
+// Evaluates the constraints on concrete trace rows, the same way `p3_uni_stark`'s debug constraint builder
+// does, but records every constraint that doesn't vanish instead of only reporting the row.
+pub struct DebugBuilder<'a, F: Field> {
+    row_index: usize,
+    main: VerticalPair<RowMajorMatrixView<'a, F>, RowMajorMatrixView<'a, F>>,
+    public_values: &'a [F],
+    is_first_row: F,
+    is_last_row: F,
+    is_transition: F,
+    constraint_index: usize,
+    violations: Vec<ConstraintViolation>,
+}
+
+impl<'a, F: Field> AirBuilder for DebugBuilder<'a, F> {
+    type F = F;
+    type Expr = F;
+    type Var = F;
+    type M = VerticalPair<RowMajorMatrixView<'a, F>, RowMajorMatrixView<'a, F>>;
+
+    fn main(&self) -> Self::M {
+        self.main
+    }
+
+    fn is_first_row(&self) -> Self::Expr {
+        self.is_first_row
+    }
+
+    fn is_last_row(&self) -> Self::Expr {
+        self.is_last_row
+    }
+
+    fn is_transition_window(&self, size: usize) -> Self::Expr {
+        if size == 2 {
+            self.is_transition
+        } else {
+            panic!("only supports a window size of 2")
+        }
+    }
+
+    fn assert_zero<I: Into<Self::Expr>>(&mut self, x: I) {
+        if x.into() != F::zero() {
+            self.violations.push(ConstraintViolation {
+                row: self.row_index,
+                constraint: self.constraint_index,
+            });
+        }
+        self.constraint_index += 1;
+    }
+}
+
+impl<'a, F: Field> AirBuilderWithPublicValues for DebugBuilder<'a, F> {
+    type PublicVar = F;
+
+    fn public_values(&self) -> &[Self::PublicVar] {
+        self.public_values
+    }
+}
+
+// Evaluates every constraint of `air` on every row of `trace`, the last row wraps around to the first.
+pub fn check_constraints<F, A>(air: &A, trace: &RowMajorMatrix<F>, public_values: &[F]) -> Vec<ConstraintViolation>
+where
+    F: Field,
+    A: for<'a> Air<DebugBuilder<'a, F>>,
+{
+    let height = trace.height();
+    let width = trace.width();
+    let mut violations = vec![];
+
+    for row_index in 0..height {
+        let local = trace.row_slice(row_index);
+        let next = trace.row_slice((row_index + 1) % height);
+        let main = VerticalPair::new(
+            RowMajorMatrixView::new(&*local, width),
+            RowMajorMatrixView::new(&*next, width),
+        );
+
+        let mut builder = DebugBuilder {
+            row_index,
+            main,
+            public_values,
+            is_first_row: F::from_bool(row_index == 0),
+            is_last_row: F::from_bool(row_index == height - 1),
+            is_transition: F::from_bool(row_index != height - 1),
+            constraint_index: 0,
+            violations: vec![],
+        };
+        air.eval(&mut builder);
+        violations.extend(builder.violations);
+    }
+
+    violations
+}
+
+/// Checks `trace` against `air` without running FRI, panicking with the first failing constraint and row.
+pub fn debug_check<F, A>(air: &A, trace: &RowMajorMatrix<F>, public_values: &[F])
+where
+    F: Field,
+    A: for<'a> Air<DebugBuilder<'a, F>>,
+{
+    if let Some(violation) = check_constraints(air, trace, public_values).first() {
+        panic!(
+            "constraint {} had a nonzero value on row {}",
+            violation.constraint, violation.row
+        );
+    }
+}
+
+// Whether `trace` satisfies every constraint of `air`, for the tests that forge traces of each AIR.
+#[cfg(test)]
+pub(crate) fn satisfies<F, A>(air: &A, trace: &RowMajorMatrix<F>, public_values: &[F]) -> bool
+where
+    F: Field,
+    A: for<'a> Air<DebugBuilder<'a, F>>,
+{
+    check_constraints(air, trace, public_values).is_empty()
+}
+
+// `x - y` in BabyBear as a canonical value, wrapped around the modulus for `x < y` the way a forged witness for
+// an out of range claim is.
+#[cfg(all(test, feature = "babybear"))]
+pub(crate) fn babybear_difference(x: u32, y: u32) -> u64 {
+    use p3_baby_bear::BabyBear;
+    use p3_field::{AbstractField, PrimeField32};
+
+    u64::from((BabyBear::from_canonical_u32(x) - BabyBear::from_canonical_u32(y)).as_canonical_u32())
+}
+
+// Debug builds refuse to prove an unsatisfied trace, otherwise the proof of a forged trace has to fail verification.
+#[cfg(test)]
+pub(crate) fn assert_proof_fails<P>(prove: impl FnOnce() -> P, verifies: impl FnOnce(&P) -> bool) {
+    if let Ok(proof) = std::panic::catch_unwind(std::panic::AssertUnwindSafe(prove)) {
+        assert!(!verifies(&proof));
+    }
+}
+
+// The first violation, if any.
+fn first_violation<F, A>(air: &A, trace: &RowMajorMatrix<F>, public_values: &[F]) -> Result<(), Error>
+where
+    F: Field,
+    A: for<'a> Air<DebugBuilder<'a, F>>,
+{
+    match check_constraints(air, trace, public_values).into_iter().next() {
+        Some(violation) => Err(Error::ConstraintViolated(violation)),
+        None => Ok(()),
+    }
+}
+
+fn to_u32(field: FieldKind, value: u64) -> Result<u32, Error> {
+    u32::try_from(value).map_err(|_| Error::ValueOutOfField { field, value })
+}
+
+/// Generates the range check trace of `value` over `field` and evaluates every constraint on it directly,
+/// without committing to it or running FRI. Returns `Error::ConstraintViolated` with the first failing
+/// constraint, e.g. as a cheap pre-flight before proving. This proves nothing to anyone else.
+pub fn verify_trace(field: FieldKind, value: u64) -> Result<(), Error> {
+    match field {
+        #[cfg(feature = "mersenne31")]
+        FieldKind::Mersenne31 => {
+            let value = to_u32(field, value)?;
+            let trace = crate::m31::generate_mersenne31_trace::<p3_mersenne_31::Mersenne31>(value);
+            first_violation(&Mersenne31RangeCheckAir { value }, &trace, &crate::m31::public_values(value))
+        }
+        #[cfg(feature = "babybear")]
+        FieldKind::BabyBear => {
+            let value = to_u32(field, value)?;
+            let trace = crate::babybear_v1::generate_trace::<p3_baby_bear::BabyBear>(value);
+            first_violation(&BabyBearRangeCheckAir { value }, &trace, &crate::babybear_v1::public_values(value))
+        }
+        #[cfg(feature = "goldilocks")]
+        FieldKind::Goldilocks => {
+            let trace = crate::goldilocks_v1::generate_trace::<p3_goldilocks::Goldilocks>(value);
+            first_violation(&GoldilocksRangeCheckAir { value }, &trace, &crate::goldilocks_v1::public_values(value))
+        }
+        #[cfg(feature = "koalabear")]
+        FieldKind::KoalaBear => {
+            let value = to_u32(field, value)?;
+            let trace = crate::koalabear::generate_trace::<p3_koala_bear::KoalaBear>(value);
+            first_violation(&KoalaBearRangeCheckAir { value }, &trace, &crate::koalabear::public_values(value))
+        }
+        #[allow(unreachable_patterns)]
+        _ => Err(Error::UnsupportedField(field)),
+    }
+}
+
+// Renders a symbolic constraint, columns read as `local[i]` and `next[i]`, public values as `public[i]`.
+pub fn format_expression<F: Field>(expr: &SymbolicExpression<F>) -> String {
+    match expr {
+        SymbolicExpression::Variable(variable) => match variable.entry {
+            Entry::Main { offset: 0 } => format!("local[{}]", variable.index),
+            Entry::Main { offset: 1 } => format!("next[{}]", variable.index),
+            Entry::Public => format!("public[{}]", variable.index),
+            entry => format!("{:?}[{}]", entry, variable.index),
+        },
+        SymbolicExpression::IsFirstRow => "is_first_row".to_string(),
+        SymbolicExpression::IsLastRow => "is_last_row".to_string(),
+        SymbolicExpression::IsTransition => "is_transition".to_string(),
+        SymbolicExpression::Constant(c) => c.to_string(),
+        SymbolicExpression::Add { x, y, .. } => format!("({} + {})", format_expression(x), format_expression(y)),
+        SymbolicExpression::Sub { x, y, .. } => format!("({} - {})", format_expression(x), format_expression(y)),
+        SymbolicExpression::Neg { x, .. } => format!("-{}", format_expression(x)),
+        SymbolicExpression::Mul { x, y, .. } => format!("{} * {}", format_expression(x), format_expression(y)),
+    }
+}
+
+// One line per constraint, in `eval` order with its degree, so `constraint` of a `ConstraintViolation` indexes it.
+fn describe<F: Field>(constraints: &[SymbolicExpression<F>]) -> String {
+    constraints
+        .iter()
+        .enumerate()
+        .map(|(i, constraint)| {
+            format!("{:>3} (degree {}): {} == 0\n", i, constraint.degree_multiple(), format_expression(constraint))
+        })
+        .collect()
+}
+
+/// The constraints of the range check AIR of `field`, as `print_constraints` prints them.
+///
+/// The AIRs embed the checked value as a constant, it is 0 here, and read it as `public[0]`.
+pub fn describe_constraints(field: FieldKind) -> Result<String, Error> {
+    match field {
+        #[cfg(feature = "mersenne31")]
+        FieldKind::Mersenne31 => Ok(describe(&get_symbolic_constraints::<p3_mersenne_31::Mersenne31, _>(
+            &Mersenne31RangeCheckAir { value: 0 },
+            1,
+        ))),
+        #[cfg(feature = "babybear")]
+        FieldKind::BabyBear => Ok(describe(&get_symbolic_constraints::<p3_baby_bear::BabyBear, _>(
+            &BabyBearRangeCheckAir { value: 0 },
+            1,
+        ))),
+        #[cfg(feature = "goldilocks")]
+        FieldKind::Goldilocks => Ok(describe(&get_symbolic_constraints::<p3_goldilocks::Goldilocks, _>(
+            &GoldilocksRangeCheckAir { value: 0 },
+            1,
+        ))),
+        #[cfg(feature = "koalabear")]
+        FieldKind::KoalaBear => Ok(describe(&get_symbolic_constraints::<p3_koala_bear::KoalaBear, _>(
+            &KoalaBearRangeCheckAir { value: 0 },
+            1,
+        ))),
+        #[allow(unreachable_patterns)]
+        _ => Err(Error::UnsupportedField(field)),
+    }
+}
+
+/// Prints every constraint of the range check AIR of `field` with its degree, for reviewing the booleanity,
+/// reconstruction and modulus boundary constraints without running a proof.
+pub fn print_constraints(field: FieldKind) {
+    match describe_constraints(field) {
+        Ok(constraints) => print!("{}", constraints),
+        Err(e) => eprintln!("{}", e),
+    }
+}
+
+#[cfg(all(test, feature = "babybear", feature = "goldilocks", feature = "mersenne31"))]
+mod tests {
+    use super::*;
+    use p3_baby_bear::BabyBear;
+    use p3_field::{AbstractField, PrimeField32, PrimeField64};
+    use p3_goldilocks::Goldilocks;
+    use p3_mersenne_31::Mersenne31;
+
+    use crate::babybear_v1::{self, BabyBearRangeCheckAir};
+    use crate::babybear_v2::{self, BabyBearRangeCheckBitDecompositionAir};
+    use crate::goldilocks_v1::{self, GoldilocksRangeCheckAir};
+    use crate::m31::{self, Mersenne31RangeCheckAir};
+
+    fn babybear_v1_violations(value: u32) -> Vec<ConstraintViolation> {
+        let trace = babybear_v1::generate_trace::<BabyBear>(value);
+        check_constraints(&BabyBearRangeCheckAir { value }, &trace, &babybear_v1::public_values(value))
+    }
+
+    fn babybear_v2_violations(value: u32) -> Vec<ConstraintViolation> {
+        let (trace, and_most_sig_byte_decomp_4_to_3, and_most_sig_byte_decomp_4_to_2, and_most_sig_byte_decomp_4_to_1) =
+            babybear_v2::generate_trace_and_inputs::<BabyBear>(value);
+        let air = BabyBearRangeCheckBitDecompositionAir {
+            value,
+            and_most_sig_byte_decomp_4_to_3,
+            and_most_sig_byte_decomp_4_to_2,
+            and_most_sig_byte_decomp_4_to_1,
+        };
+        check_constraints(&air, &trace, &[])
+    }
+
+    fn goldilocks_v1_violations(value: u64) -> Vec<ConstraintViolation> {
+        let trace = goldilocks_v1::generate_trace::<Goldilocks>(value);
+        check_constraints(&GoldilocksRangeCheckAir { value }, &trace, &goldilocks_v1::public_values(value))
+    }
+
+    fn m31_violations(value: u32) -> Vec<ConstraintViolation> {
+        let trace = m31::generate_mersenne31_trace::<Mersenne31>(value);
+        check_constraints(&Mersenne31RangeCheckAir { value }, &trace, &m31::public_values(value))
+    }
+
+    // Forges the trace of the value 2 into `0 * 2 + 2 * 1`. The reconstruction still holds, so only
+    // booleanity can catch the cell holding 2. `bits` is the index of the last bit column.
+    fn forge_non_boolean_bit<F: Field>(trace: &mut RowMajorMatrix<F>, bits: usize) {
+        assert_eq!(trace.values[bits - 1], F::one());
+        trace.values[bits - 1] = F::zero();
+        trace.values[bits] = F::two();
+    }
+
+    #[test]
+    fn non_boolean_bits_are_rejected_by_every_field() {
+        let mut trace = babybear_v1::generate_trace::<BabyBear>(2);
+        forge_non_boolean_bit(&mut trace, 31);
+        let public_values = babybear_v1::public_values(2);
+        assert!(!satisfies(&BabyBearRangeCheckAir { value: 2 }, &trace, &public_values));
+        let config = babybear_v1::config(1, &babybear_v1::DEFAULT_FRI_PARAMS);
+        assert_proof_fails(
+            || {
+                let air = BabyBearRangeCheckAir { value: 2 };
+                p3_uni_stark::prove(&config, &air, &mut babybear_v1::challenger(), trace, &public_values)
+            },
+            |proof| babybear_v1::verify(2, proof).is_ok(),
+        );
+
+        let (mut trace, and_4_to_3, and_4_to_2, and_4_to_1) = babybear_v2::generate_trace_and_inputs::<BabyBear>(2);
+        forge_non_boolean_bit(&mut trace, 31);
+        let air = BabyBearRangeCheckBitDecompositionAir {
+            value: 2,
+            and_most_sig_byte_decomp_4_to_3: and_4_to_3,
+            and_most_sig_byte_decomp_4_to_2: and_4_to_2,
+            and_most_sig_byte_decomp_4_to_1: and_4_to_1,
+        };
+        assert!(!satisfies(&air, &trace, &[]));
+        let config = babybear_v2::config(1, &babybear_v2::DEFAULT_FRI_PARAMS);
+        assert_proof_fails(
+            || p3_uni_stark::prove(&config, &air, &mut babybear_v2::challenger(), trace, &vec![]),
+            |proof| babybear_v2::verify(2, proof).is_ok(),
+        );
+
+        let mut trace = goldilocks_v1::generate_trace::<Goldilocks>(2);
+        forge_non_boolean_bit(&mut trace, 63);
+        let public_values = goldilocks_v1::public_values(2);
+        assert!(!satisfies(&GoldilocksRangeCheckAir { value: 2 }, &trace, &public_values));
+        let config = goldilocks_v1::config(&goldilocks_v1::DEFAULT_FRI_PARAMS);
+        assert_proof_fails(
+            || {
+                let air = GoldilocksRangeCheckAir { value: 2 };
+                p3_uni_stark::prove(&config, &air, &mut goldilocks_v1::challenger(), trace, &public_values)
+            },
+            |proof| goldilocks_v1::verify(2, proof).is_ok(),
+        );
+
+        let mut trace = m31::generate_mersenne31_trace::<Mersenne31>(2);
+        forge_non_boolean_bit(&mut trace, 31);
+        let public_values = m31::public_values(2);
+        assert!(!satisfies(&Mersenne31RangeCheckAir { value: 2 }, &trace, &public_values));
+        let config = m31::config(&m31::DEFAULT_FRI_PARAMS);
+        assert_proof_fails(
+            || {
+                let air = Mersenne31RangeCheckAir { value: 2 };
+                p3_uni_stark::prove(&config, &air, &mut m31::challenger(), trace, &public_values)
+            },
+            |proof| m31::verify(2, proof).is_ok(),
+        );
+
+        #[cfg(feature = "koalabear")]
+        {
+            use crate::koalabear;
+
+            let mut trace = koalabear::generate_trace::<p3_koala_bear::KoalaBear>(2);
+            forge_non_boolean_bit(&mut trace, 31);
+            let public_values = koalabear::public_values(2);
+            assert!(!satisfies(&KoalaBearRangeCheckAir { value: 2 }, &trace, &public_values));
+            let config = koalabear::config(1, &koalabear::DEFAULT_FRI_PARAMS);
+            assert_proof_fails(
+                || {
+                    let air = KoalaBearRangeCheckAir { value: 2 };
+                    p3_uni_stark::prove(&config, &air, &mut koalabear::challenger(), trace, &public_values)
+                },
+                |proof| koalabear::verify(2, proof).is_ok(),
+            );
+        }
+    }
+
+    #[test]
+    fn traces_are_verified_without_proving() {
+        verify_trace(FieldKind::BabyBear, 100).unwrap();
+        verify_trace(FieldKind::Goldilocks, Goldilocks::ORDER_U64 - 1).unwrap();
+        verify_trace(FieldKind::Mersenne31, (1 << 31) - 2).unwrap();
+
+        assert!(matches!(
+            verify_trace(FieldKind::BabyBear, u64::from(BabyBear::ORDER_U32)),
+            Err(Error::ConstraintViolated(ConstraintViolation { row: 0, .. }))
+        ));
+        assert!(matches!(
+            verify_trace(FieldKind::Goldilocks, Goldilocks::ORDER_U64),
+            Err(Error::ConstraintViolated(_))
+        ));
+        assert!(matches!(verify_trace(FieldKind::Mersenne31, 1 << 32), Err(Error::ValueOutOfField { .. })));
+    }
+
+    #[test]
+    fn constraints_are_described_with_their_degree() {
+        let constraints = describe_constraints(FieldKind::BabyBear).unwrap();
+        let symbolic = get_symbolic_constraints::<BabyBear, _>(&BabyBearRangeCheckAir { value: 0 }, 1);
+        assert_eq!(constraints.lines().count(), symbolic.len());
+        assert!(constraints.lines().all(|line| line.contains("(degree ")));
+        // Booleanity of the first bit
+        assert!(constraints.contains("local[0]"));
+
+        let constraints = describe_constraints(FieldKind::Mersenne31).unwrap();
+        assert!(constraints.contains("is_first_row"));
+
+        #[cfg(feature = "koalabear")]
+        {
+            let air = KoalaBearRangeCheckAir { value: 0 };
+            let symbolic = get_symbolic_constraints::<p3_koala_bear::KoalaBear, _>(&air, 1);
+            assert_eq!(describe_constraints(FieldKind::KoalaBear).unwrap().lines().count(), symbolic.len());
+        }
+    }
+
+    #[test]
+    fn babybear_v1_constraints() {
+        let value = 100;
+        let trace = babybear_v1::generate_trace::<BabyBear>(value);
+        debug_check(&BabyBearRangeCheckAir { value }, &trace, &babybear_v1::public_values(value));
+        assert!(babybear_v1_violations(0).is_empty());
+        assert!(babybear_v1_violations((1 << 31) - (1 << 27)).is_empty());
+        assert!(!babybear_v1_violations((1 << 31) - (1 << 27) + 1).is_empty());
+        assert!(!babybear_v1_violations(u32::MAX).is_empty());
+    }
+
+    #[test]
+    fn babybear_v2_constraints() {
+        assert!(babybear_v2_violations(0).is_empty());
+        assert!(babybear_v2_violations(100).is_empty());
+        assert!(babybear_v2_violations((1 << 31) - (1 << 27)).is_empty());
+        assert!(!babybear_v2_violations((1 << 31) - (1 << 27) + 1).is_empty());
+        assert!(!babybear_v2_violations(u32::MAX).is_empty());
+    }
+
+    #[test]
+    fn babybear_v1_and_v2_accept_the_same_values() {
+        let order = BabyBear::ORDER_U32;
+        // Around the modulus, the all ones prefixes the boundary constraints look at, and the top of `u32`
+        let values = (order - 256..=order + 256)
+            .chain((1..=4).flat_map(|ones| {
+                let prefix = (u32::MAX << (32 - ones)) >> 1;
+                [prefix - 1, prefix, prefix + 1]
+            }))
+            .chain([0, 1, 1 << 31, (1 << 31) + 1, u32::MAX - 1, u32::MAX]);
+
+        for value in values {
+            assert_eq!(
+                babybear_v1_violations(value).is_empty(),
+                babybear_v2_violations(value).is_empty(),
+                "babybear_v1 and babybear_v2 disagree on {}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn goldilocks_v1_constraints() {
+        let value = 100;
+        let trace = goldilocks_v1::generate_trace::<Goldilocks>(value);
+        debug_check(&GoldilocksRangeCheckAir { value }, &trace, &goldilocks_v1::public_values(value));
+        assert!(goldilocks_v1_violations(0).is_empty());
+        assert!(goldilocks_v1_violations(u64::MAX - (1 << 32) + 1).is_empty());
+        assert!(!goldilocks_v1_violations(u64::MAX - (1 << 32) + 2).is_empty());
+        assert!(!goldilocks_v1_violations(u64::MAX).is_empty());
+    }
+
+    #[test]
+    fn m31_constraints() {
+        let value = 100;
+        let trace = m31::generate_mersenne31_trace::<Mersenne31>(value);
+        debug_check(&Mersenne31RangeCheckAir { value }, &trace, &m31::public_values(value));
+        assert!(m31_violations(0).is_empty());
+        assert!(!m31_violations(1 << 31).is_empty());
+        assert!(!m31_violations((1 << 31) - 1).is_empty());
+        assert!(!m31_violations(u32::MAX).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "nonzero value on row 0")]
+    fn debug_check_panics_on_bad_trace() {
+        let mut trace = babybear_v1::generate_trace::<BabyBear>(100);
+        trace.values[31] = BabyBear::two();
+        debug_check(&BabyBearRangeCheckAir { value: 100 }, &trace, &babybear_v1::public_values(100));
+    }
+}
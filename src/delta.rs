@@ -0,0 +1,154 @@
+use p3_air::{Air, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, Field};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::babybear_v1::{self, MyConfig, DEFAULT_FRI_PARAMS};
+use crate::bits::{reconstruct, write_bits};
+use crate::error::Error;
+use crate::field::FieldKind;
+
+// Bit width of the delta and the slack. Inputs are below `2^DELTA_BITS`, so both honest quantities fit in the
+// bits, while a negative one wraps around the modulus to at least `p - 2^29 > 2^30` and can't be decomposed.
+pub const DELTA_BITS: usize = 29;
+
+// Proves `0 <= next - prev <= max_delta`, with a single row:
+// | delta bits | slack bits |
+// The delta is `next - prev` and the slack is `max_delta - delta`, both have to fit in `DELTA_BITS` bits.
+// Public values are `[prev, next, max_delta]`.
+pub struct BabyBearBoundedDeltaAir;
+
+impl<F: Field> BaseAir<F> for BabyBearBoundedDeltaAir {
+    fn width(&self) -> usize {
+        2 * DELTA_BITS
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for BabyBearBoundedDeltaAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+
+        let public_values = builder.public_values();
+        let prev: AB::Expr = public_values[0].into();
+        let next: AB::Expr = public_values[1].into();
+        let max_delta: AB::Expr = public_values[2].into();
+
+        // Making sure every bit is either 0 or 1
+        for &bit in local.iter() {
+            builder.assert_bool(bit);
+        }
+
+        let delta = reconstruct::<AB>(&local[..DELTA_BITS]);
+        let slack = reconstruct::<AB>(&local[DELTA_BITS..]);
+        builder.assert_eq(delta.clone(), next - prev);
+        builder.assert_eq(slack, max_delta - delta);
+    }
+}
+
+// Requires `prev <= next <= prev + max_delta`.
+pub fn generate_trace<F: Field>(prev: u32, next: u32, max_delta: u32) -> RowMajorMatrix<F> {
+    let mut row = vec![F::zero(); 2 * DELTA_BITS];
+    let delta = next - prev;
+    write_bits(&mut row[..DELTA_BITS], u64::from(delta));
+    write_bits(&mut row[DELTA_BITS..], u64::from(max_delta - delta));
+    RowMajorMatrix::new(row, 2 * DELTA_BITS)
+}
+
+fn public_values(prev: u32, next: u32, max_delta: u32) -> Vec<BabyBear> {
+    vec![
+        BabyBear::from_canonical_u32(prev),
+        BabyBear::from_canonical_u32(next),
+        BabyBear::from_canonical_u32(max_delta),
+    ]
+}
+
+fn check_inputs(prev: u32, next: u32, max_delta: u32) -> Result<(), Error> {
+    for input in [prev, next, max_delta] {
+        if input >> DELTA_BITS != 0 {
+            return Err(Error::InvalidInput(format!("{} doesn't fit in {} bits", input, DELTA_BITS)));
+        }
+    }
+    Ok(())
+}
+
+/// Proves that `next` is at least `prev` and at most `max_delta` above it. `max_delta == 0` forces `next == prev`.
+pub fn prove_bounded_delta(prev: u32, next: u32, max_delta: u32) -> Result<Proof<MyConfig>, Error> {
+    check_inputs(prev, next, max_delta)?;
+    if next < prev || next - prev > max_delta {
+        return Err(Error::InvalidInput(format!("{} - {} isn't in [0, {}]", next, prev, max_delta)));
+    }
+
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<BabyBear>(prev, next, max_delta));
+    let config = babybear_v1::config(trace.height(), &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    let public_values = public_values(prev, next, max_delta);
+    let proof = info_span!("commit_trace").in_scope(|| {
+        p3_uni_stark::prove(&config, &BabyBearBoundedDeltaAir, &mut challenger, trace, &public_values)
+    });
+    Ok(proof)
+}
+
+pub fn verify_bounded_delta(prev: u32, next: u32, max_delta: u32, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    check_inputs(prev, next, max_delta)?;
+    let config = babybear_v1::config(1, &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    let public_values = public_values(prev, next, max_delta);
+    p3_uni_stark::verify(&config, &BabyBearBoundedDeltaAir, &mut challenger, proof, &public_values)
+        .map_err(|e| Error::verification_failed(FieldKind::BabyBear, u64::from(next), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p3_field::PrimeField32;
+
+    use crate::debug::satisfies;
+
+    fn check(trace: &RowMajorMatrix<BabyBear>, prev: u32, next: u32, max_delta: u32) -> bool {
+        satisfies(&BabyBearBoundedDeltaAir, trace, &public_values(prev, next, max_delta))
+    }
+
+    // The trace a prover would have to build for any claim, with the delta and slack wrapped around the modulus.
+    fn forged_trace(prev: u32, next: u32, max_delta: u32) -> RowMajorMatrix<BabyBear> {
+        let delta = BabyBear::from_canonical_u32(next) - BabyBear::from_canonical_u32(prev);
+        let slack = BabyBear::from_canonical_u32(max_delta) - delta;
+        let mut row = vec![BabyBear::zero(); 2 * DELTA_BITS];
+        write_bits(&mut row[..DELTA_BITS], u64::from(delta.as_canonical_u32()));
+        write_bits(&mut row[DELTA_BITS..], u64::from(slack.as_canonical_u32()));
+        RowMajorMatrix::new(row, 2 * DELTA_BITS)
+    }
+
+    #[test]
+    fn deltas_within_the_bound_pass() {
+        for (prev, next, max_delta) in [(10, 10, 0), (10, 15, 5), (10, 11, 5), (0, (1 << 29) - 1, (1 << 29) - 1)] {
+            assert!(check(&generate_trace(prev, next, max_delta), prev, next, max_delta));
+        }
+    }
+
+    #[test]
+    fn decreasing_and_too_large_deltas_fail() {
+        // `next < prev`
+        assert!(!check(&forged_trace(10, 9, 5), 10, 9, 5));
+        // `next - prev > max_delta`
+        assert!(!check(&forged_trace(10, 16, 5), 10, 16, 5));
+        // `max_delta == 0` only allows `next == prev`
+        assert!(!check(&forged_trace(10, 11, 0), 10, 11, 0));
+    }
+
+    #[test]
+    fn prove_rejects_invalid_deltas() {
+        assert!(matches!(prove_bounded_delta(10, 9, 5), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_bounded_delta(10, 11, 0), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_bounded_delta(0, 1 << 29, 1 << 29), Err(Error::InvalidInput(_))));
+
+        let proof = prove_bounded_delta(10, 12, 5).unwrap();
+        verify_bounded_delta(10, 12, 5, &proof).unwrap();
+        assert!(verify_bounded_delta(10, 13, 5, &proof).is_err());
+    }
+}
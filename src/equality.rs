@@ -0,0 +1,120 @@
+use p3_air::{Air, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, Field};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::babybear_v1::{self, eval_value_columns, value_columns, MyConfig, DEFAULT_FRI_PARAMS, WIDTH};
+use crate::error::Error;
+use crate::field::FieldKind;
+
+// Proves `a == b` for two range checked values, with a single row:
+// | a bits | a ANDs | b bits | b ANDs |
+// Both decompositions are canonical, see `babybear_v1`, and their bits are equal one by one.
+// Public values are `[a, b]`, and have to match the statement the AIR is built for.
+pub struct BabyBearEqualityAir {
+    pub a: u32,
+    pub b: u32,
+}
+
+impl<F: Field> BaseAir<F> for BabyBearEqualityAir {
+    fn width(&self) -> usize {
+        2 * WIDTH
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for BabyBearEqualityAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+
+        let public_values = builder.public_values();
+        let a: AB::Expr = public_values[0].into();
+        let b: AB::Expr = public_values[1].into();
+
+        builder.assert_eq(a.clone(), AB::Expr::from_canonical_u32(self.a));
+        builder.assert_eq(b.clone(), AB::Expr::from_canonical_u32(self.b));
+
+        let (a_columns, b_columns) = local.split_at(WIDTH);
+        let reconstructed_a = eval_value_columns(builder, a_columns);
+        let reconstructed_b = eval_value_columns(builder, b_columns);
+        builder.assert_eq(reconstructed_a, a);
+        builder.assert_eq(reconstructed_b, b);
+
+        for (&a_bit, &b_bit) in a_columns[..32].iter().zip(&b_columns[..32]) {
+            builder.assert_eq(a_bit, b_bit);
+        }
+    }
+}
+
+pub fn generate_trace<F: Field>(a: u32, b: u32) -> RowMajorMatrix<F> {
+    let mut row = value_columns(a);
+    row.extend(value_columns::<F>(b));
+    RowMajorMatrix::new(row, 2 * WIDTH)
+}
+
+fn public_values(a: u32, b: u32) -> Vec<BabyBear> {
+    vec![BabyBear::from_canonical_u32(a), BabyBear::from_canonical_u32(b)]
+}
+
+/// Proves that `a` and `b` are equal canonical BabyBear elements, with both as public values so that an outer
+/// protocol can bind them to the values of other proofs.
+pub fn prove_equality(a: u32, b: u32) -> Result<Proof<MyConfig>, Error> {
+    babybear_v1::try_generate_trace::<BabyBear>(a)?;
+    babybear_v1::try_generate_trace::<BabyBear>(b)?;
+    if a != b {
+        return Err(Error::InvalidInput(format!("{} isn't equal to {}", a, b)));
+    }
+
+    let air = BabyBearEqualityAir { a, b };
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<BabyBear>(a, b));
+    let config = babybear_v1::config(trace.height(), &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    Ok(info_span!("commit_trace")
+        .in_scope(|| p3_uni_stark::prove(&config, &air, &mut challenger, trace, &public_values(a, b))))
+}
+
+pub fn verify_equality(a: u32, b: u32, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    babybear_v1::try_generate_trace::<BabyBear>(a)?;
+    babybear_v1::try_generate_trace::<BabyBear>(b)?;
+    let air = BabyBearEqualityAir { a, b };
+    let config = babybear_v1::config(1, &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    p3_uni_stark::verify(&config, &air, &mut challenger, proof, &public_values(a, b))
+        .map_err(|e| Error::verification_failed(FieldKind::BabyBear, u64::from(a), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p3_field::PrimeField32;
+
+    use crate::debug::satisfies;
+
+    fn satisfied(a: u32, b: u32) -> bool {
+        let air = BabyBearEqualityAir { a, b };
+        satisfies(&air, &generate_trace::<BabyBear>(a, b), &public_values(a, b))
+    }
+
+    #[test]
+    fn only_equal_values_pass() {
+        assert!(satisfied(0, 0));
+        assert!(satisfied(BabyBear::ORDER_U32 - 1, BabyBear::ORDER_U32 - 1));
+        assert!(!satisfied(5, 7));
+        assert!(!satisfied(1, 0));
+    }
+
+    #[test]
+    fn proof_is_bound_to_both_values() {
+        let proof = prove_equality(100, 100).unwrap();
+        verify_equality(100, 100, &proof).unwrap();
+        assert!(verify_equality(100, 101, &proof).is_err());
+        assert!(verify_equality(101, 101, &proof).is_err());
+        assert!(matches!(prove_equality(100, 101), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_equality(BabyBear::ORDER_U32, BabyBear::ORDER_U32), Err(Error::ValueOutOfField { .. })));
+    }
+}
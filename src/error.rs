@@ -0,0 +1,133 @@
+use std::fmt;
+
+use p3_uni_stark::VerificationError;
+
+use crate::config::FriParams;
+use crate::debug::ConstraintViolation;
+use crate::field::FieldKind;
+
+#[derive(Debug)]
+pub enum Error {
+    // The proof was produced over a different field than the caller expected.
+    FieldMismatch { expected: FieldKind, found: FieldKind },
+    // The proof was produced with different FRI parameters than the verifier expected.
+    ParameterMismatch { expected: FriParams, found: FriParams },
+    // The FRI parameters recorded in a proof are too weak to be trusted.
+    InsufficientSecurity { params: FriParams, min_bits: usize },
+    // Support for `field` wasn't compiled in, see the cargo features.
+    UnsupportedField(FieldKind),
+    // The value can't be represented by the prover of `field`.
+    ValueOutOfField { field: FieldKind, value: u64 },
+    // The inputs don't satisfy the statement or the parameters of the prover.
+    InvalidInput(String),
+    // The fixed-point scale is larger than supported.
+    InvalidScale { scale: u32, max: u32 },
+    // Reading or writing a file failed.
+    Io(std::io::Error),
+    // The proof bytes could not be encoded or decoded.
+    Serialization(String),
+    // The proof file was written by a format version this build can't read.
+    UnsupportedVersion { found: u16, supported: u16 },
+    // A check outside of the STARK failed, e.g. a Merkle path next to a range proof, or a STARK proof without a
+    // public value to report.
+    Verification(String),
+    // The proof of a range check of `value` over `field` did not verify, with the reason `p3_uni_stark` gave.
+    // Statements over several values report the first of them.
+    VerificationFailed { field: FieldKind, value: u64, failure: VerificationFailure },
+    // A trace checked without proving doesn't satisfy a constraint of its AIR.
+    ConstraintViolated(ConstraintViolation),
+    // The inputs an AIR was built with don't match the trace it is proven with.
+    InconsistentWitness(String),
+    // Proving was cancelled by the caller.
+    Cancelled,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::FieldMismatch { expected, found } => {
+                write!(f, "proof was made for {:?} but {:?} was expected", found, expected)
+            }
+            Error::ParameterMismatch { expected, found } => {
+                write!(f, "proof was made with {:?} but {:?} was expected", found, expected)
+            }
+            Error::InsufficientSecurity { params, min_bits } => write!(
+                f,
+                "proof was made with {:?}, which gives {} bits of security but at least {} are required",
+                params,
+                params.conjectured_security_bits(),
+                min_bits
+            ),
+            Error::UnsupportedField(field) => write!(f, "{:?} support is not enabled", field),
+            Error::ValueOutOfField { field, value } => {
+                write!(f, "value {} can't be range checked over {:?}", value, field)
+            }
+            Error::InvalidInput(msg) => write!(f, "invalid input: {}", msg),
+            Error::InvalidScale { scale, max } => {
+                write!(f, "fixed-point scale {} is larger than the maximum {}", scale, max)
+            }
+            Error::Io(e) => write!(f, "io error: {}", e),
+            Error::Serialization(msg) => write!(f, "proof serialization failed: {}", msg),
+            Error::UnsupportedVersion { found, supported } => {
+                write!(f, "proof file has format version {} but only version {} is supported", found, supported)
+            }
+            Error::Verification(msg) => write!(f, "verification failed: {}", msg),
+            Error::VerificationFailed { field, value, failure } => {
+                write!(f, "{:?} proof for {} did not verify: {}", field, value, failure)
+            }
+            Error::ConstraintViolated(violation) => {
+                write!(f, "constraint {} had a nonzero value on row {}", violation.constraint, violation.row)
+            }
+            Error::InconsistentWitness(msg) => write!(f, "inconsistent witness: {}", msg),
+            Error::Cancelled => write!(f, "proving was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Error {
+    pub(crate) fn verification_failed<E: fmt::Debug>(field: FieldKind, value: u64, e: VerificationError<E>) -> Self {
+        Error::VerificationFailed { field, value, failure: e.into() }
+    }
+}
+
+/// Why `p3_uni_stark::verify` rejected a proof.
+///
+/// A proof for other FRI parameters usually fails with `InvalidShape` or `InvalidOpening`, a well formed proof of
+/// another statement, e.g. another value, with `InvalidOpening` or `OodEvaluationMismatch`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerificationFailure {
+    // The proof doesn't have the shape the AIR and the parameters give, e.g. a different number of openings.
+    InvalidShape,
+    // A commitment opening or an FRI query didn't check out, with the PCS error.
+    InvalidOpening(String),
+    // The constraints at the out of domain point don't match the quotient.
+    OodEvaluationMismatch,
+}
+
+impl<E: fmt::Debug> From<VerificationError<E>> for VerificationFailure {
+    fn from(e: VerificationError<E>) -> Self {
+        match e {
+            VerificationError::InvalidProofShape => VerificationFailure::InvalidShape,
+            VerificationError::InvalidOpeningArgument(e) => VerificationFailure::InvalidOpening(format!("{:?}", e)),
+            VerificationError::OodEvaluationMismatch => VerificationFailure::OodEvaluationMismatch,
+        }
+    }
+}
+
+impl fmt::Display for VerificationFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerificationFailure::InvalidShape => write!(f, "the proof has the wrong shape"),
+            VerificationFailure::InvalidOpening(e) => write!(f, "an opening or FRI query failed, {}", e),
+            VerificationFailure::OodEvaluationMismatch => write!(f, "the constraints don't match the quotient"),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::Io(e)
+    }
+}
@@ -0,0 +1,132 @@
+use p3_air::{Air, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, Field};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::babybear_v1::{self, eval_value_columns, value_columns, MyConfig, DEFAULT_FRI_PARAMS, WIDTH};
+use crate::error::Error;
+use crate::field::FieldKind;
+
+// Largest bit length of a BabyBear element, `2^30 <= p - 1 < 2^31`.
+pub const MAX_EXACT_BITS: u32 = 31;
+
+// Proves `value` has exactly `k` bits, `2^(k-1) <= value < 2^k`, with the columns of `babybear_v1`:
+// | 32 bits, big endian | AND columns |
+// The bits above `k` are zero and bit `k - 1` is one. Public values are `[value, k]`, and have to match the
+// statement the AIR is built for.
+pub struct BabyBearExactBitsAir {
+    pub value: u32,
+    pub k: u32,
+}
+
+impl<F: Field> BaseAir<F> for BabyBearExactBitsAir {
+    fn width(&self) -> usize {
+        WIDTH
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for BabyBearExactBitsAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let current_row = main.row_slice(0);
+
+        let public_values = builder.public_values();
+        let value: AB::Expr = public_values[0].into();
+        let k: AB::Expr = public_values[1].into();
+
+        builder.assert_eq(value.clone(), AB::Expr::from_canonical_u32(self.value));
+        builder.assert_eq(k, AB::Expr::from_canonical_u32(self.k));
+
+        // Booleanity, the modulus boundary and the reconstruction
+        let reconstructed_value = eval_value_columns(builder, &current_row);
+        builder.assert_eq(value, reconstructed_value);
+
+        // Bit `i` has weight `2^(31 - i)`, so the top bit of a `k` bit number is at `32 - k`
+        let top = 32 - self.k as usize;
+        for &bit in &current_row[..top] {
+            builder.assert_zero(bit);
+        }
+        builder.assert_one(current_row[top]);
+    }
+}
+
+fn check_inputs(value: u32, k: u32) -> Result<(), Error> {
+    if k == 0 || k > MAX_EXACT_BITS {
+        return Err(Error::InvalidInput(format!("bit length {} has to be in [1, {}]", k, MAX_EXACT_BITS)));
+    }
+    if 32 - value.leading_zeros() != k {
+        return Err(Error::InvalidInput(format!("{} doesn't have exactly {} bits", value, k)));
+    }
+    Ok(())
+}
+
+fn public_values(value: u32, k: u32) -> Vec<BabyBear> {
+    vec![BabyBear::from_canonical_u32(value), BabyBear::from_canonical_u32(k)]
+}
+
+/// Proves `2^(k-1) <= value < 2^k`. With `k == 1` the value has to be 1.
+pub fn prove_exact_bits(value: u32, k: u32) -> Result<Proof<MyConfig>, Error> {
+    check_inputs(value, k)?;
+    babybear_v1::try_generate_trace::<BabyBear>(value)?;
+
+    let air = BabyBearExactBitsAir { value, k };
+    let trace =
+        info_span!("generate_trace").in_scope(|| RowMajorMatrix::new(value_columns::<BabyBear>(value), WIDTH));
+    let config = babybear_v1::config(trace.height(), &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    Ok(info_span!("commit_trace")
+        .in_scope(|| p3_uni_stark::prove(&config, &air, &mut challenger, trace, &public_values(value, k))))
+}
+
+pub fn verify_exact_bits(value: u32, k: u32, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    if k == 0 || k > MAX_EXACT_BITS {
+        return Err(Error::InvalidInput(format!("bit length {} has to be in [1, {}]", k, MAX_EXACT_BITS)));
+    }
+    let air = BabyBearExactBitsAir { value, k };
+    let config = babybear_v1::config(1, &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    p3_uni_stark::verify(&config, &air, &mut challenger, proof, &public_values(value, k))
+        .map_err(|e| Error::verification_failed(FieldKind::BabyBear, u64::from(value), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::debug::satisfies;
+
+    fn satisfied(value: u32, k: u32) -> bool {
+        let trace = RowMajorMatrix::new(value_columns::<BabyBear>(value), WIDTH);
+        satisfies(&BabyBearExactBitsAir { value, k }, &trace, &public_values(value, k))
+    }
+
+    #[test]
+    fn exact_bit_lengths_pass() {
+        assert!(satisfied(1, 1));
+        assert!(satisfied(2, 2));
+        assert!(satisfied(3, 2));
+        assert!(satisfied(255, 8));
+        assert!(satisfied(1 << 30, 31));
+    }
+
+    #[test]
+    fn other_bit_lengths_fail() {
+        // `k == 1` only allows 1
+        assert!(!satisfied(0, 1));
+        assert!(!satisfied(2, 1));
+        // Too short and too long
+        assert!(!satisfied(127, 8));
+        assert!(!satisfied(256, 8));
+    }
+
+    #[test]
+    fn inputs_are_checked() {
+        assert!(matches!(prove_exact_bits(0, 0), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_exact_bits(4, 2), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_exact_bits(1 << 31, 32), Err(Error::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,164 @@
+use p3_air::{Air, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, Field};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::babybear_v1::{self, MyConfig, DEFAULT_FRI_PARAMS};
+use crate::bits::{reconstruct, write_bits};
+use crate::error::Error;
+use crate::field::FieldKind;
+
+// Bit width of the distance to the excluded interval. Inputs are below `2^EXCLUSION_BITS`, so an honest
+// distance fits in the bits, while a value inside `[lo, hi]` gives a distance that wraps around the modulus
+// to at least `p - 2^29 > 2^30` and can't be decomposed.
+pub const EXCLUSION_BITS: usize = 29;
+
+// Proves `value < lo` or `value > hi`, with a single row:
+// | side | distance bits |
+// `side` is 1 if `value` is below the interval and 0 if it is above it. The distance is `lo - 1 - value`
+// below the interval and `value - hi - 1` above it, and has to fit in `EXCLUSION_BITS` bits.
+// Public values are `[value, lo, hi]`.
+pub struct BabyBearExclusionAir;
+
+impl<F: Field> BaseAir<F> for BabyBearExclusionAir {
+    fn width(&self) -> usize {
+        1 + EXCLUSION_BITS
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for BabyBearExclusionAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+
+        let public_values = builder.public_values();
+        let value: AB::Expr = public_values[0].into();
+        let lo: AB::Expr = public_values[1].into();
+        let hi: AB::Expr = public_values[2].into();
+
+        // Making sure the side selector and every bit are either 0 or 1
+        for &bit in local.iter() {
+            builder.assert_bool(bit);
+        }
+
+        let below: AB::Expr = local[0].into();
+        let distance = reconstruct::<AB>(&local[1..]);
+
+        // The distance of the chosen side has to be non-negative, i.e. fit in the bits
+        let below_distance = lo - AB::Expr::one() - value.clone();
+        let above_distance = value - hi - AB::Expr::one();
+        let expected = below.clone() * below_distance + (AB::Expr::one() - below) * above_distance;
+        builder.assert_eq(distance, expected);
+    }
+}
+
+pub fn generate_trace<F: Field>(value: u32, lo: u32, hi: u32) -> RowMajorMatrix<F> {
+    let mut row = vec![F::zero(); 1 + EXCLUSION_BITS];
+    // With `lo == 0` nothing is below the interval, so the upper side is the only option
+    if value < lo {
+        row[0] = F::one();
+        write_bits(&mut row[1..], u64::from(lo - 1 - value));
+    } else {
+        write_bits(&mut row[1..], u64::from(value - hi - 1));
+    }
+    RowMajorMatrix::new(row, 1 + EXCLUSION_BITS)
+}
+
+fn public_values(value: u32, lo: u32, hi: u32) -> Vec<BabyBear> {
+    vec![
+        BabyBear::from_canonical_u32(value),
+        BabyBear::from_canonical_u32(lo),
+        BabyBear::from_canonical_u32(hi),
+    ]
+}
+
+fn check_inputs(value: u32, lo: u32, hi: u32) -> Result<(), Error> {
+    for input in [value, lo, hi] {
+        if input >> EXCLUSION_BITS != 0 {
+            return Err(Error::InvalidInput(format!("{} doesn't fit in {} bits", input, EXCLUSION_BITS)));
+        }
+    }
+    Ok(())
+}
+
+/// Proves that `value` lies outside of `[lo, hi]`.
+pub fn prove(value: u32, lo: u32, hi: u32) -> Result<Proof<MyConfig>, Error> {
+    check_inputs(value, lo, hi)?;
+    if (lo..=hi).contains(&value) {
+        return Err(Error::InvalidInput(format!("{} is inside [{}, {}]", value, lo, hi)));
+    }
+
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<BabyBear>(value, lo, hi));
+    let config = babybear_v1::config(trace.height(), &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    let proof = info_span!("commit_trace").in_scope(|| {
+        p3_uni_stark::prove(&config, &BabyBearExclusionAir, &mut challenger, trace, &public_values(value, lo, hi))
+    });
+    Ok(proof)
+}
+
+pub fn verify(value: u32, lo: u32, hi: u32, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    check_inputs(value, lo, hi)?;
+    let config = babybear_v1::config(1, &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    p3_uni_stark::verify(&config, &BabyBearExclusionAir, &mut challenger, proof, &public_values(value, lo, hi))
+        .map_err(|e| Error::verification_failed(FieldKind::BabyBear, u64::from(value), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p3_field::PrimeField32;
+
+    use crate::debug::satisfies;
+
+    fn check(trace: &RowMajorMatrix<BabyBear>, value: u32, lo: u32, hi: u32) -> bool {
+        satisfies(&BabyBearExclusionAir, trace, &public_values(value, lo, hi))
+    }
+
+    #[test]
+    fn values_outside_the_interval_pass() {
+        for (value, lo, hi) in [(9, 10, 20), (21, 10, 20), (0, 1, 5), (6, 0, 5), ((1 << 29) - 1, 0, 0)] {
+            assert!(check(&generate_trace(value, lo, hi), value, lo, hi), "{} outside [{}, {}]", value, lo, hi);
+        }
+    }
+
+    #[test]
+    fn values_inside_the_interval_fail_on_both_sides() {
+        let (lo, hi) = (10, 20);
+        for value in [10, 15, 20] {
+            for below in [false, true] {
+                // The distance the prover would have to decompose, wrapped around the modulus
+                let distance = if below {
+                    BabyBear::from_canonical_u32(lo) - BabyBear::one() - BabyBear::from_canonical_u32(value)
+                } else {
+                    BabyBear::from_canonical_u32(value) - BabyBear::from_canonical_u32(hi) - BabyBear::one()
+                };
+                let mut row = vec![BabyBear::from_bool(below)];
+                row.extend((0..EXCLUSION_BITS).rev().map(|i| {
+                    BabyBear::from_bool((distance.as_canonical_u32() >> i) & 1 == 1)
+                }));
+                assert!(!check(&RowMajorMatrix::new(row, 1 + EXCLUSION_BITS), value, lo, hi));
+            }
+        }
+    }
+
+    #[test]
+    fn zero_lower_bound_only_allows_the_upper_side() {
+        // Below `lo == 0` the distance is `-1 - value`, which never fits in the bits
+        let mut trace = generate_trace::<BabyBear>(6, 0, 5);
+        trace.values[0] = BabyBear::one();
+        assert!(!check(&trace, 6, 0, 5));
+    }
+
+    #[test]
+    fn prove_rejects_values_inside_the_interval() {
+        assert!(matches!(prove(10, 10, 20), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove(1 << 29, 0, 5), Err(Error::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::modulus::{BABYBEAR, GOLDILOCKS, KOALABEAR, MERSENNE31};
+
+/// The prime fields a range check proof can be produced over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FieldKind {
+    Mersenne31,
+    BabyBear,
+    Goldilocks,
+    KoalaBear,
+}
+
+impl FieldKind {
+    /// Every field, whether or not its feature is enabled.
+    pub fn all() -> &'static [FieldKind] {
+        &[FieldKind::Mersenne31, FieldKind::BabyBear, FieldKind::Goldilocks, FieldKind::KoalaBear]
+    }
+
+    // The field's cargo feature, which is also the prefix of its CLI functions.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FieldKind::Mersenne31 => "mersenne31",
+            FieldKind::BabyBear => "babybear",
+            FieldKind::Goldilocks => "goldilocks",
+            FieldKind::KoalaBear => "koalabear",
+        }
+    }
+
+    // Whether support for the field is compiled in.
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            FieldKind::Mersenne31 => cfg!(feature = "mersenne31"),
+            FieldKind::BabyBear => cfg!(feature = "babybear"),
+            FieldKind::Goldilocks => cfg!(feature = "goldilocks"),
+            FieldKind::KoalaBear => cfg!(feature = "koalabear"),
+        }
+    }
+
+    // Number of bit columns in the trace row of the field's range check.
+    pub fn bit_width(&self) -> usize {
+        match self {
+            FieldKind::Mersenne31 | FieldKind::BabyBear | FieldKind::KoalaBear => 32,
+            FieldKind::Goldilocks => 64,
+        }
+    }
+}
+
+/// The big endian bit decomposition of `value` exactly as the first trace row of `field` lays it out,
+/// before any padding rows. Only the low `field.bit_width()` bits of `value` are used.
+pub fn value_bits(field: FieldKind, value: u64) -> Vec<bool> {
+    (0..field.bit_width()).rev().map(|i| (value >> i) & 1 == 1).collect()
+}
+
+/// The largest value the range check of `field` accepts, `field`'s order minus one.
+pub fn max_provable_value(field: FieldKind) -> u128 {
+    let modulus = match field {
+        FieldKind::Mersenne31 => MERSENNE31.modulus(),
+        FieldKind::BabyBear => BABYBEAR.modulus(),
+        FieldKind::Goldilocks => GOLDILOCKS.modulus(),
+        FieldKind::KoalaBear => KOALABEAR.modulus(),
+    };
+    u128::from(modulus) - 1
+}
+
+/// Returns `Error::ValueOutOfField` unless `value` is below `field`'s order. Verifiers check this before
+/// touching the proof, so a value the AIR could never accept is rejected without relying on the AIR.
+pub fn check_value_in_field(field: FieldKind, value: u64) -> Result<(), Error> {
+    if u128::from(value) > max_provable_value(field) {
+        return Err(Error::ValueOutOfField { field, value });
+    }
+    Ok(())
+}
+
+/// The cheapest enabled field whose range check accepts `value`.
+///
+/// The 32 bit fields come first, ordered by their number of AND columns, BabyBear, KoalaBear, then Mersenne31, whose
+/// boundary check covers 30 one bits. Goldilocks with twice the bit columns comes last. Returns
+/// `Error::InvalidInput` if no enabled field holds `value`.
+pub fn recommend_field(value: u128) -> Result<FieldKind, Error> {
+    [FieldKind::BabyBear, FieldKind::KoalaBear, FieldKind::Mersenne31, FieldKind::Goldilocks]
+        .into_iter()
+        .find(|field| field.is_enabled() && value <= max_provable_value(*field))
+        .ok_or_else(|| Error::InvalidInput(format!("{} doesn't fit in any enabled field", value)))
+}
+
+#[cfg(all(test, feature = "mersenne31", feature = "babybear", feature = "goldilocks", feature = "koalabear"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recommends_the_smallest_field_holding_the_value() {
+        assert_eq!(recommend_field(0).unwrap(), FieldKind::BabyBear);
+        assert_eq!(recommend_field(max_provable_value(FieldKind::BabyBear)).unwrap(), FieldKind::BabyBear);
+        assert_eq!(recommend_field(max_provable_value(FieldKind::BabyBear) + 1).unwrap(), FieldKind::KoalaBear);
+        assert_eq!(recommend_field(max_provable_value(FieldKind::KoalaBear)).unwrap(), FieldKind::KoalaBear);
+        assert_eq!(recommend_field(max_provable_value(FieldKind::KoalaBear) + 1).unwrap(), FieldKind::Mersenne31);
+        assert_eq!(recommend_field(max_provable_value(FieldKind::Mersenne31)).unwrap(), FieldKind::Mersenne31);
+        assert_eq!(recommend_field(max_provable_value(FieldKind::Mersenne31) + 1).unwrap(), FieldKind::Goldilocks);
+        assert_eq!(recommend_field(max_provable_value(FieldKind::Goldilocks)).unwrap(), FieldKind::Goldilocks);
+        assert!(matches!(
+            recommend_field(max_provable_value(FieldKind::Goldilocks) + 1),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+}
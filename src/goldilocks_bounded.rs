@@ -0,0 +1,191 @@
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+use p3_field::{AbstractField, Field};
+use p3_goldilocks::Goldilocks;
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::bits::reconstruct;
+use crate::error::Error;
+use crate::field::{check_value_in_field, FieldKind};
+use crate::goldilocks_v1::{self, MyConfig, DEFAULT_FRI_PARAMS};
+use crate::modulus::{and_chain, eval_modulus_boundary_degree_2, GOLDILOCKS};
+
+// 64 bits followed by the running AND of the 32 top one bits, see `eval_modulus_boundary_degree_2`.
+pub(crate) const ELEMENT_WIDTH: usize = 64 + GOLDILOCKS.top_ones - 1;
+const WIDTH: usize = 2 * ELEMENT_WIDTH + 64 + 1;
+
+// Proves `value < bound` for canonical Goldilocks elements, with a single row:
+// | value bits | value ANDs | bound bits | bound ANDs | slack bits | carry |
+// The slack is `bound - 1 - value`. The comparison runs on 32 bit limbs,
+// `value_lo + slack_lo + 1 == bound_lo + carry * 2^32` and `value_hi + slack_hi + carry == bound_hi`, so
+// neither side can reach `2^33` and wrap around the modulus like a single 64 bit sum could.
+// Public values are `[value, bound]`, and have to match the statement the AIR is built for.
+pub struct GoldilocksBoundedRangeCheckAir {
+    pub value: u64,
+    pub bound: u64,
+}
+
+impl<F: Field> BaseAir<F> for GoldilocksBoundedRangeCheckAir {
+    fn width(&self) -> usize {
+        WIDTH
+    }
+}
+
+// Constrains `ELEMENT_WIDTH` columns to a canonical element and returns its `(hi, lo)` limbs.
+pub(crate) fn eval_element<AB: AirBuilder>(builder: &mut AB, columns: &[AB::Var]) -> (AB::Expr, AB::Expr) {
+    for &bit in &columns[..64] {
+        builder.assert_bool(bit);
+    }
+    eval_modulus_boundary_degree_2(builder, &columns[..64], &columns[64..], &GOLDILOCKS);
+    (reconstruct::<AB>(&columns[..32]), reconstruct::<AB>(&columns[32..64]))
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for GoldilocksBoundedRangeCheckAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+
+        let public_values = builder.public_values();
+        let value: AB::Expr = public_values[0].into();
+        let bound: AB::Expr = public_values[1].into();
+
+        builder.assert_eq(value.clone(), AB::Expr::from_canonical_u64(self.value));
+        builder.assert_eq(bound.clone(), AB::Expr::from_canonical_u64(self.bound));
+
+        let (value_hi, value_lo) = eval_element(builder, &local[..ELEMENT_WIDTH]);
+        let (bound_hi, bound_lo) = eval_element(builder, &local[ELEMENT_WIDTH..2 * ELEMENT_WIDTH]);
+        builder.assert_eq(value, value_hi.clone() * AB::Expr::from_canonical_u64(1 << 32) + value_lo.clone());
+        builder.assert_eq(bound, bound_hi.clone() * AB::Expr::from_canonical_u64(1 << 32) + bound_lo.clone());
+
+        // Making sure the slack bits and the carry are either 0 or 1
+        let slack = &local[2 * ELEMENT_WIDTH..WIDTH - 1];
+        let carry = local[WIDTH - 1];
+        for &bit in slack {
+            builder.assert_bool(bit);
+        }
+        builder.assert_bool(carry);
+
+        let slack_hi = reconstruct::<AB>(&slack[..32]);
+        let slack_lo = reconstruct::<AB>(&slack[32..]);
+        builder.assert_eq(
+            value_lo + slack_lo + AB::Expr::one(),
+            bound_lo + AB::Expr::from(carry) * AB::Expr::from_canonical_u64(1 << 32),
+        );
+        builder.assert_eq(value_hi + slack_hi + carry, bound_hi);
+    }
+}
+
+pub(crate) fn write_element<F: Field>(columns: &mut [F], value: u64) {
+    for (i, bit) in columns[..64].iter_mut().enumerate() {
+        *bit = F::from_bool((value >> (63 - i)) & 1 == 1);
+    }
+    let ands = and_chain(&columns[..64], &GOLDILOCKS);
+    columns[64..].copy_from_slice(&ands);
+}
+
+// Requires `value < bound`.
+pub fn generate_trace<F: Field>(value: u64, bound: u64) -> RowMajorMatrix<F> {
+    let mut row = vec![F::zero(); WIDTH];
+    write_element(&mut row[..ELEMENT_WIDTH], value);
+    write_element(&mut row[ELEMENT_WIDTH..2 * ELEMENT_WIDTH], bound);
+
+    let slack = bound - 1 - value;
+    for (i, bit) in row[2 * ELEMENT_WIDTH..WIDTH - 1].iter_mut().enumerate() {
+        *bit = F::from_bool((slack >> (63 - i)) & 1 == 1);
+    }
+    let carry = ((value & 0xFFFF_FFFF) + (slack & 0xFFFF_FFFF) + 1) >> 32;
+    row[WIDTH - 1] = F::from_canonical_u64(carry);
+    RowMajorMatrix::new(row, WIDTH)
+}
+
+// Both have to be canonical to be public values, so a bound at or above the modulus is rejected here.
+fn check_inputs(value: u64, bound: u64) -> Result<(), Error> {
+    check_value_in_field(FieldKind::Goldilocks, value)?;
+    check_value_in_field(FieldKind::Goldilocks, bound)
+}
+
+fn public_values(value: u64, bound: u64) -> Vec<Goldilocks> {
+    vec![Goldilocks::from_canonical_u64(value), Goldilocks::from_canonical_u64(bound)]
+}
+
+/// Proves that `value < bound`, e.g. `bound == 2^63` for values that fit in 63 bits.
+pub fn prove(value: u64, bound: u64) -> Result<Proof<MyConfig>, Error> {
+    check_inputs(value, bound)?;
+    if value >= bound {
+        return Err(Error::InvalidInput(format!("{} isn't below {}", value, bound)));
+    }
+
+    let air = GoldilocksBoundedRangeCheckAir { value, bound };
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<Goldilocks>(value, bound));
+    let config = goldilocks_v1::config(&DEFAULT_FRI_PARAMS);
+
+    let mut challenger = goldilocks_v1::challenger();
+    Ok(info_span!("commit_trace")
+        .in_scope(|| p3_uni_stark::prove(&config, &air, &mut challenger, trace, &public_values(value, bound))))
+}
+
+pub fn verify(value: u64, bound: u64, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    check_inputs(value, bound)?;
+    let air = GoldilocksBoundedRangeCheckAir { value, bound };
+    let config = goldilocks_v1::config(&DEFAULT_FRI_PARAMS);
+
+    let mut challenger = goldilocks_v1::challenger();
+    p3_uni_stark::verify(&config, &air, &mut challenger, proof, &public_values(value, bound))
+        .map_err(|e| Error::verification_failed(FieldKind::Goldilocks, value, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p3_field::PrimeField64;
+
+    use crate::debug::satisfies;
+
+    fn satisfied(trace: &RowMajorMatrix<Goldilocks>, value: u64, bound: u64) -> bool {
+        let air = GoldilocksBoundedRangeCheckAir { value, bound };
+        satisfies(&air, trace, &public_values(value, bound))
+    }
+
+    #[test]
+    fn values_below_the_bound_pass() {
+        let max = Goldilocks::ORDER_U64 - 1;
+        for (value, bound) in [(0, 1), (100, 101), ((1 << 63) - 1, 1 << 63), (0xFFFF_FFFF, 1 << 32), (max - 1, max)] {
+            assert!(satisfied(&generate_trace(value, bound), value, bound), "{} < {}", value, bound);
+        }
+    }
+
+    #[test]
+    fn values_at_or_above_the_bound_fail() {
+        // The honest trace for a smaller value can't be reused for the claimed one
+        let trace = generate_trace::<Goldilocks>((1 << 63) - 1, 1 << 63);
+        assert!(!satisfied(&trace, 1 << 63, 1 << 63));
+
+        // A slack that wraps around the modulus doesn't satisfy the limb equations
+        let mut trace = generate_trace::<Goldilocks>(0, 1 << 63);
+        // `bound - 1 - value` for `value == bound` is `-1`, i.e. `p - 1`
+        let wrapped = Goldilocks::ORDER_U64 - 1;
+        write_element(&mut trace.values[..ELEMENT_WIDTH], 1 << 63);
+        for (i, bit) in trace.values[2 * ELEMENT_WIDTH..WIDTH - 1].iter_mut().enumerate() {
+            *bit = Goldilocks::from_bool((wrapped >> (63 - i)) & 1 == 1);
+        }
+        assert!(!satisfied(&trace, 1 << 63, 1 << 63));
+    }
+
+    #[test]
+    fn bounds_outside_the_field_are_rejected() {
+        let order = Goldilocks::ORDER_U64;
+        assert!(matches!(prove(0, order), Err(Error::ValueOutOfField { .. })));
+        assert!(matches!(prove(0, u64::MAX), Err(Error::ValueOutOfField { .. })));
+        assert!(matches!(prove(1 << 63, 1 << 63), Err(Error::InvalidInput(_))));
+    }
+
+    #[test]
+    fn proof_near_the_modulus_verifies() {
+        let bound = Goldilocks::ORDER_U64 - 1;
+        let proof = prove(bound - 1, bound).unwrap();
+        verify(bound - 1, bound, &proof).unwrap();
+        assert!(verify(bound - 2, bound, &proof).is_err());
+    }
+}
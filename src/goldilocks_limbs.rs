@@ -0,0 +1,119 @@
+use p3_air::{Air, AirBuilderWithPublicValues, BaseAir};
+use p3_field::{AbstractField, Field};
+use p3_goldilocks::Goldilocks;
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::error::Error;
+use crate::field::{check_value_in_field, FieldKind};
+use crate::goldilocks_bounded::{eval_element, write_element, ELEMENT_WIDTH};
+use crate::goldilocks_v1::{self, MyConfig, DEFAULT_FRI_PARAMS};
+
+// Range checks the limbs of a big integer, side by side in a single row:
+// | limb 0 bits | limb 0 ANDs | limb 1 bits | limb 1 ANDs | ...
+// Every limb is a canonical Goldilocks element, see `eval_element`, and equal to its public value.
+// Public values are the limbs in order, which binds the whole integer.
+pub struct GoldilocksLimbsRangeCheckAir {
+    pub count: usize,
+}
+
+impl<F: Field> BaseAir<F> for GoldilocksLimbsRangeCheckAir {
+    fn width(&self) -> usize {
+        self.count * ELEMENT_WIDTH
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for GoldilocksLimbsRangeCheckAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let public_values = builder.public_values().to_vec();
+
+        for (columns, &limb) in local.chunks(ELEMENT_WIDTH).zip(public_values.iter()) {
+            let (hi, lo) = eval_element(builder, columns);
+            builder.assert_eq(hi * AB::Expr::from_canonical_u64(1 << 32) + lo, limb);
+        }
+    }
+}
+
+pub fn generate_trace<F: Field>(limbs: &[u64]) -> RowMajorMatrix<F> {
+    let mut row = vec![F::zero(); limbs.len() * ELEMENT_WIDTH];
+    for (columns, &limb) in row.chunks_mut(ELEMENT_WIDTH).zip(limbs) {
+        write_element(columns, limb);
+    }
+    RowMajorMatrix::new(row, limbs.len() * ELEMENT_WIDTH)
+}
+
+// The public values have to be canonical, so limbs at or above the modulus are rejected before proving.
+fn public_values(limbs: &[u64]) -> Result<Vec<Goldilocks>, Error> {
+    if limbs.is_empty() {
+        return Err(Error::InvalidInput("no limbs to range check".to_string()));
+    }
+    limbs
+        .iter()
+        .map(|&limb| {
+            check_value_in_field(FieldKind::Goldilocks, limb)?;
+            Ok(Goldilocks::from_canonical_u64(limb))
+        })
+        .collect()
+}
+
+/// Proves that every limb of a big integer, e.g. a `[u64; 4]` for 256 bits, is below the Goldilocks modulus.
+/// The limbs are the public values.
+pub fn prove_biguint_limbs(limbs: &[u64]) -> Result<Proof<MyConfig>, Error> {
+    let public_values = public_values(limbs)?;
+    let air = GoldilocksLimbsRangeCheckAir { count: limbs.len() };
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<Goldilocks>(limbs));
+    let config = goldilocks_v1::config(&DEFAULT_FRI_PARAMS);
+
+    let mut challenger = goldilocks_v1::challenger();
+    Ok(info_span!("commit_trace")
+        .in_scope(|| p3_uni_stark::prove(&config, &air, &mut challenger, trace, &public_values)))
+}
+
+pub fn verify_biguint_limbs(limbs: &[u64], proof: &Proof<MyConfig>) -> Result<(), Error> {
+    let public_values = public_values(limbs)?;
+    let air = GoldilocksLimbsRangeCheckAir { count: limbs.len() };
+    let config = goldilocks_v1::config(&DEFAULT_FRI_PARAMS);
+
+    let mut challenger = goldilocks_v1::challenger();
+    p3_uni_stark::verify(&config, &air, &mut challenger, proof, &public_values)
+        .map_err(|e| Error::verification_failed(FieldKind::Goldilocks, limbs[0], e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p3_field::PrimeField64;
+
+    use crate::debug::satisfies;
+
+    #[test]
+    fn canonical_limbs_pass() {
+        let limbs = [0, 1, u64::from(u32::MAX), Goldilocks::ORDER_U64 - 1];
+        let air = GoldilocksLimbsRangeCheckAir { count: limbs.len() };
+        let public = public_values(&limbs).unwrap();
+        assert!(satisfies(&air, &generate_trace::<Goldilocks>(&limbs), &public));
+    }
+
+    #[test]
+    fn a_limb_at_the_modulus_fails() {
+        // The bits of `p` reconstruct to 0, only the modulus boundary of the limb rejects them
+        let air = GoldilocksLimbsRangeCheckAir { count: 2 };
+        let trace = generate_trace::<Goldilocks>(&[5, Goldilocks::ORDER_U64]);
+        let public = [Goldilocks::from_canonical_u64(5), Goldilocks::zero()];
+        assert!(!satisfies(&air, &trace, &public));
+        assert!(matches!(prove_biguint_limbs(&[5, Goldilocks::ORDER_U64]), Err(Error::ValueOutOfField { .. })));
+    }
+
+    #[test]
+    fn proof_is_bound_to_every_limb() {
+        let limbs = [1, 2, 3, u64::MAX - (1 << 32)];
+        let proof = prove_biguint_limbs(&limbs).unwrap();
+        verify_biguint_limbs(&limbs, &proof).unwrap();
+        assert!(verify_biguint_limbs(&[1, 2, 4, u64::MAX - (1 << 32)], &proof).is_err());
+        assert!(verify_biguint_limbs(&limbs[..3], &proof).is_err());
+    }
+}
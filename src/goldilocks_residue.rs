@@ -0,0 +1,172 @@
+use p3_air::{Air, AirBuilderWithPublicValues, BaseAir};
+use p3_field::{AbstractField, Field, PrimeField64};
+use p3_goldilocks::Goldilocks;
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::bits::{reconstruct, write_bits};
+use crate::error::Error;
+use crate::field::{check_value_in_field, FieldKind};
+use crate::goldilocks_bounded::{eval_element, write_element, ELEMENT_WIDTH};
+use crate::goldilocks_v1::{self, MyConfig, DEFAULT_FRI_PARAMS};
+
+// Proves `value == hash_output % range` for a private 64 bit `hash_output`, with a single row:
+// | hash_output bits | hash_output ANDs | q bits | slack bits |
+// The hash output columns are the canonical element of `goldilocks_bounded`, so `hash_output < p`. Like in
+// `modular`, the constraints are `hash_output == q * range + value` and `q + slack == q_max`, with
+// `q_max = (p - 1 - value) / range`, so the right hand side is at most `p - 1` and can't wrap around the field.
+// For `range >= 2`, `q_max < 2^63` and `q + slack < 2^64 - 2^33 < p` can't wrap either.
+// Public values are `[value, range]`, `value < range` is checked outside of the AIR.
+pub struct GoldilocksResidueAir {
+    pub q_max: u64,
+}
+
+impl GoldilocksResidueAir {
+    pub fn new(range: u64, value: u64) -> Self {
+        Self { q_max: (Goldilocks::ORDER_U64 - 1 - value) / range }
+    }
+
+    // Both `q` and the slack have the bit length of `q_max`.
+    pub fn q_bits(&self) -> usize {
+        ((64 - self.q_max.leading_zeros()) as usize).max(1)
+    }
+}
+
+impl<F: Field> BaseAir<F> for GoldilocksResidueAir {
+    fn width(&self) -> usize {
+        ELEMENT_WIDTH + 2 * self.q_bits()
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for GoldilocksResidueAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let n = self.q_bits();
+
+        let public_values = builder.public_values();
+        let value: AB::Expr = public_values[0].into();
+        let range: AB::Expr = public_values[1].into();
+
+        let (hash_hi, hash_lo) = eval_element(builder, &local[..ELEMENT_WIDTH]);
+        let hash_output = hash_hi * AB::Expr::from_canonical_u64(1 << 32) + hash_lo;
+
+        // Making sure every bit of `q` and the slack is either 0 or 1
+        for &bit in &local[ELEMENT_WIDTH..] {
+            builder.assert_bool(bit);
+        }
+        let q = reconstruct::<AB>(&local[ELEMENT_WIDTH..ELEMENT_WIDTH + n]);
+        let slack = reconstruct::<AB>(&local[ELEMENT_WIDTH + n..]);
+
+        builder.assert_eq(q.clone() + slack, AB::Expr::from_canonical_u64(self.q_max));
+        builder.assert_eq(hash_output, q * range + value);
+    }
+}
+
+// Requires `hash_output < p`.
+pub fn generate_trace<F: Field>(hash_output: u64, range: u64) -> RowMajorMatrix<F> {
+    let air = GoldilocksResidueAir::new(range, hash_output % range);
+    let n = air.q_bits();
+    let q = hash_output / range;
+
+    let mut row = vec![F::zero(); ELEMENT_WIDTH + 2 * n];
+    write_element(&mut row[..ELEMENT_WIDTH], hash_output);
+    write_bits(&mut row[ELEMENT_WIDTH..ELEMENT_WIDTH + n], q);
+    write_bits(&mut row[ELEMENT_WIDTH + n..], air.q_max - q);
+    RowMajorMatrix::new(row, ELEMENT_WIDTH + 2 * n)
+}
+
+fn check_inputs(value: u64, range: u64) -> Result<Vec<Goldilocks>, Error> {
+    if range < 2 || range >= Goldilocks::ORDER_U64 {
+        return Err(Error::InvalidInput(format!("range {} has to be in [2, p)", range)));
+    }
+    if value >= range {
+        return Err(Error::InvalidInput(format!("{} isn't below the range {}", value, range)));
+    }
+    Ok(vec![Goldilocks::from_canonical_u64(value), Goldilocks::from_canonical_u64(range)])
+}
+
+/// Proves that `hash_output % range` is the returned value without revealing `hash_output`, e.g. for a hash
+/// reduced into `[0, range)`. Hash outputs at or above the Goldilocks order, a `2^-32` fraction, are rejected.
+pub fn prove_residue(hash_output: u64, range: u64) -> Result<(u64, Proof<MyConfig>), Error> {
+    check_value_in_field(FieldKind::Goldilocks, hash_output)?;
+    let value = hash_output % range.max(1);
+    let public_values = check_inputs(value, range)?;
+
+    let air = GoldilocksResidueAir::new(range, value);
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<Goldilocks>(hash_output, range));
+    let config = goldilocks_v1::config(&DEFAULT_FRI_PARAMS);
+
+    let mut challenger = goldilocks_v1::challenger();
+    let proof = info_span!("commit_trace")
+        .in_scope(|| p3_uni_stark::prove(&config, &air, &mut challenger, trace, &public_values));
+    Ok((value, proof))
+}
+
+pub fn verify_residue(value: u64, range: u64, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    let public_values = check_inputs(value, range)?;
+    let air = GoldilocksResidueAir::new(range, value);
+    let config = goldilocks_v1::config(&DEFAULT_FRI_PARAMS);
+
+    let mut challenger = goldilocks_v1::challenger();
+    p3_uni_stark::verify(&config, &air, &mut challenger, proof, &public_values)
+        .map_err(|e| Error::verification_failed(FieldKind::Goldilocks, value, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::debug::satisfies;
+
+    fn satisfied(trace: &RowMajorMatrix<Goldilocks>, value: u64, range: u64) -> bool {
+        let air = GoldilocksResidueAir::new(range, value);
+        satisfies(&air, trace, &check_inputs(value, range).unwrap())
+    }
+
+    #[test]
+    fn residues_satisfy_constraints() {
+        let max = Goldilocks::ORDER_U64 - 1;
+        for (hash_output, range) in [(0, 2), (7, 2), (u64::from(u32::MAX), 1000), (max, 3), (max, max), (5, max)] {
+            let trace = generate_trace::<Goldilocks>(hash_output, range);
+            assert!(satisfied(&trace, hash_output % range, range), "{} mod {}", hash_output, range);
+        }
+    }
+
+    #[test]
+    fn wrong_residue_fails() {
+        let trace = generate_trace::<Goldilocks>(1_000_003, 1000);
+        assert!(!satisfied(&trace, 4, 1000));
+        assert!(!satisfied(&trace, 3, 1001));
+    }
+
+    #[test]
+    fn quotient_above_q_max_fails() {
+        // `hash_output == q * range + value` holds in the field for `q = q_max + 1` once the right hand side
+        // wraps around, but the slack would have to be -1
+        let (value, range) = (1, 3);
+        let air = GoldilocksResidueAir::new(range, value);
+        let n = air.q_bits();
+        let q = air.q_max + 1;
+        let order = u128::from(Goldilocks::ORDER_U64);
+        let wrapped = (u128::from(q) * u128::from(range) + u128::from(value) - order) as u64;
+
+        let mut row = vec![Goldilocks::zero(); ELEMENT_WIDTH + 2 * n];
+        write_element(&mut row[..ELEMENT_WIDTH], wrapped);
+        write_bits(&mut row[ELEMENT_WIDTH..ELEMENT_WIDTH + n], q);
+        assert!(!satisfied(&RowMajorMatrix::new(row, ELEMENT_WIDTH + 2 * n), value, range));
+    }
+
+    #[test]
+    fn residue_proves_and_verifies() {
+        let (value, proof) = prove_residue(0xDEAD_BEEF_1234, 1_000_000).unwrap();
+        assert_eq!(value, 0xDEAD_BEEF_1234 % 1_000_000);
+        verify_residue(value, 1_000_000, &proof).unwrap();
+        assert!(verify_residue(value + 1, 1_000_000, &proof).is_err());
+        assert!(matches!(verify_residue(1_000_000, 1_000_000, &proof), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_residue(7, 1), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_residue(u64::MAX, 1000), Err(Error::ValueOutOfField { .. })));
+    }
+}
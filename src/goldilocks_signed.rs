@@ -0,0 +1,159 @@
+use p3_air::{Air, AirBuilderWithPublicValues, BaseAir};
+use p3_field::{AbstractField, Field};
+use p3_goldilocks::Goldilocks;
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::bits::reconstruct;
+use crate::error::Error;
+use crate::field::FieldKind;
+use crate::goldilocks_v1::{self, MyConfig, DEFAULT_FRI_PARAMS};
+
+// Proves `-2^(bits - 1) <= value < 2^(bits - 1)` for a signed 64 bit `value`, with a single row of the 64 bit
+// two's complement of `value` in big endian format. The first bit is the sign and the last `bits - 1` bits the
+// magnitude, the value fits in `bits` bits iff the `64 - bits` bits in between repeat the sign.
+// `bits == 64` leaves nothing to repeat, every `i64` is in range.
+//
+// A negative `i64` has no canonical single element encoding, `-2^63 + m` and `2^63 - 2^32 + 1 + m` are the same
+// element, so the public values are the two 32 bit limbs of the two's complement, `[hi, lo, bits]`. The bits
+// are fixed by the limbs, so zero and every other value have exactly one trace.
+pub struct GoldilocksSignedRangeCheckAir {
+    pub bits: u32,
+}
+
+impl<F: Field> BaseAir<F> for GoldilocksSignedRangeCheckAir {
+    fn width(&self) -> usize {
+        64
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for GoldilocksSignedRangeCheckAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+
+        let public_values = builder.public_values();
+        let hi: AB::Expr = public_values[0].into();
+        let lo: AB::Expr = public_values[1].into();
+        let bits: AB::Expr = public_values[2].into();
+        builder.assert_eq(bits, AB::Expr::from_canonical_u32(self.bits));
+
+        // Making sure every bit is either 0 or 1
+        for &bit in local.iter() {
+            builder.assert_bool(bit);
+        }
+
+        // Limbs are below 2^32, so they can't wrap around the modulus
+        builder.assert_eq(reconstruct::<AB>(&local[..32]), hi);
+        builder.assert_eq(reconstruct::<AB>(&local[32..]), lo);
+
+        // Sign extension
+        let sign = local[0];
+        for &bit in &local[1..=64 - self.bits as usize] {
+            builder.assert_eq(bit, sign);
+        }
+    }
+}
+
+pub fn generate_trace<F: Field>(value: i64) -> RowMajorMatrix<F> {
+    let value = value as u64;
+    let row = (0..64).map(|i| F::from_bool((value >> (63 - i)) & 1 == 1)).collect();
+    RowMajorMatrix::new(row, 64)
+}
+
+fn public_values(value: i64, bits: u32) -> Vec<Goldilocks> {
+    let value = value as u64;
+    vec![
+        Goldilocks::from_canonical_u64(value >> 32),
+        Goldilocks::from_canonical_u64(value & 0xFFFF_FFFF),
+        Goldilocks::from_canonical_u32(bits),
+    ]
+}
+
+fn check_bits(bits: u32) -> Result<(), Error> {
+    if !(1..=64).contains(&bits) {
+        return Err(Error::InvalidInput(format!("{} bits isn't in [1, 64]", bits)));
+    }
+    Ok(())
+}
+
+/// Proves that `value` fits in a `bits` bit two's complement integer, `bits` in `[1, 64]`.
+pub fn prove_signed_goldilocks(value: i64, bits: u32) -> Result<Proof<MyConfig>, Error> {
+    check_bits(bits)?;
+    // `value >> (bits - 1)` is all zeros or all ones iff the bits above the magnitude repeat the sign
+    let rest = value >> (bits - 1);
+    if rest != 0 && rest != -1 {
+        return Err(Error::InvalidInput(format!("{} doesn't fit in {} signed bits", value, bits)));
+    }
+
+    let air = GoldilocksSignedRangeCheckAir { bits };
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<Goldilocks>(value));
+    let config = goldilocks_v1::config(&DEFAULT_FRI_PARAMS);
+
+    let mut challenger = goldilocks_v1::challenger();
+    Ok(info_span!("commit_trace")
+        .in_scope(|| p3_uni_stark::prove(&config, &air, &mut challenger, trace, &public_values(value, bits))))
+}
+
+pub fn verify_signed_goldilocks(value: i64, bits: u32, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    check_bits(bits)?;
+    let air = GoldilocksSignedRangeCheckAir { bits };
+    let config = goldilocks_v1::config(&DEFAULT_FRI_PARAMS);
+
+    let mut challenger = goldilocks_v1::challenger();
+    p3_uni_stark::verify(&config, &air, &mut challenger, proof, &public_values(value, bits))
+        .map_err(|e| Error::verification_failed(FieldKind::Goldilocks, value as u64, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::debug::satisfies;
+
+    fn satisfied(value: i64, bits: u32) -> bool {
+        let air = GoldilocksSignedRangeCheckAir { bits };
+        satisfies(&air, &generate_trace::<Goldilocks>(value), &public_values(value, bits))
+    }
+
+    #[test]
+    fn signed_bounds_are_inclusive_below_and_exclusive_above() {
+        assert!(satisfied(-128, 8));
+        assert!(satisfied(127, 8));
+        assert!(satisfied(0, 8));
+        assert!(satisfied(-1, 1));
+        assert!(satisfied(0, 1));
+        assert!(!satisfied(-129, 8));
+        assert!(!satisfied(128, 8));
+        assert!(!satisfied(1, 1));
+    }
+
+    #[test]
+    fn full_width_accepts_every_value() {
+        for value in [i64::MIN, -1, 0, 1, i64::MAX] {
+            assert!(satisfied(value, 64));
+        }
+        assert!(!satisfied(i64::MIN, 63));
+        assert!(!satisfied(i64::MAX, 63));
+    }
+
+    #[test]
+    fn zero_has_a_single_encoding() {
+        // All ones is -1, not a negative zero, so it can't be passed off as 0
+        let trace = generate_trace::<Goldilocks>(-1);
+        let air = GoldilocksSignedRangeCheckAir { bits: 8 };
+        assert!(!satisfies(&air, &trace, &public_values(0, 8)));
+    }
+
+    #[test]
+    fn proof_verifies_for_the_value_and_bits() {
+        let proof = prove_signed_goldilocks(-5, 4).unwrap();
+        verify_signed_goldilocks(-5, 4, &proof).unwrap();
+        assert!(verify_signed_goldilocks(5, 4, &proof).is_err());
+        assert!(verify_signed_goldilocks(-5, 5, &proof).is_err());
+        assert!(matches!(prove_signed_goldilocks(8, 4), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_signed_goldilocks(0, 0), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_signed_goldilocks(0, 65), Err(Error::InvalidInput(_))));
+    }
+}
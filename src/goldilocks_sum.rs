@@ -0,0 +1,188 @@
+use p3_air::{Air, AirBuilderWithPublicValues, BaseAir};
+use p3_field::{AbstractField, Field, PrimeField64};
+use p3_goldilocks::Goldilocks;
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::bits::reconstruct;
+use crate::error::Error;
+use crate::field::{check_value_in_field, FieldKind};
+use crate::goldilocks_bounded::{eval_element, write_element, ELEMENT_WIDTH};
+use crate::goldilocks_v1::{self, MyConfig, DEFAULT_FRI_PARAMS};
+
+const ACC: usize = ELEMENT_WIDTH;
+const CARRY: usize = 2 * ELEMENT_WIDTH;
+const ACTIVE: usize = CARRY + 1;
+const SEEN: usize = CARRY + 2;
+const WIDTH: usize = CARRY + 3;
+
+// Proves `value == part_0 + part_1 + ...` over the integers, one part per row:
+// | part bits | part ANDs | acc bits | acc ANDs | carry | active | seen |
+// Parts and the running sum `acc` are canonical elements, and every step adds on 32 bit limbs,
+// `acc_lo + part_lo == next_acc_lo + carry * 2^32` and `acc_hi + part_hi + carry == next_acc_hi`, so the sum can't
+// wrap around the modulus. Padding rows, up to the next power of two, aren't active and have to hold a zero part,
+// `seen` counts the active rows.
+// Public values are `[value, number of parts]`.
+pub struct GoldilocksSumDecompositionAir;
+
+impl<F: Field> BaseAir<F> for GoldilocksSumDecompositionAir {
+    fn width(&self) -> usize {
+        WIDTH
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for GoldilocksSumDecompositionAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let next = main.row_slice(1);
+
+        let public_values = builder.public_values();
+        let value: AB::Expr = public_values[0].into();
+        let count: AB::Expr = public_values[1].into();
+
+        let (part_hi, part_lo) = eval_element(builder, &local[..ACC]);
+        let (acc_hi, acc_lo) = eval_element(builder, &local[ACC..CARRY]);
+        // The elements of the next row are constrained on that row, only its limbs are needed here
+        let next_part_hi = reconstruct::<AB>(&next[..32]);
+        let next_part_lo = reconstruct::<AB>(&next[32..64]);
+        let next_acc_hi = reconstruct::<AB>(&next[ACC..ACC + 32]);
+        let next_acc_lo = reconstruct::<AB>(&next[ACC + 32..ACC + 64]);
+
+        builder.assert_bool(local[CARRY]);
+        builder.assert_bool(local[ACTIVE]);
+
+        // Padding rows can't add anything to the sum
+        let inactive = AB::Expr::one() - local[ACTIVE];
+        builder.assert_zero(part_hi.clone() * inactive.clone());
+        builder.assert_zero(part_lo.clone() * inactive);
+
+        // The sum starts at the first part
+        builder.when_first_row().assert_eq(acc_hi.clone(), part_hi);
+        builder.when_first_row().assert_eq(acc_lo.clone(), part_lo);
+        builder.when_first_row().assert_eq(local[SEEN], local[ACTIVE]);
+
+        // Every transition adds the next part
+        let mut transition = builder.when_transition();
+        transition.assert_eq(
+            acc_lo.clone() + next_part_lo,
+            next_acc_lo + AB::Expr::from(next[CARRY]) * AB::Expr::from_canonical_u64(1 << 32),
+        );
+        transition.assert_eq(acc_hi.clone() + next_part_hi + next[CARRY], next_acc_hi);
+        transition.assert_eq(next[SEEN], local[SEEN] + next[ACTIVE]);
+
+        builder.when_last_row().assert_eq(acc_hi * AB::Expr::from_canonical_u64(1 << 32) + acc_lo, value);
+        builder.when_last_row().assert_eq(local[SEEN], count);
+    }
+}
+
+// Requires the parts to sum to less than the modulus.
+pub fn generate_trace<F: Field>(parts: &[u64]) -> RowMajorMatrix<F> {
+    let height = parts.len().next_power_of_two();
+    let mut trace = RowMajorMatrix::new(vec![F::zero(); height * WIDTH], WIDTH);
+
+    let mut acc = 0u64;
+    for (i, row) in trace.values.chunks_mut(WIDTH).enumerate() {
+        let part = parts.get(i).copied().unwrap_or(0);
+        let carry = ((acc & 0xFFFF_FFFF) + (part & 0xFFFF_FFFF)) >> 32;
+        acc += part;
+        write_element(&mut row[..ACC], part);
+        write_element(&mut row[ACC..CARRY], acc);
+        row[CARRY] = F::from_canonical_u64(carry);
+        row[ACTIVE] = F::from_bool(i < parts.len());
+        row[SEEN] = F::from_canonical_usize(parts.len().min(i + 1));
+    }
+    trace
+}
+
+fn public_values(value: u64, count: usize) -> Vec<Goldilocks> {
+    vec![Goldilocks::from_canonical_u64(value), Goldilocks::from_canonical_usize(count)]
+}
+
+/// Proves that `value` is the sum of `parts`, each a canonical element and the sum below the modulus.
+/// Only `value` and the number of parts are public.
+pub fn prove_sum_decomposition(value: u64, parts: &[u64]) -> Result<Proof<MyConfig>, Error> {
+    check_value_in_field(FieldKind::Goldilocks, value)?;
+    if parts.is_empty() {
+        return Err(Error::InvalidInput("no parts to sum".to_string()));
+    }
+    for &part in parts {
+        check_value_in_field(FieldKind::Goldilocks, part)?;
+    }
+    let sum: u128 = parts.iter().map(|&part| u128::from(part)).sum();
+    if sum >= u128::from(Goldilocks::ORDER_U64) {
+        return Err(Error::InvalidInput(format!("the parts sum to {}, which overflows the field", sum)));
+    }
+    if sum != u128::from(value) {
+        return Err(Error::InvalidInput(format!("the parts sum to {}, not {}", sum, value)));
+    }
+
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<Goldilocks>(parts));
+    let config = goldilocks_v1::config(&DEFAULT_FRI_PARAMS);
+
+    let mut challenger = goldilocks_v1::challenger();
+    let public_values = public_values(value, parts.len());
+    Ok(info_span!("commit_trace").in_scope(|| {
+        p3_uni_stark::prove(&config, &GoldilocksSumDecompositionAir, &mut challenger, trace, &public_values)
+    }))
+}
+
+pub fn verify_sum_decomposition(value: u64, count: usize, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    check_value_in_field(FieldKind::Goldilocks, value)?;
+    let config = goldilocks_v1::config(&DEFAULT_FRI_PARAMS);
+
+    let mut challenger = goldilocks_v1::challenger();
+    p3_uni_stark::verify(&config, &GoldilocksSumDecompositionAir, &mut challenger, proof, &public_values(value, count))
+        .map_err(|e| Error::verification_failed(FieldKind::Goldilocks, value, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::debug::satisfies;
+
+    fn satisfied(trace: &RowMajorMatrix<Goldilocks>, value: u64, count: usize) -> bool {
+        satisfies(&GoldilocksSumDecompositionAir, trace, &public_values(value, count))
+    }
+
+    #[test]
+    fn parts_summing_to_the_value_pass() {
+        let max = Goldilocks::ORDER_U64 - 1;
+        assert!(satisfied(&generate_trace(&[5]), 5, 1));
+        assert!(satisfied(&generate_trace(&[1, 2, 3]), 6, 3));
+        assert!(satisfied(&generate_trace(&[0xFFFF_FFFF, 1, max - (1 << 32)]), max, 3));
+    }
+
+    #[test]
+    fn wrong_sums_and_counts_fail() {
+        let trace = generate_trace::<Goldilocks>(&[1, 2, 3]);
+        assert!(!satisfied(&trace, 7, 3));
+        assert!(!satisfied(&trace, 6, 4));
+
+        // A part hidden in a padding row
+        let mut trace = generate_trace::<Goldilocks>(&[1, 2, 3, 4]);
+        trace.values[3 * WIDTH + ACTIVE] = Goldilocks::zero();
+        trace.values[3 * WIDTH + SEEN] = Goldilocks::from_canonical_u64(3);
+        assert!(!satisfied(&trace, 10, 3));
+    }
+
+    #[test]
+    fn sums_wrapping_around_the_modulus_fail() {
+        // `(p - 1) + 2` is 1 in the field, but the running sum has to stay canonical
+        let max = Goldilocks::ORDER_U64 - 1;
+        let mut trace = generate_trace::<Goldilocks>(&[max, 2]);
+        write_element(&mut trace.values[WIDTH + ACC..WIDTH + CARRY], 1);
+        assert!(!satisfied(&trace, 1, 2));
+        assert!(matches!(prove_sum_decomposition(1, &[max, 2]), Err(Error::InvalidInput(_))));
+    }
+
+    #[test]
+    fn proof_verifies_for_the_value_and_count() {
+        let proof = prove_sum_decomposition(100, &[40, 35, 25]).unwrap();
+        verify_sum_decomposition(100, 3, &proof).unwrap();
+        assert!(verify_sum_decomposition(101, 3, &proof).is_err());
+        assert!(verify_sum_decomposition(100, 2, &proof).is_err());
+    }
+}
@@ -0,0 +1,100 @@
+use p3_air::{Air, AirBuilderWithPublicValues, BaseAir};
+use p3_field::{AbstractField, Field, PrimeField64};
+use p3_goldilocks::Goldilocks;
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::bits::reconstruct;
+use crate::error::Error;
+use crate::field::FieldKind;
+use crate::goldilocks_v1::{self, MyConfig, DEFAULT_FRI_PARAMS};
+
+// Proves that a Goldilocks element's canonical representative is below `2^32`, with a single row of its 32 bits
+// in big endian format. 32 bits reconstruct to less than the modulus, so the reconstruction equals the public
+// element only if the element is that `u32`, no boundary constraints are needed.
+// Public values are `[value]`.
+pub struct GoldilocksU32RangeCheckAir;
+
+impl<F: Field> BaseAir<F> for GoldilocksU32RangeCheckAir {
+    fn width(&self) -> usize {
+        32
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for GoldilocksU32RangeCheckAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let value: AB::Expr = builder.public_values()[0].into();
+
+        // Making sure every bit is either 0 or 1
+        for &bit in local.iter() {
+            builder.assert_bool(bit);
+        }
+
+        builder.assert_eq(reconstruct::<AB>(&local), value);
+    }
+}
+
+pub fn generate_trace<F: Field>(value: u32) -> RowMajorMatrix<F> {
+    let row = (0..32).map(|i| F::from_bool((value >> (31 - i)) & 1 == 1)).collect();
+    RowMajorMatrix::new(row, 32)
+}
+
+/// Proves that `value`, e.g. the output of a Goldilocks computation, is a `u32`, so it can be used as a value of
+/// the 32 bit field provers. The element itself is the public input.
+pub fn prove_goldilocks_value_as_u32(value: Goldilocks) -> Result<Proof<MyConfig>, Error> {
+    let canonical = value.as_canonical_u64();
+    let value_u32 = u32::try_from(canonical)
+        .map_err(|_| Error::InvalidInput(format!("{} doesn't fit in 32 bits", canonical)))?;
+
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<Goldilocks>(value_u32));
+    let config = goldilocks_v1::config(&DEFAULT_FRI_PARAMS);
+
+    let mut challenger = goldilocks_v1::challenger();
+    Ok(info_span!("commit_trace")
+        .in_scope(|| p3_uni_stark::prove(&config, &GoldilocksU32RangeCheckAir, &mut challenger, trace, &vec![value])))
+}
+
+pub fn verify_goldilocks_value_as_u32(value: Goldilocks, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    let config = goldilocks_v1::config(&DEFAULT_FRI_PARAMS);
+
+    let mut challenger = goldilocks_v1::challenger();
+    p3_uni_stark::verify(&config, &GoldilocksU32RangeCheckAir, &mut challenger, proof, &vec![value])
+        .map_err(|e| Error::verification_failed(FieldKind::Goldilocks, value.as_canonical_u64(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::debug::satisfies;
+
+    fn satisfied(trace: &RowMajorMatrix<Goldilocks>, value: Goldilocks) -> bool {
+        satisfies(&GoldilocksU32RangeCheckAir, trace, &[value])
+    }
+
+    #[test]
+    fn u32_elements_pass() {
+        for value in [0, 1, u32::MAX] {
+            assert!(satisfied(&generate_trace(value), Goldilocks::from_canonical_u32(value)));
+        }
+    }
+
+    #[test]
+    fn larger_elements_fail() {
+        // `2^32` and `-1` agree with `0` and `u32::MAX` in the low 32 bits, but not as elements
+        assert!(!satisfied(&generate_trace(0), Goldilocks::from_canonical_u64(1 << 32)));
+        assert!(!satisfied(&generate_trace(u32::MAX), -Goldilocks::one()));
+        assert!(matches!(prove_goldilocks_value_as_u32(-Goldilocks::one()), Err(Error::InvalidInput(_))));
+    }
+
+    #[test]
+    fn proof_verifies_for_the_element() {
+        let value = Goldilocks::from_canonical_u32(u32::MAX);
+        let proof = prove_goldilocks_value_as_u32(value).unwrap();
+        verify_goldilocks_value_as_u32(value, &proof).unwrap();
+        assert!(verify_goldilocks_value_as_u32(value + Goldilocks::one(), &proof).is_err());
+    }
+}
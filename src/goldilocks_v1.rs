@@ -1,50 +1,59 @@
-use p3_air::{Air, AirBuilder, BaseAir};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use p3_air::{Air, AirBuilderWithPublicValues, BaseAir};
 use p3_field::{AbstractField, Field};
 use p3_matrix::Matrix;
 use p3_matrix::dense::RowMajorMatrix;
 
-use p3_challenger::{HashChallenger, SerializingChallenger64};
+use p3_challenger::{CanObserve, HashChallenger, SerializingChallenger64};
 use p3_commit::ExtensionMmcs;
-use p3_dft::Radix2DitParallel;
+use p3_dft::{Radix2Dit, Radix2DitParallel, TwoAdicSubgroupDft};
 use p3_field::extension::BinomialExtensionField;
-use p3_fri::{FriConfig, TwoAdicFriPcs};
+use p3_fri::TwoAdicFriPcs;
 use p3_goldilocks::Goldilocks;
 use p3_merkle_tree::MerkleTreeMmcs;
 use p3_keccak::Keccak256Hash;
 use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher64};
-use p3_uni_stark::{prove, verify, StarkConfig};
-use tracing_forest::util::LevelFilter;
-use tracing_forest::ForestLayer;
-use tracing_subscriber::layer::SubscriberExt;
-use tracing_subscriber::util::SubscriberInitExt;
-use tracing_subscriber::{EnvFilter, Registry};
+use p3_uni_stark::{Proof, StarkConfig};
+use tracing::info_span;
+
+use crate::config::{FriParams, MERKLE_ARITY};
+use crate::error::Error;
+use crate::field::{check_value_in_field, FieldKind};
+use crate::modulus::{eval_modulus_boundary, GOLDILOCKS};
+use crate::proof_info::{ProofStats, ProveStats};
+use crate::prover;
 
 pub struct GoldilocksRangeCheckAir {
     pub value: u64, // define constraint input, value is assigned to check against the reconstructed value.
 }
 
-// Goldilocks Modulus in big endian format:
+impl GoldilocksRangeCheckAir {
+    pub const WIDTH: usize = 64;
+}
+
+// Goldilocks Modulus in big endian format, see `modulus::GOLDILOCKS`:
 // 11111111 11111111 11111111 11111111 00000000 00000000 00000000 00000001
 // 2^64 - 2^32 + 1
 impl<F: Field> BaseAir<F> for GoldilocksRangeCheckAir {
     fn width(&self) -> usize {
-        64
+        Self::WIDTH
     }
 }
 
-impl<AB: AirBuilder> Air<AB> for GoldilocksRangeCheckAir {
+impl<AB: AirBuilderWithPublicValues> Air<AB> for GoldilocksRangeCheckAir {
     fn eval(&self, builder: &mut AB) {
         let main = builder.main();
         let current_row = main.row_slice(0);
 
-        // Value to check if the 1st to 32nd bits are all one
-        let upper_bits_product = current_row[0..32].iter().map(|&bit| bit.into()).product::<AB::Expr>();
-        // Value to check if the sum of the remaining bits is zero, only if `upper_bits_product` is 1.
-        let remaining_bits_sum = current_row[32..64].iter().map(|&bit| bit.into()).sum::<AB::Expr>();
-        
-        // Assert if the 0th to 31st bits are all one, then `remaining_bits_sum` has to be zero.
-        builder.when(upper_bits_product.clone()).assert_zero(remaining_bits_sum.clone());
-        // builder.assert_zero(remaining_bits_sum);
+        // The public value is the value, see `public_values`
+        let value: AB::Expr = builder.public_values()[0].into();
+        builder.assert_eq(value.clone(), AB::Expr::from_wrapped_u64(self.value));
+
+        // Assert if the 0th to 31st bits are all one, then the remaining bits have to be zero.
+        // The constraints are generated from the modulus pattern.
+        eval_modulus_boundary(builder, &current_row, &GOLDILOCKS);
 
         // initializing the `reconstructed_value`
         let mut reconstructed_value = AB::Expr::zero();
@@ -56,7 +65,7 @@ impl<AB: AirBuilder> Air<AB> for GoldilocksRangeCheckAir {
         }
 
         // Assert if the reconstructed value matches the original value
-        builder.when_first_row().assert_eq(AB::Expr::from_wrapped_u64(self.value), reconstructed_value);
+        builder.when_first_row().assert_eq(value, reconstructed_value);
     }
 }
 
@@ -73,56 +82,350 @@ pub fn generate_trace<F: Field>(value: u64) -> RowMajorMatrix<F> {
     RowMajorMatrix::new(bits, 64)
 }
 
-pub fn prove_and_verify<F: Field>(value: u64) {
-    let env_filter = EnvFilter::builder()
-        .with_default_directive(LevelFilter::INFO.into())
-        .from_env_lossy();
+pub const DEFAULT_FRI_PARAMS: FriParams = FriParams {
+    log_blowup: 5,
+    num_queries: 100,
+    proof_of_work_bits: 16,
+};
+
+// Smallest proofs at 100 conjectured bits of security, `6 * 14 + 16`, see `config::ProofSizePreset`.
+pub const MINIMAL_FRI_PARAMS: FriParams = FriParams {
+    log_blowup: 6,
+    num_queries: 14,
+    proof_of_work_bits: 16,
+};
+
+pub type Val = Goldilocks;
+pub type Challenge = BinomialExtensionField<Val, 2>;
 
-    Registry::default()
-        .with(env_filter)
-        .with(ForestLayer::default())
-        .init();
+pub type ByteHash = Keccak256Hash;
+pub type FieldHash = SerializingHasher64<ByteHash>;
+pub type MyCompress = CompressionFunctionFromHasher<ByteHash, MERKLE_ARITY, 32>;
+pub type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+pub type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+pub type Challenger = SerializingChallenger64<Val, HashChallenger<u8, ByteHash, 32>>;
+pub type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+pub type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
 
-    type Val = Goldilocks;
-    type Challenge = BinomialExtensionField<Val, 2>;
+// Nameable outside of this module, e.g. to store proofs.
+pub type GoldilocksConfig = MyConfig;
+pub type GoldilocksProof = Proof<MyConfig>;
 
-    type ByteHash = Keccak256Hash;
-    type FieldHash = SerializingHasher64<ByteHash>;
+/// The DFT of the prover, chosen at runtime and passed to `config_with_dft`. Both compute the same LDE, so proofs
+/// don't depend on the choice.
+#[derive(Clone)]
+pub enum Dft {
+    // Spreads the work over the rayon pool, the default.
+    Parallel(Radix2DitParallel<Val>),
+    // Stays on the calling thread, for environments without spare threads.
+    SingleThreaded(Radix2Dit<Val>),
+}
+
+impl Default for Dft {
+    fn default() -> Self {
+        Dft::Parallel(Radix2DitParallel::default())
+    }
+}
+
+// Both DFTs return natural order evaluations, the parallel one as a bit reversed view that is copied out here.
+impl TwoAdicSubgroupDft<Val> for Dft {
+    type Evaluations = RowMajorMatrix<Val>;
+
+    fn dft_batch(&self, mat: RowMajorMatrix<Val>) -> Self::Evaluations {
+        match self {
+            Dft::Parallel(dft) => dft.dft_batch(mat).to_row_major_matrix(),
+            Dft::SingleThreaded(dft) => dft.dft_batch(mat),
+        }
+    }
+
+    fn idft_batch(&self, mat: RowMajorMatrix<Val>) -> RowMajorMatrix<Val> {
+        match self {
+            Dft::Parallel(dft) => dft.idft_batch(mat),
+            Dft::SingleThreaded(dft) => dft.idft_batch(mat),
+        }
+    }
+
+    fn coset_lde_batch(&self, mat: RowMajorMatrix<Val>, added_bits: usize, shift: Val) -> Self::Evaluations {
+        match self {
+            Dft::Parallel(dft) => dft.coset_lde_batch(mat, added_bits, shift).to_row_major_matrix(),
+            Dft::SingleThreaded(dft) => dft.coset_lde_batch(mat, added_bits, shift),
+        }
+    }
+}
+
+pub fn config(params: &FriParams) -> MyConfig {
+    config_with_dft(params, &Dft::default())
+}
+
+// The DFT caches its twiddle factors per height, and clones of `Dft::Parallel` share the cache (`Dft::SingleThreaded`
+// copies what is cached so far). Reusing one DFT skips recomputing the twiddles for every proof of the same height.
+pub fn config_with_dft(params: &FriParams, dft: &Dft) -> MyConfig {
     let byte_hash = ByteHash {};
     let field_hash = FieldHash::new(byte_hash);
-
-    type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
     let compress = MyCompress::new(byte_hash);
-
-    type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
     let val_mmcs = ValMmcs::new(field_hash, compress);
-
-    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
     let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
 
-    type Dft = Radix2DitParallel<Val>;
-    let dft = Dft::default();
+    let fri_config = params.fri_config(challenge_mmcs);
+    let pcs = Pcs::new(dft.clone(), val_mmcs, fri_config);
+    MyConfig::new(pcs)
+}
+
+// A fresh challenger with an empty transcript.
+pub fn challenger() -> Challenger {
+    Challenger::from_hasher(vec![], ByteHash {})
+}
+
+/// Proves `value < p` with the default FRI parameters, see `verify` to check the returned proof.
+pub fn prove(value: u64) -> Proof<MyConfig> {
+    prove_with_params(value, &DEFAULT_FRI_PARAMS)
+}
+
+// Runs `prove` on tokio's blocking thread pool so it doesn't stall the async runtime. A panic in the prover is
+// resumed in the awaiting task.
+#[cfg(feature = "async")]
+pub async fn prove_async(value: u64) -> Proof<MyConfig> {
+    match tokio::task::spawn_blocking(move || prove(value)).await {
+        Ok(proof) => proof,
+        Err(e) if e.is_panic() => std::panic::resume_unwind(e.into_panic()),
+        Err(e) => panic!("proving task didn't finish: {}", e),
+    }
+}
+
+pub fn prove_with_params(value: u64, params: &FriParams) -> Proof<MyConfig> {
+    prove_with_challenger(value, &public_values(value), params, &mut challenger())
+}
+
+// The public values of the range check of `value`, the value itself reduced into the field like the AIR does.
+pub fn public_values(value: u64) -> Vec<Val> {
+    vec![Val::from_wrapped_u64(value)]
+}
+
+// Continues the transcript of `challenger`, binding the proof to everything observed before.
+pub fn prove_with_challenger(
+    value: u64,
+    public_values: &[Val],
+    params: &FriParams,
+    challenger: &mut Challenger,
+) -> Proof<MyConfig> {
+    prove_with_trace_hook(value, public_values, params, challenger, None)
+}
+
+/// Like `prove_with_params`, with the DFT of `dft` and its cached twiddle factors, for proving many values in a row.
+pub fn prove_with_dft(value: u64, params: &FriParams, dft: &Dft) -> Proof<MyConfig> {
+    let config = config_with_dft(params, dft);
+    let air = GoldilocksRangeCheckAir { value };
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<Val>(value));
+
+    info_span!("commit_trace")
+        .in_scope(|| p3_uni_stark::prove(&config, &air, &mut challenger(), trace, &public_values(value)))
+}
+
+// `trace_hook` is called with the generated trace right before it is committed to.
+pub fn prove_with_trace_hook(
+    value: u64,
+    public_values: &[Val],
+    params: &FriParams,
+    challenger: &mut Challenger,
+    trace_hook: Option<&mut dyn FnMut(&RowMajorMatrix<Val>)>,
+) -> Proof<MyConfig> {
+    let config = config(params);
+
+    let air = GoldilocksRangeCheckAir { value };
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<Val>(value));
+
+    if let Some(trace_hook) = trace_hook {
+        trace_hook(&trace);
+    }
+
+    info_span!("commit_trace").in_scope(|| p3_uni_stark::prove(&config, &air, challenger, trace, public_values))
+}
+
+/// Like `prove`, but returns `Error::Cancelled` once `cancel` is set.
+///
+/// `p3_uni_stark::prove` runs the commitments, the quotient and FRI in one call without a way to interrupt it,
+/// so the flag is only checked before and after trace generation and after proving. A cancelled call still
+/// finishes the `p3_uni_stark::prove` it is in, but its proof is dropped.
+pub fn prove_with_cancel(value: u64, cancel: &AtomicBool) -> Result<Proof<MyConfig>, Error> {
+    let check = || if cancel.load(Ordering::Relaxed) { Err(Error::Cancelled) } else { Ok(()) };
+
+    check()?;
+    let config = config(&DEFAULT_FRI_PARAMS);
+    let air = GoldilocksRangeCheckAir { value };
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<Val>(value));
+
+    check()?;
+    let proof = info_span!("commit_trace")
+        .in_scope(|| p3_uni_stark::prove(&config, &air, &mut challenger(), trace, &public_values(value)));
 
-    type Challenger = SerializingChallenger64<Val, HashChallenger<u8, ByteHash, 32>>;
+    check()?;
+    Ok(proof)
+}
+
+pub fn verify(value: u64, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    verify_with_params(value, proof, &DEFAULT_FRI_PARAMS)
+}
 
-    let fri_config = FriConfig {
-        log_blowup: 5,
-        num_queries: 100,
-        proof_of_work_bits: 16,
-        mmcs: challenge_mmcs,
-    };
-    type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
-    let pcs = Pcs::new(dft, val_mmcs, fri_config);
+pub fn verify_with_params(
+    value: u64,
+    proof: &Proof<MyConfig>,
+    params: &FriParams,
+) -> Result<(), Error> {
+    verify_with_challenger(value, &public_values(value), proof, params, &mut challenger())
+}
 
-    type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
-    let config = MyConfig::new(pcs);
+// `challenger` has to be in the same state the prover's challenger was in, and `public_values` the ones the proof
+// was made with.
+pub fn verify_with_challenger(
+    value: u64,
+    public_values: &[Val],
+    proof: &Proof<MyConfig>,
+    params: &FriParams,
+    challenger: &mut Challenger,
+) -> Result<(), Error> {
+    check_value_in_field(FieldKind::Goldilocks, value)?;
+    let config = config(params);
 
     let air = GoldilocksRangeCheckAir { value };
-    let trace = generate_trace::<Val>(value);
 
-    let mut challenger = Challenger::from_hasher(vec![], byte_hash);
-    let proof = prove(&config, &air, &mut challenger, trace, &vec![]);
+    p3_uni_stark::verify(&config, &air, challenger, proof, public_values)
+        .map_err(|e| Error::verification_failed(FieldKind::Goldilocks, value, e))
+}
+
+// Largest supported scale of a fixed-point number, `x` is represented as `round(x * 2^scale)`.
+pub const MAX_FIXED_POINT_SCALE: u32 = 32;
 
-    let mut challenger = Challenger::from_hasher(vec![], byte_hash);
-    let _ = verify(&config, &air, &mut challenger, &proof, &vec![]).expect("verification failed");
+// Range check proof for the integer representation of a fixed-point number.
+// The scale is observed by the challenger before proving, so the proof is bound to it.
+pub struct FixedPointProof {
+    pub scale: u32,
+    pub proof: Proof<MyConfig>,
+}
+
+impl fmt::Display for FixedPointProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Goldilocks fixed-point range check proof, scale 2^{}", self.scale)
+    }
+}
+
+fn fixed_point_challenger(scale: u32) -> Challenger {
+    let mut challenger = challenger();
+    challenger.observe(Val::from_canonical_u32(scale));
+    challenger
+}
+
+fn check_scale(scale: u32) -> Result<(), Error> {
+    if scale > MAX_FIXED_POINT_SCALE {
+        return Err(Error::InvalidScale { scale, max: MAX_FIXED_POINT_SCALE });
+    }
+    Ok(())
+}
+
+pub fn prove_fixed_point(value_scaled: u64, scale: u32) -> Result<FixedPointProof, Error> {
+    check_scale(scale)?;
+    let mut challenger = fixed_point_challenger(scale);
+    let proof = prove_with_challenger(value_scaled, &public_values(value_scaled), &DEFAULT_FRI_PARAMS, &mut challenger);
+    Ok(FixedPointProof { scale, proof })
+}
+
+pub fn verify_fixed_point(value_scaled: u64, proof: &FixedPointProof) -> Result<(), Error> {
+    check_scale(proof.scale)?;
+    let mut challenger = fixed_point_challenger(proof.scale);
+    let public_values = public_values(value_scaled);
+    verify_with_challenger(value_scaled, &public_values, &proof.proof, &DEFAULT_FRI_PARAMS, &mut challenger)
+}
+
+// Returns the proof of work stats of the proof, which are also logged, or why the proof didn't verify.
+pub fn prove_and_verify(value: u64) -> Result<ProofStats, Error> {
+    prove_and_verify_with_params(value, &DEFAULT_FRI_PARAMS)
+}
+
+pub fn prove_and_verify_with_params(value: u64, params: &FriParams) -> Result<ProofStats, Error> {
+    prover::prove_and_verify_with_params::<Val>(value, params)
+}
+
+pub fn prove_and_verify_with_stats(value: u64) -> Result<ProveStats, Error> {
+    prover::prove_and_verify_with_stats::<Val>(value)
+}
+
+/// Whether `value` is in range, see `prover::check`.
+pub fn check(value: u64) -> bool {
+    prover::check::<Val>(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn width_matches_the_const() {
+        let air = GoldilocksRangeCheckAir { value: 0 };
+        assert_eq!(<GoldilocksRangeCheckAir as BaseAir<Val>>::width(&air), GoldilocksRangeCheckAir::WIDTH);
+    }
+
+    #[test]
+    fn cancelled_proofs_return_an_error() {
+        assert!(matches!(prove_with_cancel(100, &AtomicBool::new(true)), Err(Error::Cancelled)));
+
+        let proof = prove_with_cancel(100, &AtomicBool::new(false)).unwrap();
+        assert!(verify(100, &proof).is_ok());
+    }
+
+    #[test]
+    fn shared_dft_proofs_verify() {
+        let dft = Dft::default();
+        for value in [0, 100, u64::MAX - (1 << 32)] {
+            let proof = prove_with_dft(value, &DEFAULT_FRI_PARAMS, &dft);
+            assert!(verify(value, &proof).is_ok());
+        }
+    }
+
+    #[test]
+    fn single_threaded_dft_proofs_match_parallel_ones() {
+        let parallel = Dft::Parallel(Radix2DitParallel::default());
+        let single_threaded = Dft::SingleThreaded(Radix2Dit::default());
+        for value in [0, 100, u64::MAX - (1 << 32)] {
+            let proof = prove_with_dft(value, &DEFAULT_FRI_PARAMS, &single_threaded);
+            assert!(verify(value, &proof).is_ok());
+            let expected = bincode::serialize(&prove_with_dft(value, &DEFAULT_FRI_PARAMS, &parallel)).unwrap();
+            assert_eq!(bincode::serialize(&proof).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn shared_dft_proofs_match_fresh_ones() {
+        // Sharing the DFT only reuses its cached twiddles, the proofs come out byte for byte the same
+        let dft = Dft::default();
+        for value in [0, 100, u64::MAX - (1 << 32)] {
+            let fresh = bincode::serialize(&prove_with_params(value, &DEFAULT_FRI_PARAMS)).unwrap();
+            let shared = bincode::serialize(&prove_with_dft(value, &DEFAULT_FRI_PARAMS, &dft)).unwrap();
+            assert_eq!(fresh, shared);
+        }
+    }
+
+    // Run with `cargo test --release shared_dft_saves_time -- --ignored --nocapture` to compare the timings.
+    #[test]
+    #[ignore]
+    fn shared_dft_saves_time() {
+        let start = std::time::Instant::now();
+        for value in 0..100 {
+            prove_with_params(value, &DEFAULT_FRI_PARAMS);
+        }
+        let fresh = start.elapsed();
+
+        let dft = Dft::default();
+        let start = std::time::Instant::now();
+        for value in 0..100 {
+            prove_with_dft(value, &DEFAULT_FRI_PARAMS, &dft);
+        }
+        println!("100 proofs: {:?} with a fresh DFT each, {:?} with a shared one", fresh, start.elapsed());
+    }
+
+    #[test]
+    fn proof_is_bound_to_the_public_value() {
+        let proof = prove(100);
+        let params = &DEFAULT_FRI_PARAMS;
+        assert!(verify_with_challenger(100, &public_values(100), &proof, params, &mut challenger()).is_ok());
+        assert!(verify_with_challenger(100, &public_values(101), &proof, params, &mut challenger()).is_err());
+    }
 }
@@ -0,0 +1,206 @@
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_field::{AbstractField, Field, PrimeField64};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::config::FriParams;
+use crate::error::Error;
+use crate::field::{check_value_in_field, FieldKind};
+use crate::goldilocks_v1::{challenger, config, MyConfig, Val};
+use crate::modulus::GOLDILOCKS;
+use crate::proof_info::{ProofStats, ProveStats};
+use crate::timings;
+
+pub use crate::goldilocks_v1::DEFAULT_FRI_PARAMS;
+
+// Index of the first bit after the 32 top one bits of the modulus.
+const TAIL: usize = GOLDILOCKS.tail_start();
+// Number of AND helpers, the running products of the top one bits from the second one on.
+pub const AND_HELPERS: usize = GOLDILOCKS.top_ones - 1;
+
+// Goldilocks Modulus in big endian format, see `modulus::GOLDILOCKS`
+// 11111111 11111111 11111111 11111111 00000000 00000000 00000000 00000001
+// Like `babybear_v2`, the running AND of the top one bits is an input of the AIR instead of committed columns:
+// `and_top_bits[i]` is the product of the bits 0 to `i + 1`, so the last one is 1 iff all 32 top bits are.
+pub struct GoldilocksRangeCheckBitDecompositionAir<T> {
+    // The original value to check.
+    pub value: u64,
+
+    pub and_top_bits: [T; AND_HELPERS],
+}
+
+impl<F: Field> GoldilocksRangeCheckBitDecompositionAir<F> {
+    pub const WIDTH: usize = 64;
+
+    // The AIR for `value` with the AND helpers derived from its bits, so they always match `generate_trace_and_inputs`.
+    pub fn new(value: u64) -> Self {
+        Self { value, and_top_bits: and_inputs(value) }
+    }
+}
+
+impl<F: Field> BaseAir<F> for GoldilocksRangeCheckBitDecompositionAir<F> {
+    fn width(&self) -> usize {
+        Self::WIDTH
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for GoldilocksRangeCheckBitDecompositionAir<AB::F>
+where
+    AB::F: Field,
+{
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let current_row = main.row_slice(0);
+
+        // Every helper is the previous one times the next top bit
+        let mut previous: AB::Expr = current_row[0].into();
+        for (i, &helper) in self.and_top_bits.iter().enumerate() {
+            builder.assert_eq(AB::Expr::from(helper), previous * current_row[i + 1]);
+            previous = AB::Expr::from(helper);
+        }
+
+        // If all top bits are one, the remaining bits have to be zero
+        let remaining_bits_sum = current_row[TAIL..].iter().map(|&bit| bit.into()).sum::<AB::Expr>();
+        builder.when(AB::Expr::from(self.and_top_bits[AND_HELPERS - 1])).assert_zero(remaining_bits_sum);
+
+        let mut reconstructed_value = AB::Expr::zero();
+        for (i, &bit) in current_row.iter().enumerate() {
+            builder.assert_bool(bit); // Making sure every bit is either 0 or 1
+            reconstructed_value += AB::Expr::from_wrapped_u64(1 << (63 - i)) * bit;
+        }
+
+        // Assert if the reconstructed value matches the original value
+        builder.when_first_row().assert_eq(AB::Expr::from_wrapped_u64(self.value), reconstructed_value);
+    }
+}
+
+pub fn generate_trace_and_inputs<F: Field>(value: u64) -> (RowMajorMatrix<F>, [F; AND_HELPERS]) {
+    // Convert the value to binary, in big endian format
+    let bits = (0..64).rev().map(|i| F::from_bool((value >> i) & 1 == 1)).collect();
+    (RowMajorMatrix::new(bits, 64), and_inputs(value))
+}
+
+// Like `generate_trace_and_inputs`, but rejects values at or above the field order instead of building bits
+// that can't verify.
+pub fn try_generate_trace_and_inputs<F: PrimeField64>(
+    value: u64,
+) -> Result<(RowMajorMatrix<F>, [F; AND_HELPERS]), Error> {
+    if value >= F::ORDER_U64 {
+        return Err(Error::ValueOutOfField { field: FieldKind::Goldilocks, value });
+    }
+    Ok(generate_trace_and_inputs(value))
+}
+
+// The AND helpers only depend on the value, so the verifier can derive them without building the trace.
+fn and_inputs<F: Field>(value: u64) -> [F; AND_HELPERS] {
+    let bit = |i: usize| F::from_bool((value >> (63 - i)) & 1 == 1);
+    let mut helpers = [F::zero(); AND_HELPERS];
+    let mut previous = bit(0);
+    for (i, helper) in helpers.iter_mut().enumerate() {
+        *helper = previous * bit(i + 1);
+        previous = *helper;
+    }
+    helpers
+}
+
+pub fn prove(value: u64) -> Proof<MyConfig> {
+    prove_with_params(value, &DEFAULT_FRI_PARAMS)
+}
+
+pub fn prove_with_params(value: u64, params: &FriParams) -> Proof<MyConfig> {
+    let (trace, and_top_bits) = info_span!("generate_trace").in_scope(|| generate_trace_and_inputs::<Val>(value));
+    let air = GoldilocksRangeCheckBitDecompositionAir { value, and_top_bits };
+    let config = config(params);
+
+    info_span!("commit_trace").in_scope(|| p3_uni_stark::prove(&config, &air, &mut challenger(), trace, &vec![]))
+}
+
+pub fn verify(value: u64, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    verify_with_params(value, proof, &DEFAULT_FRI_PARAMS)
+}
+
+pub fn verify_with_params(
+    value: u64,
+    proof: &Proof<MyConfig>,
+    params: &FriParams,
+) -> Result<(), Error> {
+    check_value_in_field(FieldKind::Goldilocks, value)?;
+    let air = GoldilocksRangeCheckBitDecompositionAir::<Val>::new(value);
+    let config = config(params);
+
+    p3_uni_stark::verify(&config, &air, &mut challenger(), proof, &vec![])
+        .map_err(|e| Error::verification_failed(FieldKind::Goldilocks, value, e))
+}
+
+// Returns the proof of work stats of the proof, which are also logged, or why the proof didn't verify.
+pub fn prove_and_verify(value: u64) -> Result<ProofStats, Error> {
+    prove_and_verify_with_params(value, &DEFAULT_FRI_PARAMS)
+}
+
+pub fn prove_and_verify_with_params(value: u64, params: &FriParams) -> Result<ProofStats, Error> {
+    timings::init_tracing();
+
+    let proof = info_span!("prove").in_scope(|| prove_with_params(value, params));
+    info_span!("verify")
+        .in_scope(|| verify_with_params(value, &proof, params))?;
+
+    let stats = ProofStats::from_proof(&proof, params)?;
+    tracing::info!("{}", stats);
+    Ok(stats)
+}
+
+/// Returns the proof size and the proving and verifying times, or why the proof didn't verify.
+pub fn prove_and_verify_with_stats(value: u64) -> Result<ProveStats, Error> {
+    ProveStats::measure(
+        || Ok(prove_with_params(value, &DEFAULT_FRI_PARAMS)),
+        |proof| verify_with_params(value, proof, &DEFAULT_FRI_PARAMS),
+    )
+}
+
+/// Whether `value` is in range with this module's AIR, like `prover::check` for the `_v1` provers.
+pub fn check(value: u64) -> bool {
+    check_value_in_field(FieldKind::Goldilocks, value).is_ok()
+        && verify_with_params(value, &prove_with_params(value, &DEFAULT_FRI_PARAMS), &DEFAULT_FRI_PARAMS).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::debug::{check_constraints, satisfies};
+
+    fn violations(value: u64) -> usize {
+        let (trace, and_top_bits) = generate_trace_and_inputs::<Val>(value);
+        let air = GoldilocksRangeCheckBitDecompositionAir { value, and_top_bits };
+        check_constraints(&air, &trace, &[]).len()
+    }
+
+    #[test]
+    fn boundary_follows_the_goldilocks_order() {
+        let max = Val::ORDER_U64 - 1;
+        assert_eq!(violations(0), 0);
+        assert_eq!(violations(0xFFFF_FFFE_FFFF_FFFF), 0);
+        assert_eq!(violations(max), 0);
+        assert!(violations(max + 1) > 0);
+        assert!(violations(u64::MAX) > 0);
+    }
+
+    #[test]
+    fn helpers_have_to_match_the_bits() {
+        // Claiming the top bits aren't all one lets the tail through, but breaks the AND chain
+        let value = Val::ORDER_U64;
+        let (trace, _) = generate_trace_and_inputs::<Val>(value);
+        let air = GoldilocksRangeCheckBitDecompositionAir { value, and_top_bits: [Val::zero(); AND_HELPERS] };
+        assert!(!satisfies(&air, &trace, &[]));
+    }
+
+    #[test]
+    fn proof_verifies_for_its_value() {
+        let proof = prove(Val::ORDER_U64 - 1);
+        verify(Val::ORDER_U64 - 1, &proof).unwrap();
+        assert!(verify(Val::ORDER_U64 - 2, &proof).is_err());
+        assert!(matches!(try_generate_trace_and_inputs::<Val>(u64::MAX), Err(Error::ValueOutOfField { .. })));
+    }
+}
@@ -0,0 +1,58 @@
+use crate::batch::{self, BatchProof};
+use crate::error::Error;
+
+// Flattens the grid row by row, rejecting ragged grids, and returns the values with the grid's shape.
+fn flatten(values: &[Vec<u32>]) -> Result<(Vec<u32>, [usize; 2]), Error> {
+    let columns = values.first().map_or(0, Vec::len);
+    if columns == 0 {
+        return Err(Error::InvalidInput("no values to range check".to_string()));
+    }
+    if let Some(row) = values.iter().position(|row| row.len() != columns) {
+        return Err(Error::InvalidInput(format!("row {} doesn't have {} columns", row, columns)));
+    }
+    Ok((values.concat(), [values.len(), columns]))
+}
+
+/// Proves that every value of the rectangular grid `values` is in range, flattened row by row into a `batch` proof.
+/// The proof is bound to the shape of the grid, it doesn't verify for the same values in other rows.
+pub fn prove_grid(values: &[Vec<u32>]) -> Result<BatchProof, Error> {
+    let (values, shape) = flatten(values)?;
+    batch::prove_shaped(&values, shape)
+}
+
+pub fn verify_grid(values: &[Vec<u32>], proof: &BatchProof) -> Result<(), Error> {
+    let (values, shape) = flatten(values)?;
+    batch::verify_shaped(&values, shape, proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::PrimeField32;
+
+    use super::*;
+
+    #[test]
+    fn grid_proves_and_verifies() {
+        let grid = vec![vec![1, 2, 3], vec![4, 5, 6]];
+        let proof = prove_grid(&grid).unwrap();
+        verify_grid(&grid, &proof).unwrap();
+        assert!(verify_grid(&[vec![1, 2, 3], vec![4, 5, 7]], &proof).is_err());
+    }
+
+    #[test]
+    fn reshaped_grids_are_rejected() {
+        let proof = prove_grid(&[vec![1, 2, 3], vec![4, 5, 6]]).unwrap();
+        assert!(verify_grid(&[vec![1, 2], vec![3, 4], vec![5, 6]], &proof).is_err());
+        assert!(verify_grid(&[vec![1, 2, 3, 4, 5, 6]], &proof).is_err());
+        assert!(batch::verify(&[1, 2, 3, 4, 5, 6], &proof).is_err());
+    }
+
+    #[test]
+    fn ragged_and_out_of_field_grids_are_rejected() {
+        assert!(matches!(prove_grid(&[vec![1, 2], vec![3]]), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_grid(&[]), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_grid(&[vec![]]), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_grid(&[vec![BabyBear::ORDER_U32]]), Err(Error::ValueOutOfField { .. })));
+    }
+}
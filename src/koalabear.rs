@@ -0,0 +1,282 @@
+use p3_air::{Air, AirBuilderWithPublicValues, BaseAir};
+use p3_field::{AbstractField, Field, PrimeField32};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+
+use p3_challenger::{HashChallenger, SerializingChallenger32};
+use p3_commit::ExtensionMmcs;
+use p3_field::extension::BinomialExtensionField;
+use p3_fri::TwoAdicFriPcs;
+use p3_keccak::Keccak256Hash;
+use p3_koala_bear::KoalaBear;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_monty_31::dft::RecursiveDft;
+use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher32};
+use p3_uni_stark::{Proof, StarkConfig};
+use tracing::info_span;
+
+use crate::config::{FriParams, MERKLE_ARITY};
+use crate::error::Error;
+use crate::field::{check_value_in_field, FieldKind};
+use crate::modulus::{and_chain, eval_modulus_boundary_degree_2, KOALABEAR};
+use crate::proof_info::{ProofStats, ProveStats};
+use crate::prover;
+
+pub struct KoalaBearRangeCheckAir {
+    pub value: u32, // define constraint input, value is assigned to check against the reconstructed value.
+}
+
+// Number of committed AND columns after the 32 bits, the running AND of the 7 top one bits.
+const AND_COLUMNS: usize = KOALABEAR.top_ones - 1;
+pub(crate) const WIDTH: usize = 32 + AND_COLUMNS;
+
+impl KoalaBearRangeCheckAir {
+    pub const WIDTH: usize = WIDTH;
+}
+
+// Koala Bear Modulus in big endian format, see `modulus::KOALABEAR`
+// 01111111 00000000 00000000 00000001
+impl<F: Field> BaseAir<F> for KoalaBearRangeCheckAir {
+    fn width(&self) -> usize {
+        Self::WIDTH
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for KoalaBearRangeCheckAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let current_row = main.row_slice(0);
+
+        // The public value is the value, see `public_values`
+        let value: AB::Expr = builder.public_values()[0].into();
+        builder.assert_eq(value.clone(), AB::Expr::from_wrapped_u32(self.value));
+
+        // Assert that the most significant bit is zero, and if the 1st to 7th bits are all one then the
+        // remaining bits are zero. The AND columns keep every constraint at degree 2.
+        eval_modulus_boundary_degree_2(builder, &current_row[..32], &current_row[32..WIDTH], &KOALABEAR);
+
+        // initializing the `reconstructed_value`
+        let mut reconstructed_value = AB::Expr::zero();
+        for i in 0..32 {
+            let bit = current_row[i];
+            // Making sure every bit is either 0 or 1
+            builder.assert_bool(bit);
+            reconstructed_value += AB::Expr::from_wrapped_u32(1 << (31 - i)) * bit;
+        }
+
+        // Assert if the reconstructed value matches the original value
+        builder.when_first_row().assert_eq(value, reconstructed_value);
+    }
+}
+
+// A single row, the 32 bits of `value` in big endian format followed by the AND columns.
+pub fn generate_trace<F: Field>(value: u32) -> RowMajorMatrix<F> {
+    let mut bits = Vec::with_capacity(WIDTH);
+    for i in (0..32).rev() {
+        if (value & (1 << i)) != 0 {
+            bits.push(F::one());
+        } else {
+            bits.push(F::zero());
+        }
+    }
+    let ands = and_chain(&bits, &KOALABEAR);
+    bits.extend(ands);
+    RowMajorMatrix::new(bits, WIDTH)
+}
+
+// Like `generate_trace`, but rejects values at or above the field order instead of building bits that can't verify.
+pub fn try_generate_trace<F: PrimeField32>(value: u32) -> Result<RowMajorMatrix<F>, Error> {
+    if value >= F::ORDER_U32 {
+        return Err(Error::ValueOutOfField { field: FieldKind::KoalaBear, value: u64::from(value) });
+    }
+    Ok(generate_trace(value))
+}
+
+pub const DEFAULT_FRI_PARAMS: FriParams = FriParams {
+    log_blowup: 1,
+    num_queries: 100,
+    proof_of_work_bits: 16,
+};
+
+// The same 100 bit parameters as `babybear_v1::MINIMAL_FRI_PARAMS`, the AIRs have the same degree.
+pub const MINIMAL_FRI_PARAMS: FriParams = FriParams {
+    log_blowup: 4,
+    num_queries: 21,
+    proof_of_work_bits: 16,
+};
+
+pub type Val = KoalaBear;
+pub type Challenge = BinomialExtensionField<Val, 4>;
+
+pub type ByteHash = Keccak256Hash;
+pub type FieldHash = SerializingHasher32<ByteHash>;
+pub type MyCompress = CompressionFunctionFromHasher<ByteHash, MERKLE_ARITY, 32>;
+pub type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+pub type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+pub type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+pub type Dft = RecursiveDft<Val>;
+pub type Pcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+pub type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
+
+// Nameable outside of this module, e.g. to store proofs.
+pub type KoalaBearConfig = MyConfig;
+pub type KoalaBearProof = Proof<MyConfig>;
+
+pub fn config(trace_height: usize, params: &FriParams) -> MyConfig {
+    let byte_hash = ByteHash {};
+    let field_hash = FieldHash::new(Keccak256Hash {});
+    let compress = MyCompress::new(byte_hash);
+    let val_mmcs = ValMmcs::new(field_hash, compress);
+    let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+
+    let fri_config = params.fri_config(challenge_mmcs);
+    let dft = Dft::new(trace_height << fri_config.log_blowup);
+
+    let pcs = Pcs::new(dft, val_mmcs, fri_config);
+    MyConfig::new(pcs)
+}
+
+// A fresh challenger with an empty transcript.
+pub fn challenger() -> Challenger {
+    Challenger::from_hasher(vec![], ByteHash {})
+}
+
+pub fn prove(value: u32) -> Proof<MyConfig> {
+    prove_with_params(value, &DEFAULT_FRI_PARAMS)
+}
+
+pub fn prove_with_params(value: u32, params: &FriParams) -> Proof<MyConfig> {
+    prove_with_challenger(value, &public_values(value), params, &mut challenger())
+}
+
+// The public values of the range check of `value`, the value itself reduced into the field like the AIR does.
+pub fn public_values(value: u32) -> Vec<Val> {
+    vec![Val::from_wrapped_u32(value)]
+}
+
+// Continues the transcript of `challenger`, binding the proof to everything observed before.
+pub fn prove_with_challenger(
+    value: u32,
+    public_values: &[Val],
+    params: &FriParams,
+    challenger: &mut Challenger,
+) -> Proof<MyConfig> {
+    prove_with_trace_hook(value, public_values, params, challenger, None)
+}
+
+// `trace_hook` is called with the generated trace right before it is committed to.
+pub fn prove_with_trace_hook(
+    value: u32,
+    public_values: &[Val],
+    params: &FriParams,
+    challenger: &mut Challenger,
+    trace_hook: Option<&mut dyn FnMut(&RowMajorMatrix<Val>)>,
+) -> Proof<MyConfig> {
+    let air = KoalaBearRangeCheckAir { value };
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<Val>(value));
+    let config = config(trace.height(), params);
+
+    if let Some(trace_hook) = trace_hook {
+        trace_hook(&trace);
+    }
+
+    info_span!("commit_trace").in_scope(|| p3_uni_stark::prove(&config, &air, challenger, trace, public_values))
+}
+
+pub fn verify(value: u32, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    verify_with_params(value, proof, &DEFAULT_FRI_PARAMS)
+}
+
+pub fn verify_with_params(
+    value: u32,
+    proof: &Proof<MyConfig>,
+    params: &FriParams,
+) -> Result<(), Error> {
+    verify_with_challenger(value, &public_values(value), proof, params, &mut challenger())
+}
+
+// `challenger` has to be in the same state the prover's challenger was in, and `public_values` the ones the proof
+// was made with.
+pub fn verify_with_challenger(
+    value: u32,
+    public_values: &[Val],
+    proof: &Proof<MyConfig>,
+    params: &FriParams,
+    challenger: &mut Challenger,
+) -> Result<(), Error> {
+    check_value_in_field(FieldKind::KoalaBear, u64::from(value))?;
+    let air = KoalaBearRangeCheckAir { value };
+    let config = config(1, params);
+
+    p3_uni_stark::verify(&config, &air, challenger, proof, public_values)
+        .map_err(|e| Error::verification_failed(FieldKind::KoalaBear, u64::from(value), e))
+}
+
+// Returns the proof of work stats of the proof, which are also logged, or why the proof didn't verify.
+pub fn prove_and_verify(value: u32) -> Result<ProofStats, Error> {
+    prove_and_verify_with_params(value, &DEFAULT_FRI_PARAMS)
+}
+
+pub fn prove_and_verify_with_params(value: u32, params: &FriParams) -> Result<ProofStats, Error> {
+    prover::prove_and_verify_with_params::<Val>(u64::from(value), params)
+}
+
+pub fn prove_and_verify_with_stats(value: u32) -> Result<ProveStats, Error> {
+    prover::prove_and_verify_with_stats::<Val>(u64::from(value))
+}
+
+/// Whether `value` is in range, see `prover::check`.
+pub fn check(value: u64) -> bool {
+    prover::check::<Val>(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::debug::check_constraints;
+
+    fn violations(value: u32) -> usize {
+        check_constraints(&KoalaBearRangeCheckAir { value }, &generate_trace::<Val>(value), &public_values(value)).len()
+    }
+
+    #[test]
+    fn width_matches_the_const() {
+        let air = KoalaBearRangeCheckAir { value: 0 };
+        assert_eq!(<KoalaBearRangeCheckAir as BaseAir<Val>>::width(&air), KoalaBearRangeCheckAir::WIDTH);
+    }
+
+    #[test]
+    fn boundary_follows_the_koalabear_order() {
+        let max = Val::ORDER_U32 - 1;
+        assert_eq!(violations(0), 0);
+        assert_eq!(violations(max), 0);
+        assert_ne!(violations(max + 1), 0);
+        // In range for KoalaBear, but not below the BabyBear modulus
+        assert_eq!(violations((1 << 31) - (1 << 27) + 1), 0);
+    }
+
+    #[test]
+    fn proves_and_verifies() {
+        let value = Val::ORDER_U32 - 1;
+        let proof = prove(value);
+        assert!(verify(value, &proof).is_ok());
+        assert!(verify(value - 1, &proof).is_err());
+
+        let params = &DEFAULT_FRI_PARAMS;
+        assert!(verify_with_challenger(value, &public_values(value - 1), &proof, params, &mut challenger()).is_err());
+    }
+
+    #[test]
+    fn trace_hook_sees_the_committed_trace() {
+        let mut shapes = vec![];
+        let proof = prove_with_trace_hook(
+            100,
+            &public_values(100),
+            &DEFAULT_FRI_PARAMS,
+            &mut challenger(),
+            Some(&mut |trace: &RowMajorMatrix<Val>| shapes.push((trace.width(), trace.height()))),
+        );
+        assert_eq!(shapes, vec![(WIDTH, 1)]);
+        assert!(verify(100, &proof).is_ok());
+    }
+}
@@ -1,4 +1,4 @@
-use p3_air::{Air, AirBuilder, BaseAir};
+use p3_air::{Air, AirBuilderWithPublicValues, BaseAir};
 use p3_field::{AbstractField, Field};
 use p3_matrix::Matrix;
 use p3_matrix::dense::RowMajorMatrix;
@@ -8,57 +8,79 @@ use p3_challenger::{HashChallenger, SerializingChallenger32};
 use p3_circle::CirclePcs;
 use p3_commit::ExtensionMmcs;
 use p3_field::extension::BinomialExtensionField;
-use p3_fri::FriConfig;
 use p3_keccak::Keccak256Hash;
 use p3_merkle_tree::MerkleTreeMmcs;
 use p3_mersenne_31::Mersenne31;
 use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher32};
-use p3_uni_stark::{prove, verify, StarkConfig};
-use tracing_forest::util::LevelFilter;
-use tracing_forest::ForestLayer;
-use tracing_subscriber::layer::SubscriberExt;
-use tracing_subscriber::util::SubscriberInitExt;
-use tracing_subscriber::{EnvFilter, Registry};
+use p3_uni_stark::{Proof, StarkConfig};
+use tracing::info_span;
+
+use crate::config::{FriParams, MERKLE_ARITY};
+use crate::error::Error;
+use crate::field::{check_value_in_field, FieldKind};
+use crate::modulus::{and_chain, eval_modulus_boundary_degree_2, MERSENNE31};
+use crate::proof_info::{ProofStats, ProveStats};
+use crate::prover;
 
 pub struct Mersenne31RangeCheckAir {
     pub value: u32,
 }
 
-// Mersenne31 Modulus in big endian format
+// Number of committed AND columns after the 32 bits, the running AND of the 30 top one bits.
+const AND_COLUMNS: usize = MERSENNE31.top_ones - 1;
+
+impl Mersenne31RangeCheckAir {
+    pub const WIDTH: usize = 32 + AND_COLUMNS; // 1 number per row
+}
+
+// Mersenne31 Modulus in big endian format, see `modulus::MERSENNE31`
 // 01111111 11111111 11111111 11111111
 // 2^31 - 1
+// The bits of `2^31 - 1` itself reconstruct to 0, so besides the zero most significant bit the boundary check
+// rejects bits 1 to 31 being all one.
 impl<F: Field> BaseAir<F> for Mersenne31RangeCheckAir {
     fn width(&self) -> usize {
-        32 // 1 number per row
+        Self::WIDTH
     }
 }
 
-impl<AB: AirBuilder> Air<AB> for Mersenne31RangeCheckAir {
+impl<AB: AirBuilderWithPublicValues> Air<AB> for Mersenne31RangeCheckAir {
     fn eval(&self, builder: &mut AB) {
         let main = builder.main();
         let current_row = main.row_slice(0);
-        let next_row = main.row_slice(1);
 
-        // Assert that the most significant bit is zero
-        builder.when_first_row().assert_eq(current_row[0], AB::Expr::zero());
+        // The public value is the value, see `public_values`
+        let value: AB::Expr = builder.public_values()[0].into();
+        builder.assert_eq(value.clone(), AB::Expr::from_wrapped_u32(self.value));
+
+        // Assert that the most significant bit is zero, and if the 1st to 30th bits are all one then the last bit
+        // is zero. Checked on every row, the padding rows are all zero and pass.
+        eval_modulus_boundary_degree_2(builder, &current_row[..32], &current_row[32..], &MERSENNE31);
 
         let mut reconstructed_value = AB::Expr::zero();
-        let mut next_row_rowsum = AB::Expr::zero();
+        let mut rowsum = AB::Expr::zero();
         for i in 0..32 {
             let bit = current_row[i];
             builder.assert_bool(bit); // Making sure every bit is either 0 or 1
             reconstructed_value += AB::Expr::from_wrapped_u32(1 << (31-i)) * bit; // using `from_wrapped_u32` to make sure the value is in range of 31 bits.
-            next_row_rowsum += next_row[i].into();
+            rowsum += bit.into();
         }
 
         // Assert if the reconstructed value matches the original value
-        builder.when_first_row().assert_eq(AB::Expr::from_wrapped_u32(self.value), reconstructed_value);
-        builder.when_transition().assert_eq(next_row_rowsum, AB::Expr::zero());
+        builder.when_first_row().assert_eq(value, reconstructed_value);
+
+        // Assert the padding rows, every row but the first, are all zero. Since the bits are boolean a zero sum
+        // means every bit is zero. This only reads the current row, so nothing depends on how the last row
+        // wraps around to the first one.
+        let is_padding_row = AB::Expr::one() - builder.is_first_row();
+        builder.when(is_padding_row).assert_zero(rowsum);
     }
 }
 
+// The 32 bits of `value` in big endian format followed by the AND columns, and 3 all zero padding rows.
 pub fn generate_mersenne31_trace<F: Field>(value: u32) -> RowMajorMatrix<F> {
-    let mut bits = Vec::with_capacity(32 * 4); // 32 bits per row, 4 rows, CirclePCS requires 4 rows
+    let width = Mersenne31RangeCheckAir::WIDTH;
+    let mut bits = Vec::with_capacity(width * 4); // 4 rows, CirclePCS requires 4 rows
     // Convert the value to binary, in big endian format
     for i in (0..32).rev() {
         if (value & (1 << i)) != 0 {
@@ -67,64 +89,228 @@ pub fn generate_mersenne31_trace<F: Field>(value: u32) -> RowMajorMatrix<F> {
             bits.push(F::zero());
         }
     }
-    for _ in 0..32*3 {
-        bits.push(F::zero());
-    }
-    RowMajorMatrix::new(bits, 32)
+    let ands = and_chain(&bits, &MERSENNE31);
+    bits.extend(ands);
+    bits.resize(width * 4, F::zero());
+    RowMajorMatrix::new(bits, width)
 }
 
-pub fn prove_and_verify<F: Field>(value: u32) {
-    let env_filter = EnvFilter::builder()
-        .with_default_directive(LevelFilter::INFO.into())
-        .from_env_lossy();
+pub const DEFAULT_FRI_PARAMS: FriParams = FriParams {
+    log_blowup: 1,
+    num_queries: 100,
+    proof_of_work_bits: 16,
+};
+
+// Smallest proofs at 100 conjectured bits of security, `4 * 21 + 16`, see `config::ProofSizePreset`.
+pub const MINIMAL_FRI_PARAMS: FriParams = FriParams {
+    log_blowup: 4,
+    num_queries: 21,
+    proof_of_work_bits: 16,
+};
 
-    Registry::default()
-        .with(env_filter)
-        .with(ForestLayer::default())
-        .init();
+pub type Val = Mersenne31;
+pub type Challenge = BinomialExtensionField<Val, 3>;
 
-    type Val = Mersenne31;
-    type Challenge = BinomialExtensionField<Val, 3>;
+pub type ByteHash = Keccak256Hash;
+pub type FieldHash = SerializingHasher32<ByteHash>;
+pub type MyCompress = CompressionFunctionFromHasher<ByteHash, MERKLE_ARITY, 32>;
+pub type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+pub type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+pub type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+pub type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
+pub type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
 
-    type ByteHash = Keccak256Hash;
-    type FieldHash = SerializingHasher32<ByteHash>;
+// Nameable outside of this module, e.g. to store proofs.
+pub type Mersenne31Config = MyConfig;
+pub type Mersenne31Proof = Proof<MyConfig>;
+
+pub fn config(params: &FriParams) -> MyConfig {
     let byte_hash = ByteHash {};
     let field_hash = FieldHash::new(Keccak256Hash {});
-
-    type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
     let compress = MyCompress::new(byte_hash);
-
-    type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
     let val_mmcs = ValMmcs::new(field_hash, compress);
-
-    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
     let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
 
-    type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+    let fri_config = params.fri_config(challenge_mmcs);
 
-    let fri_config = FriConfig {
-        log_blowup: 1,
-        num_queries: 100,
-        proof_of_work_bits: 16,
-        mmcs: challenge_mmcs,
-    };
-
-    type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
     let pcs = Pcs {
         mmcs: val_mmcs,
         fri_config,
         _phantom: PhantomData,
     };
+    MyConfig::new(pcs)
+}
+
+// A fresh challenger with an empty transcript.
+pub fn challenger() -> Challenger {
+    Challenger::from_hasher(vec![], ByteHash {})
+}
+
+/// Proves `value < 2^31 - 1` with the default FRI parameters, see `verify` to check the returned proof.
+pub fn prove(value: u32) -> Proof<MyConfig> {
+    prove_with_params(value, &DEFAULT_FRI_PARAMS)
+}
+
+pub fn prove_with_params(value: u32, params: &FriParams) -> Proof<MyConfig> {
+    prove_with_challenger(value, &public_values(value), params, &mut challenger())
+}
+
+// The public values of the range check of `value`, the value itself reduced into the field like the AIR does.
+pub fn public_values(value: u32) -> Vec<Val> {
+    vec![Val::from_wrapped_u32(value)]
+}
+
+// Continues the transcript of `challenger`, binding the proof to everything observed before.
+pub fn prove_with_challenger(
+    value: u32,
+    public_values: &[Val],
+    params: &FriParams,
+    challenger: &mut Challenger,
+) -> Proof<MyConfig> {
+    prove_with_trace_hook(value, public_values, params, challenger, None)
+}
+
+// `trace_hook` is called with the generated trace right before it is committed to.
+pub fn prove_with_trace_hook(
+    value: u32,
+    public_values: &[Val],
+    params: &FriParams,
+    challenger: &mut Challenger,
+    trace_hook: Option<&mut dyn FnMut(&RowMajorMatrix<Val>)>,
+) -> Proof<MyConfig> {
+    let config = config(params);
+
+    let air = Mersenne31RangeCheckAir { value };
+    let trace = info_span!("generate_trace").in_scope(|| generate_mersenne31_trace::<Val>(value));
+
+    if let Some(trace_hook) = trace_hook {
+        trace_hook(&trace);
+    }
+
+    info_span!("commit_trace").in_scope(|| p3_uni_stark::prove(&config, &air, challenger, trace, public_values))
+}
 
-    type MyConfig = StarkConfig<Pcs, Challenge, Challenger>;
-    let config = MyConfig::new(pcs);
+pub fn verify(value: u32, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    verify_with_params(value, proof, &DEFAULT_FRI_PARAMS)
+}
+
+pub fn verify_with_params(
+    value: u32,
+    proof: &Proof<MyConfig>,
+    params: &FriParams,
+) -> Result<(), Error> {
+    verify_with_challenger(value, &public_values(value), proof, params, &mut challenger())
+}
+
+// `challenger` has to be in the same state the prover's challenger was in, and `public_values` the ones the proof
+// was made with.
+pub fn verify_with_challenger(
+    value: u32,
+    public_values: &[Val],
+    proof: &Proof<MyConfig>,
+    params: &FriParams,
+    challenger: &mut Challenger,
+) -> Result<(), Error> {
+    check_value_in_field(FieldKind::Mersenne31, u64::from(value))?;
+    let config = config(params);
 
     let air = Mersenne31RangeCheckAir { value };
-    let trace = generate_mersenne31_trace::<Val>( value);
 
-    let mut challenger = Challenger::from_hasher(vec![], byte_hash);
-    let proof = prove(&config, &air, &mut challenger, trace, &vec![]);
+    p3_uni_stark::verify(&config, &air, challenger, proof, public_values)
+        .map_err(|e| Error::verification_failed(FieldKind::Mersenne31, u64::from(value), e))
+}
+
+// Returns the proof of work stats of the proof, which are also logged, or why the proof didn't verify.
+pub fn prove_and_verify(value: u32) -> Result<ProofStats, Error> {
+    prove_and_verify_with_params(value, &DEFAULT_FRI_PARAMS)
+}
+
+pub fn prove_and_verify_with_params(value: u32, params: &FriParams) -> Result<ProofStats, Error> {
+    prover::prove_and_verify_with_params::<Val>(u64::from(value), params)
+}
+
+pub fn prove_and_verify_with_stats(value: u32) -> Result<ProveStats, Error> {
+    prover::prove_and_verify_with_stats::<Val>(u64::from(value))
+}
+
+/// Whether `value` is in range, see `prover::check`.
+pub fn check(value: u64) -> bool {
+    prover::check::<Val>(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p3_mersenne_31::Mersenne31;
+
+    use crate::debug::{check_constraints, satisfies};
+
+    #[test]
+    fn width_matches_the_const() {
+        let air = Mersenne31RangeCheckAir { value: 0 };
+        assert_eq!(<Mersenne31RangeCheckAir as BaseAir<Mersenne31>>::width(&air), Mersenne31RangeCheckAir::WIDTH);
+    }
+
+    #[test]
+    fn padding_rows_must_be_zero() {
+        let air = Mersenne31RangeCheckAir { value: 100 };
+        assert!(satisfies(&air, &generate_mersenne31_trace::<Mersenne31>(100), &public_values(100)));
+
+        // Every padding row is constrained on its own, including the last one.
+        for row in 1..4 {
+            let mut trace = generate_mersenne31_trace::<Mersenne31>(100);
+            trace.values[row * Mersenne31RangeCheckAir::WIDTH + 31] = Mersenne31::one();
+            let violations = check_constraints(&air, &trace, &public_values(100));
+            assert!(!violations.is_empty());
+            assert!(violations.iter().all(|violation| violation.row == row));
+        }
+    }
+
+    #[test]
+    fn last_row_does_not_wrap_around() {
+        // The value row follows the last padding row cyclically, but it must not be forced to zero.
+        let air = Mersenne31RangeCheckAir { value: (1 << 31) - 2 };
+        let trace = generate_mersenne31_trace::<Mersenne31>((1 << 31) - 2);
+        assert!(satisfies(&air, &trace, &public_values((1 << 31) - 2)));
+    }
+
+    #[test]
+    fn the_modulus_is_rejected() {
+        // The bits of `2^31 - 1` reconstruct to the public value 0, only the boundary check catches them
+        let value = (1 << 31) - 1;
+        let air = Mersenne31RangeCheckAir { value };
+        let trace = generate_mersenne31_trace::<Mersenne31>(value);
+        let violations = check_constraints(&air, &trace, &public_values(value));
+        assert!(!violations.is_empty());
+        assert!(violations.iter().all(|violation| violation.row == 0));
+    }
+
+    // Proving an unsatisfied trace panics in debug builds, so this one only runs in release.
+    #[test]
+    #[cfg(not(debug_assertions))]
+    fn tampered_trace_fails_verification() {
+        // A nonzero padding row, and the bits of 100 with the most significant one set, which reconstruct to
+        // `2^31 + 100 = 101` so only the AIR's bit checks can reject the in-field public value
+        let mut padded = generate_mersenne31_trace::<Mersenne31>(100);
+        padded.values[2 * Mersenne31RangeCheckAir::WIDTH] = Mersenne31::one();
+        let mut top_bit = generate_mersenne31_trace::<Mersenne31>(100);
+        top_bit.values[0] = Mersenne31::one();
+
+        for (value, trace) in [(100, padded), (101, top_bit)] {
+            let air = Mersenne31RangeCheckAir { value };
+            assert!(!satisfies(&air, &trace, &public_values(value)));
+
+            let config = config(&DEFAULT_FRI_PARAMS);
+            let proof = p3_uni_stark::prove(&config, &air, &mut challenger(), trace, &public_values(value));
+            assert!(matches!(verify(value, &proof), Err(Error::VerificationFailed { .. })));
+        }
+    }
 
-    let mut challenger = Challenger::from_hasher(vec![], byte_hash);
-    let _ = verify(&config, &air, &mut challenger, &proof, &vec![]).expect("verification failed");
-}
\ No newline at end of file
+    #[test]
+    fn proof_is_bound_to_the_public_value() {
+        let proof = prove(100);
+        let params = &DEFAULT_FRI_PARAMS;
+        assert!(verify_with_challenger(100, &public_values(100), &proof, params, &mut challenger()).is_ok());
+        assert!(verify_with_challenger(100, &public_values(101), &proof, params, &mut challenger()).is_err());
+    }
+}
@@ -1,20 +1,247 @@
 use std::fmt::Debug;
 
-use clap::{Command, Arg};
+use clap::builder::PossibleValuesParser;
+use clap::{Arg, ArgAction, Command};
 
+use crate::config::{fri_params_from_env, FriParams};
+use crate::error::Error;
+use crate::field::{check_value_in_field, max_provable_value, FieldKind};
+use crate::proof_info::ProofStats;
+
+#[cfg(feature = "mersenne31")]
 pub mod m31;
+#[cfg(feature = "babybear")]
 pub mod babybear_v1;
+#[cfg(feature = "babybear")]
 pub mod babybear_v2;
+#[cfg(feature = "babybear")]
+pub mod babybear_compact;
+#[cfg(feature = "babybear")]
+pub mod babybear_recursion;
+#[cfg(feature = "goldilocks")]
 pub mod goldilocks_v1;
+#[cfg(feature = "goldilocks")]
+pub mod goldilocks_v2;
+#[cfg(feature = "goldilocks")]
+pub mod goldilocks_bounded;
+#[cfg(feature = "goldilocks")]
+pub mod goldilocks_limbs;
+#[cfg(feature = "goldilocks")]
+pub mod goldilocks_residue;
+#[cfg(feature = "goldilocks")]
+pub mod goldilocks_signed;
+#[cfg(feature = "goldilocks")]
+pub mod goldilocks_sum;
+#[cfg(feature = "goldilocks")]
+pub mod goldilocks_u32;
+#[cfg(feature = "koalabear")]
+pub mod koalabear;
+#[cfg(feature = "babybear")]
+pub mod grid;
+#[cfg(feature = "babybear")]
+pub mod membership;
+#[cfg(feature = "babybear")]
+pub mod modular;
+pub mod modulus;
+#[cfg(feature = "babybear")]
+pub mod n_bits;
+#[cfg(feature = "babybear")]
+pub mod non_membership;
+#[cfg(feature = "babybear")]
+pub mod nonzero;
+#[cfg(feature = "babybear")]
+pub mod batch;
+#[cfg(feature = "babybear")]
+pub mod bit_and_field;
+pub mod bits;
+#[cfg(feature = "babybear")]
+pub mod bitrev;
+#[cfg(feature = "babybear")]
+pub mod bounded;
+pub mod builder;
+pub mod bundle;
+#[cfg(feature = "babybear")]
+pub mod compare;
+pub mod config;
+#[cfg(feature = "babybear")]
+pub mod conjunction;
+pub mod debug;
+#[cfg(feature = "babybear")]
+pub mod delta;
+#[cfg(feature = "babybear")]
+pub mod equality;
+pub mod error;
+pub mod field;
+#[cfg(feature = "babybear")]
+pub mod pair_bounded;
+pub mod plan;
+pub mod proof_file;
+pub mod proof_info;
+pub mod prover;
+pub mod report;
+pub mod serialize;
+#[cfg(feature = "babybear")]
+pub mod short_range;
+pub mod stream;
+#[cfg(feature = "babybear")]
+pub mod exclusion;
+#[cfg(feature = "babybear")]
+pub mod exact_bits;
+#[cfg(feature = "babybear")]
+pub mod sorted;
+#[cfg(feature = "babybear")]
+pub mod sum_range;
+pub mod timings;
+#[cfg(feature = "babybear")]
+pub mod top_byte;
+pub mod trace_csv;
+#[cfg(feature = "babybear")]
+pub mod union_range;
+pub mod vectors;
 
-fn main() -> Result<(), Box<dyn Debug>> {
-    use p3_mersenne_31::Mersenne31;
-    use p3_baby_bear::BabyBear;
+// Parses `--value` as hexadecimal with a `0x` or `0X` prefix and as decimal otherwise. `from_str_radix` also takes
+// a leading `+`, so anything but digits is rejected first.
+fn parse_value(input: &str) -> Result<u64, String> {
+    let (digits, radix) = match input.strip_prefix("0x").or_else(|| input.strip_prefix("0X")) {
+        Some(hex) => (hex, 16),
+        None => (input, 10),
+    };
+    if !digits.chars().all(|digit| digit.is_digit(radix)) {
+        return Err(format!("{} is not a decimal or 0x prefixed hexadecimal number", input));
+    }
+    u64::from_str_radix(digits, radix).map_err(|e| match e.kind() {
+        std::num::IntErrorKind::PosOverflow => {
+            format!("{} doesn't fit in 64 bits, the largest value is {}", input, u64::MAX)
+        }
+        _ => format!("{} is not a decimal or 0x prefixed hexadecimal number", input),
+    })
+}
+
+// The exit code tells scripts whether the proof verified, so a failure exits with 1 instead of panicking.
+fn fail(function: &str, value: impl std::fmt::Display, e: Error) -> ! {
+    eprintln!("{} failed for {}: {}", function, value, e);
+    std::process::exit(1);
+}
+
+// The field of `function`, every function name starts with the name of its field.
+fn field_of(function: &str) -> FieldKind {
+    *FieldKind::all()
+        .iter()
+        .find(|field| function.starts_with(field.name()))
+        .unwrap()
+}
+
+// One value per line, decimal or hexadecimal like `--value`. Blank lines are skipped, a value that doesn't parse is
+// reported with its line number, counting from 1.
+fn read_values(text: &str) -> Result<Vec<u64>, String> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| parse_value(line.trim()).map_err(|e| format!("line {}: {}", i + 1, e)))
+        .collect()
+}
+
+/// Proves and verifies `value` with the range check `function`, one of the `--function` choices.
+///
+/// Returns `Error::ValueOutOfField` for values that don't fit the prover's input type.
+#[cfg_attr(not(feature = "goldilocks"), allow(unused_variables))]
+fn prove_value(function: &str, value: u64, as_field: bool) -> Result<ProofStats, Error> {
+    #[cfg(feature = "goldilocks")]
     use p3_goldilocks::Goldilocks;
+    #[cfg(feature = "goldilocks")]
+    use p3_field::{AbstractField, PrimeField64};
+    #[cfg(feature = "mersenne31")]
     use crate::m31 as rc_m31;
+    #[cfg(feature = "babybear")]
     use crate::babybear_v1 as rc_babybear_v1;
+    #[cfg(feature = "babybear")]
     use crate::babybear_v2 as rc_babybear_v2;
+    #[cfg(feature = "babybear")]
+    use crate::babybear_compact as rc_babybear_compact;
+    #[cfg(feature = "goldilocks")]
     use crate::goldilocks_v1 as rc_goldilocks_v1;
+    #[cfg(feature = "goldilocks")]
+    use crate::goldilocks_v2 as rc_goldilocks_v2;
+    #[cfg(feature = "koalabear")]
+    use crate::koalabear as rc_koalabear;
+
+    let field = field_of(function);
+
+    #[allow(unused_variables)]
+    let to_u32 = |value: u64| u32::try_from(value).map_err(|_| Error::ValueOutOfField { field, value });
+
+    // `RANGECHECK_*` environment variables override the FRI parameters of the chosen function.
+    match function {
+        #[cfg(feature = "mersenne31")]
+        "mersenne31" => {
+            let value = to_u32(value)?;
+            let params = fri_params_from_env(rc_m31::DEFAULT_FRI_PARAMS)?;
+            rc_m31::prove_and_verify_with_params(value, &params)
+        }
+        #[cfg(feature = "babybear")]
+        "babybear_v1" => {
+            let value = to_u32(value)?;
+            let params = fri_params_from_env(rc_babybear_v1::DEFAULT_FRI_PARAMS)?;
+            rc_babybear_v1::prove_and_verify_with_params(value, &params)
+        }
+        #[cfg(feature = "babybear")]
+        "babybear_v2" => {
+            let value = to_u32(value)?;
+            let params = fri_params_from_env(rc_babybear_v2::DEFAULT_FRI_PARAMS)?;
+            rc_babybear_v2::prove_and_verify_with_params(value, &params)
+        }
+        #[cfg(feature = "babybear")]
+        "babybear_compact" => {
+            let value = to_u32(value)?;
+            let params = fri_params_from_env(rc_babybear_compact::DEFAULT_FRI_PARAMS)?;
+            rc_babybear_compact::prove_and_verify_with_params(value, &params)
+        }
+        #[cfg(feature = "goldilocks")]
+        "goldilocks_v1" => {
+            // Round-trip the input through the field so it is tied to the actual modulus, not raw `u64`.
+            let value = if as_field {
+                if value >= Goldilocks::ORDER_U64 {
+                    return Err(Error::InvalidInput(format!(
+                        "{} is not a canonical Goldilocks element, it must be less than the modulus {}",
+                        value,
+                        Goldilocks::ORDER_U64
+                    )));
+                }
+                Goldilocks::from_canonical_u64(value).as_canonical_u64()
+            } else {
+                value
+            };
+            let params = fri_params_from_env(rc_goldilocks_v1::DEFAULT_FRI_PARAMS)?;
+            rc_goldilocks_v1::prove_and_verify_with_params(value, &params)
+        }
+        #[cfg(feature = "koalabear")]
+        "koalabear" | "koalabear_v1" => {
+            let value = to_u32(value)?;
+            let params = fri_params_from_env(rc_koalabear::DEFAULT_FRI_PARAMS)?;
+            rc_koalabear::prove_and_verify_with_params(value, &params)
+        }
+        #[cfg(feature = "goldilocks")]
+        "goldilocks_v2" => {
+            let params = fri_params_from_env(rc_goldilocks_v2::DEFAULT_FRI_PARAMS)?;
+            rc_goldilocks_v2::prove_and_verify_with_params(value, &params)
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn main() -> Result<(), Box<dyn Debug>> {
+    // Only the functions of the enabled fields are offered, every one of them has an arm below.
+    let functions: Vec<&'static str> = FieldKind::all()
+        .iter()
+        .filter(|field| field.is_enabled())
+        .flat_map(|field| match field {
+            FieldKind::BabyBear => vec!["babybear_v1", "babybear_v2", "babybear_compact"],
+            FieldKind::Goldilocks => vec!["goldilocks_v1", "goldilocks_v2"],
+            // `koalabear_v1` names the same prover, after the `_v1` modules of the other fields
+            FieldKind::KoalaBear => vec!["koalabear", "koalabear_v1"],
+            field => vec![field.name()],
+        })
+        .collect();
 
     let matches = Command::new("Range Check")
         .arg(
@@ -23,7 +250,7 @@ fn main() -> Result<(), Box<dyn Debug>> {
                 .long("function")
                 .value_name("FUNCTION")
                 .help("Range check function to use")
-                .value_parser(["mersenne31", "babybear_v1", "babybear_v2", "goldilocks_v1", "goldilocks_v2"])
+                .value_parser(PossibleValuesParser::new(functions))
                 .required(true),
         )
         .arg(
@@ -31,57 +258,158 @@ fn main() -> Result<(), Box<dyn Debug>> {
                 .short('v')
                 .long("value")
                 .value_name("VALUE")
-                .help("Input value to check")
-                .required(true),
+                .help("Input value to check, decimal or hexadecimal with a 0x prefix")
+                .value_parser(parse_value)
+                .required_unless_present("input-file"),
+        )
+        .arg(
+            Arg::new("input-file")
+                .long("input-file")
+                .value_name("PATH")
+                .help("Check every value in the file, one per line, `-` reads stdin")
+                .conflicts_with_all(["value", "min", "max"]),
+        )
+        .arg(
+            Arg::new("as-field")
+                .long("as-field")
+                .help("Interpret the input as a canonical field element (goldilocks_v1 only)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("min")
+                .long("min")
+                .value_name("MIN")
+                .help("Check min <= value <= max instead of the field range (babybear only)")
+                .value_parser(clap::value_parser!(u32))
+                .requires("max"),
+        )
+        .arg(
+            Arg::new("max")
+                .long("max")
+                .value_name("MAX")
+                .help("Upper bound of the value, see --min")
+                .value_parser(clap::value_parser!(u32))
+                .requires("min"),
+        )
+        .arg(
+            Arg::new("quiet")
+                .short('q')
+                .long("quiet")
+                .help("Suppress all logging, including span timings, and only print the result")
+                .action(ArgAction::SetTrue),
         )
         .get_matches();
 
     let function = matches.get_one::<String>("function").unwrap();
-    let value = matches
-        .get_one::<String>("value")
-        .unwrap()
-        .parse::<u64>()
-        .expect("Invalid input value");
+    let as_field = matches.get_flag("as-field");
+    let quiet = matches.get_flag("quiet");
+    if quiet {
+        // Read by the `EnvFilter` the first `prove_and_verify` installs, before any thread is spawned.
+        std::env::set_var("RUST_LOG", "off");
+    }
 
+    let field = field_of(function);
 
-    match function.as_str() {
-        "mersenne31" => {
-            if value > u64::from(u32::MAX) {
-                panic!("Input value is not u32");
+    // Flags a function doesn't support are rejected instead of being ignored.
+    if as_field && function != "goldilocks_v1" {
+        return Err(Box::new(format!("--as-field is only supported by goldilocks_v1, not {}", function)));
+    }
+    #[cfg(not(feature = "babybear"))]
+    if matches.contains_id("min") {
+        return Err(Box::new("--min and --max need the babybear feature".to_string()));
+    }
+
+    // Every value of `--input-file` is proven on its own, failures don't stop the later values. Values outside the
+    // field are reported without proving, the prover of a debug build panics on them.
+    if let Some(path) = matches.get_one::<String>("input-file") {
+        let text = if path == "-" { std::io::read_to_string(std::io::stdin()) } else { std::fs::read_to_string(path) };
+        let text = text.map_err(|e| Box::new(format!("can't read {}: {}", path, e)) as Box<dyn Debug>)?;
+        let values = read_values(&text).map_err(|e| Box::new(format!("{}: {}", path, e)) as Box<dyn Debug>)?;
+
+        let mut failed = 0;
+        for &value in &values {
+            match check_value_in_field(field, value).and_then(|_| prove_value(function, value, as_field)) {
+                Ok(_) => println!("{}: verified", value),
+                Err(e) => {
+                    failed += 1;
+                    println!("{}: failed, {}", value, e);
+                }
             }
-            let value = value as u32;
-            rc_m31::prove_and_verify::<Mersenne31>(value);
         }
-        "babybear_v1" => {
-            if value > u64::from(u32::MAX) {
-                panic!("Input value is not u32");
-            }
-            let value = value as u32;
-            rc_babybear_v1::prove_and_verify::<BabyBear>(value);
+        println!("{} of {} values verified with {}", values.len() - failed, values.len(), function);
+        if failed > 0 {
+            std::process::exit(1);
         }
-        "babybear_v2" => {
-            if value > u64::from(u32::MAX) {
-                panic!("Input value is not u32");
-            }
-            let value = value as u32;
-            rc_babybear_v2::prove_and_verify::<BabyBear>(value);
+        return Ok(());
+    }
+
+    let value = *matches.get_one::<u64>("value").unwrap();
+
+    // `--min` and `--max` replace the field range check with a bounded one, both bounds included.
+    #[cfg(feature = "babybear")]
+    if let (Some(&min), Some(&max)) = (matches.get_one::<u32>("min"), matches.get_one::<u32>("max")) {
+        if field != FieldKind::BabyBear {
+            return Err(Box::new(format!(
+                "--min and --max are only supported by the babybear functions, not {}",
+                function
+            )));
         }
-        "goldilocks_v1" => {
-            if value > u64::from(u64::MAX) {
-                panic!("Input value is not u64");
-            }
-            let value = value as u64;
-            rc_goldilocks_v1::prove_and_verify::<Goldilocks>(value);
+        let value = u32::try_from(value)
+            .unwrap_or_else(|_| fail(function, value, Error::ValueOutOfField { field, value }));
+        let stats = bounded::prove_and_verify_bounded(value, min, max).unwrap_or_else(|e| fail(function, value, e));
+        if quiet {
+            println!("{} verified for {} in [{}, {}], {}", function, value, min, max, stats);
         }
-        // "goldilocks_v2" => {
-        //     if value > u64::from(u64::MAX) {
-        //         panic!("Input value is not u64");
-        //     }
-        //     let value = value as u64;
-        //     rc_goldilocks_v2::prove_and_verify::<Goldilocks>(value);
-        // }
-        _ => unreachable!(),
+        return Ok(());
+    }
+
+    // Out of range values are still proven to show the proof failing, but the user is told why unless `--quiet`
+    // asked for the result only.
+    let max_value = max_provable_value(field);
+    if u128::from(value) > max_value && !quiet {
+        eprintln!(
+            "Input value {} is larger than {}, the largest value a {:?} range check accepts, the proof is expected to fail",
+            value, max_value, field
+        );
     }
 
+    let stats = prove_value(function, value, as_field).unwrap_or_else(|e| fail(function, value, e));
+
+    if quiet {
+        println!("{} verified for {}, {}", function, value, stats);
+    }
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_parse_as_decimal_or_hex() {
+        assert_eq!(parse_value("100"), Ok(100));
+        assert_eq!(parse_value("0xdeadbeef"), Ok(0xdead_beef));
+        assert_eq!(parse_value("0XFFFFFFFF00000001"), Ok(0xFFFF_FFFF_0000_0001));
+        assert_eq!(parse_value(&u64::MAX.to_string()), Ok(u64::MAX));
+    }
+
+    #[test]
+    fn bad_values_are_rejected_with_a_message() {
+        assert!(parse_value("0x1_0000_0000_0000_0000").unwrap_err().contains("not a decimal"));
+        assert!(parse_value("0x10000000000000000").unwrap_err().contains("doesn't fit in 64 bits"));
+        assert!(parse_value("18446744073709551616").unwrap_err().contains("doesn't fit in 64 bits"));
+        assert!(parse_value("0x").is_err());
+        assert!(parse_value("-1").is_err());
+        assert!(parse_value("ff").is_err());
+        assert!(parse_value("+5").unwrap_err().contains("not a decimal"));
+        assert!(parse_value("0x+5").unwrap_err().contains("not a decimal"));
+    }
+
+    #[test]
+    fn input_files_skip_blank_lines_and_report_bad_ones() {
+        assert_eq!(read_values("1\n\n  0x10 \n\n"), Ok(vec![1, 16]));
+        assert_eq!(read_values(""), Ok(vec![]));
+        let error = read_values("1\n\n2\nabc\n").unwrap_err();
+        assert!(error.starts_with("line 4: "), "{}", error);
+    }
+}
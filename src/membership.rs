@@ -0,0 +1,146 @@
+use p3_baby_bear::BabyBear;
+use p3_challenger::CanObserve;
+use p3_field::AbstractField;
+use p3_keccak::Keccak256Hash;
+use p3_symmetric::{CompressionFunctionFromHasher, CryptographicHasher, PseudoCompressionFunction};
+use p3_uni_stark::Proof;
+
+use crate::babybear_v1::{self, public_values, Challenger, MyConfig, DEFAULT_FRI_PARAMS};
+use crate::config::MERKLE_ARITY;
+use crate::error::Error;
+
+// The 2-to-1 Keccak compression the MMCS of the provers uses.
+type Compress = CompressionFunctionFromHasher<Keccak256Hash, MERKLE_ARITY, 32>;
+
+pub type Digest = [u8; 32];
+
+/// Range check proof for a value that is a leaf of a Keccak Merkle tree with a public root.
+///
+/// The range check is a regular `babybear_v1` proof over a transcript that starts with the root. The Merkle
+/// path is checked natively by the verifier with the same Keccak compression the MMCS uses, not inside the
+/// AIR: recomputing Keccak in the trace needs a Keccak-f AIR linked to the range check columns, which the
+/// single-table `p3_uni_stark` prover can't express. The proof therefore reveals the value and its path.
+pub struct MembershipProof {
+    // Position of the leaf in the tree.
+    pub index: usize,
+    // Sibling digests from the leaf level up to the root.
+    pub path: Vec<Digest>,
+    pub proof: Proof<MyConfig>,
+}
+
+pub fn leaf_hash(value: u32) -> Digest {
+    Keccak256Hash {}.hash_iter(value.to_le_bytes())
+}
+
+fn compress(left: Digest, right: Digest) -> Digest {
+    Compress::new(Keccak256Hash {}).compress([left, right])
+}
+
+// Builds every level of the tree, from the leaves to the root. The number of leaves has to be a power of two.
+fn levels(leaves: &[u32]) -> Result<Vec<Vec<Digest>>, Error> {
+    if !leaves.len().is_power_of_two() {
+        return Err(Error::InvalidInput(format!("{} leaves isn't a power of two", leaves.len())));
+    }
+    let mut levels = vec![leaves.iter().map(|&leaf| leaf_hash(leaf)).collect::<Vec<_>>()];
+    while levels.last().unwrap().len() > 1 {
+        let next = levels.last().unwrap().chunks(2).map(|pair| compress(pair[0], pair[1])).collect();
+        levels.push(next);
+    }
+    Ok(levels)
+}
+
+pub fn merkle_root(leaves: &[u32]) -> Result<Digest, Error> {
+    Ok(levels(leaves)?.last().unwrap()[0])
+}
+
+pub fn merkle_path(leaves: &[u32], index: usize) -> Result<Vec<Digest>, Error> {
+    let levels = levels(leaves)?;
+    if index >= leaves.len() {
+        return Err(Error::InvalidInput(format!("leaf {} is out of {} leaves", index, leaves.len())));
+    }
+    Ok(levels[..levels.len() - 1].iter().enumerate().map(|(height, level)| level[(index >> height) ^ 1]).collect())
+}
+
+// Only the low `path.len()` bits of `index` pick a side, so larger indices are rejected instead of aliasing a leaf.
+pub(crate) fn root_from_path(value: u32, index: usize, path: &[Digest]) -> Result<Digest, Error> {
+    if index.checked_shr(u32::try_from(path.len()).unwrap_or(u32::MAX)).unwrap_or(0) != 0 {
+        return Err(Error::InvalidInput(format!("leaf {} is out of a tree of depth {}", index, path.len())));
+    }
+    Ok(path.iter().enumerate().fold(leaf_hash(value), |node, (height, &sibling)| {
+        if (index >> height) & 1 == 0 {
+            compress(node, sibling)
+        } else {
+            compress(sibling, node)
+        }
+    }))
+}
+
+pub(crate) fn membership_challenger(root: &Digest) -> Challenger {
+    let mut challenger = babybear_v1::challenger();
+    // Observed as 16 bit limbs, which are always canonical field elements
+    for limb in root.chunks(2) {
+        challenger.observe(BabyBear::from_canonical_u32(u32::from(u16::from_le_bytes([limb[0], limb[1]]))));
+    }
+    challenger
+}
+
+/// Proves `value` is in range and is the leaf at `index` of the tree with `root`.
+pub fn prove_membership(value: u32, index: usize, path: Vec<Digest>, root: &Digest) -> Result<MembershipProof, Error> {
+    if root_from_path(value, index, &path)? != *root {
+        return Err(Error::InvalidInput(format!("{} isn't leaf {} of the tree", value, index)));
+    }
+    let mut challenger = membership_challenger(root);
+    let proof = babybear_v1::prove_with_challenger(value, &public_values(value), &DEFAULT_FRI_PARAMS, &mut challenger);
+    Ok(MembershipProof { index, path, proof })
+}
+
+pub fn verify_membership(value: u32, root: &Digest, proof: &MembershipProof) -> Result<(), Error> {
+    if !matches!(root_from_path(value, proof.index, &proof.path), Ok(found) if found == *root) {
+        return Err(Error::Verification("Merkle path doesn't lead to the root".to_string()));
+    }
+    let (mut challenger, public_values) = (membership_challenger(root), public_values(value));
+    babybear_v1::verify_with_challenger(value, &public_values, &proof.proof, &DEFAULT_FRI_PARAMS, &mut challenger)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_lead_to_the_root() {
+        let leaves = [3, 1, 4, 1, 5, 9, 2, 6];
+        let root = merkle_root(&leaves).unwrap();
+        for (index, &leaf) in leaves.iter().enumerate() {
+            let path = merkle_path(&leaves, index).unwrap();
+            assert_eq!(path.len(), 3);
+            assert_eq!(root_from_path(leaf, index, &path).unwrap(), root);
+            assert_ne!(root_from_path(leaf + 1, index, &path).unwrap(), root);
+        }
+    }
+
+    #[test]
+    fn membership_proof_is_bound_to_the_root() {
+        let leaves = [10, 20, 30, 40];
+        let root = merkle_root(&leaves).unwrap();
+        let proof = prove_membership(30, 2, merkle_path(&leaves, 2).unwrap(), &root).unwrap();
+        assert!(verify_membership(30, &root, &proof).is_ok());
+        assert!(verify_membership(20, &root, &proof).is_err());
+
+        let other_root = merkle_root(&[10, 20, 30, 50]).unwrap();
+        assert!(verify_membership(30, &other_root, &proof).is_err());
+    }
+
+    #[test]
+    fn indices_past_the_tree_are_rejected() {
+        // Index 6 has the same low bits as 2 in a tree of depth 2, it must not verify as that leaf
+        let leaves = [10, 20, 30, 40];
+        let root = merkle_root(&leaves).unwrap();
+        let path = merkle_path(&leaves, 2).unwrap();
+        assert!(matches!(root_from_path(30, 6, &path), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_membership(30, 6, path.clone(), &root), Err(Error::InvalidInput(_))));
+
+        let proof = prove_membership(30, 2, path, &root).unwrap();
+        let aliased = MembershipProof { index: 6, ..proof };
+        assert!(matches!(verify_membership(30, &root, &aliased), Err(Error::Verification(_))));
+    }
+}
@@ -0,0 +1,150 @@
+use p3_air::{Air, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, Field, PrimeField32};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::babybear_v1::{self, eval_value_columns, value_columns, MyConfig, DEFAULT_FRI_PARAMS, WIDTH};
+use crate::bits::{reconstruct, write_bits};
+use crate::error::Error;
+use crate::field::FieldKind;
+
+// Proves `value ≡ remainder (mod modulus)` for a private `value`, with a single row:
+// | value bits | value ANDs | q bits | slack bits |
+// The value columns are the same as `babybear_v1`'s, so `value < p`. The constraints are `value == q * modulus +
+// remainder` and `q + slack == q_max`, where `q_max = (p - 1 - remainder) / modulus`. With `q <= q_max` the right
+// hand side is at most `p - 1`, so it can't wrap around the field and the equality holds over the integers.
+// Public values are `[modulus, remainder]`, `remainder < modulus` is checked outside of the AIR.
+pub struct BabyBearModAir {
+    pub q_max: u32,
+}
+
+impl BabyBearModAir {
+    pub fn new(modulus: u32, remainder: u32) -> Self {
+        Self { q_max: (BabyBear::ORDER_U32 - 1 - remainder) / modulus }
+    }
+
+    // `q_max < 2^30` for `modulus >= 2`. Both `q` and the slack have the bit length of `q_max`, so their sum is
+    // below `p + q_max` and can only equal `q_max` without wrapping.
+    pub fn q_bits(&self) -> usize {
+        ((32 - self.q_max.leading_zeros()) as usize).max(1)
+    }
+}
+
+impl<F: Field> BaseAir<F> for BabyBearModAir {
+    fn width(&self) -> usize {
+        WIDTH + 2 * self.q_bits()
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for BabyBearModAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let n = self.q_bits();
+
+        let public_values = builder.public_values();
+        let modulus: AB::Expr = public_values[0].into();
+        let remainder: AB::Expr = public_values[1].into();
+
+        let value = eval_value_columns(builder, &local[..WIDTH]);
+
+        // Making sure every bit of `q` and the slack is either 0 or 1
+        for &bit in &local[WIDTH..] {
+            builder.assert_bool(bit);
+        }
+        let q = reconstruct::<AB>(&local[WIDTH..WIDTH + n]);
+        let slack = reconstruct::<AB>(&local[WIDTH + n..]);
+
+        builder.assert_eq(q.clone() + slack, AB::Expr::from_canonical_u32(self.q_max));
+        builder.assert_eq(value, q * modulus + remainder);
+    }
+}
+
+pub fn generate_trace<F: Field>(value: u32, modulus: u32, remainder: u32) -> RowMajorMatrix<F> {
+    let air = BabyBearModAir::new(modulus, remainder);
+    let n = air.q_bits();
+    let q = value / modulus;
+
+    let mut row = value_columns::<F>(value);
+    row.resize(WIDTH + 2 * n, F::zero());
+    write_bits(&mut row[WIDTH..WIDTH + n], u64::from(q));
+    write_bits(&mut row[WIDTH + n..], u64::from(air.q_max - q));
+    RowMajorMatrix::new(row, WIDTH + 2 * n)
+}
+
+fn check_inputs(modulus: u32, remainder: u32) -> Result<Vec<BabyBear>, Error> {
+    if modulus < 2 || modulus >= BabyBear::ORDER_U32 {
+        return Err(Error::InvalidInput(format!("modulus {} has to be in [2, p)", modulus)));
+    }
+    if remainder >= modulus {
+        return Err(Error::InvalidInput(format!("remainder {} isn't below the modulus {}", remainder, modulus)));
+    }
+    Ok(vec![BabyBear::from_canonical_u32(modulus), BabyBear::from_canonical_u32(remainder)])
+}
+
+/// Proves `value ≡ remainder (mod modulus)` without revealing `value`.
+pub fn prove_mod(value: u32, modulus: u32, remainder: u32) -> Result<Proof<MyConfig>, Error> {
+    let public_values = check_inputs(modulus, remainder)?;
+    if value >= BabyBear::ORDER_U32 {
+        return Err(Error::ValueOutOfField { field: FieldKind::BabyBear, value: u64::from(value) });
+    }
+    if value % modulus != remainder {
+        return Err(Error::InvalidInput(format!("{} is not {} modulo {}", value, remainder, modulus)));
+    }
+
+    let air = BabyBearModAir::new(modulus, remainder);
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<BabyBear>(value, modulus, remainder));
+    let config = babybear_v1::config(trace.height(), &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    Ok(info_span!("commit_trace")
+        .in_scope(|| p3_uni_stark::prove(&config, &air, &mut challenger, trace, &public_values)))
+}
+
+pub fn verify_mod(modulus: u32, remainder: u32, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    let public_values = check_inputs(modulus, remainder)?;
+    let air = BabyBearModAir::new(modulus, remainder);
+    let config = babybear_v1::config(1, &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    p3_uni_stark::verify(&config, &air, &mut challenger, proof, &public_values)
+        .map_err(|e| Error::verification_failed(FieldKind::BabyBear, u64::from(remainder), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::debug::satisfies;
+
+    fn public(modulus: u32, remainder: u32) -> Vec<BabyBear> {
+        vec![BabyBear::from_canonical_u32(modulus), BabyBear::from_canonical_u32(remainder)]
+    }
+
+    #[test]
+    fn residues_satisfy_constraints() {
+        for (value, modulus) in [(0, 2), (7, 2), (100, 7), (BabyBear::ORDER_U32 - 1, 3), (5, BabyBear::ORDER_U32 - 1)] {
+            let remainder = value % modulus;
+            let trace = generate_trace::<BabyBear>(value, modulus, remainder);
+            let air = BabyBearModAir::new(modulus, remainder);
+            assert!(satisfies(&air, &trace, &public(modulus, remainder)));
+        }
+    }
+
+    #[test]
+    fn wrong_remainder_fails() {
+        // A trace for 7 mod 2 can't claim a remainder of 0
+        let trace = generate_trace::<BabyBear>(7, 2, 1);
+        assert!(!satisfies(&BabyBearModAir::new(2, 0), &trace, &public(2, 0)));
+    }
+
+    #[test]
+    fn inputs_are_checked() {
+        assert!(matches!(prove_mod(7, 2, 0), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_mod(7, 1, 0), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_mod(7, 3, 3), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_mod(BabyBear::ORDER_U32, 2, 1), Err(Error::ValueOutOfField { .. })));
+    }
+}
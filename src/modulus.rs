@@ -0,0 +1,180 @@
+use p3_air::AirBuilder;
+use p3_field::Field;
+
+/// Big endian bit layout of a prime modulus of the form `2^a - 2^b + 1`.
+///
+/// For such a modulus, `modulus - 1` is a run of leading zeros, followed by a run of ones, followed by zeros.
+/// A big endian bit decomposition encodes a value below the modulus iff the leading zero bits are zero and,
+/// whenever the top ones bits are all one, all the remaining bits are zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ModulusPattern {
+    // Number of bits in the decomposition, e.g. 32 for BabyBear.
+    pub width: usize,
+    // Number of most significant bits that have to be zero.
+    pub leading_zeros: usize,
+    // Number of bits following the leading zeros that are all one in `modulus - 1`.
+    pub top_ones: usize,
+}
+
+impl ModulusPattern {
+    pub const fn new(modulus: u64, width: usize) -> Self {
+        let max = modulus - 1;
+        let leading_zeros = max.leading_zeros() as usize - (64 - width);
+        let top_ones = (max << (64 - width + leading_zeros)).leading_ones() as usize;
+        let pattern = Self { width, leading_zeros, top_ones };
+        // Rejects moduli whose `modulus - 1` has stray bits after the run of ones.
+        assert!(pattern.modulus() == modulus);
+        pattern
+    }
+
+    // Index of the first bit after the top ones bits.
+    pub const fn tail_start(&self) -> usize {
+        self.leading_zeros + self.top_ones
+    }
+
+    // Reconstructs the modulus from the bit pattern.
+    pub const fn modulus(&self) -> u64 {
+        let tail = self.width - self.tail_start();
+        (((1u64 << self.top_ones) - 1) << tail) + 1
+    }
+}
+
+// Mersenne31 Modulus in big endian format
+// 01111111 11111111 11111111 11111111
+pub const MERSENNE31: ModulusPattern = ModulusPattern::new((1 << 31) - 1, 32);
+
+// Baby Bear Modulus in big endian format
+// 01111000 00000000 00000000 00000001
+pub const BABYBEAR: ModulusPattern = ModulusPattern::new(0b01111000_00000000_00000000_00000001, 32);
+
+// Koala Bear Modulus in big endian format
+// 01111111 00000000 00000000 00000001
+pub const KOALABEAR: ModulusPattern = ModulusPattern::new(0b01111111_00000000_00000000_00000001, 32);
+
+// Goldilocks Modulus in big endian format:
+// 11111111 11111111 11111111 11111111 00000000 00000000 00000000 00000001
+pub const GOLDILOCKS: ModulusPattern = ModulusPattern::new(0xFFFF_FFFF_0000_0001, 64);
+
+/// Asserts that the big endian `bits` encode a value below the modulus described by `pattern`.
+pub fn eval_modulus_boundary<AB: AirBuilder>(builder: &mut AB, bits: &[AB::Var], pattern: &ModulusPattern) {
+    // Assert that the leading bits are zero
+    for &bit in &bits[..pattern.leading_zeros] {
+        builder.assert_zero(bit);
+    }
+
+    // Value to check if the top ones bits are all one
+    let upper_bits_product = bits[pattern.leading_zeros..pattern.tail_start()]
+        .iter()
+        .map(|&bit| bit.into())
+        .product::<AB::Expr>();
+    // Value to check if the sum of the remaining bits is zero, only if `upper_bits_product` is 1.
+    let remaining_bits_sum = bits[pattern.tail_start()..pattern.width]
+        .iter()
+        .map(|&bit| bit.into())
+        .sum::<AB::Expr>();
+
+    builder.when(upper_bits_product).assert_zero(remaining_bits_sum);
+}
+
+/// Same check as `eval_modulus_boundary`, but keeps every constraint at degree 2 using committed `ands`.
+///
+/// `ands` holds `pattern.top_ones - 1` auxiliary columns, the running AND of the top ones bits starting from
+/// the least significant one, as computed by `and_chain`. The last one is 1 iff the top ones bits are all one.
+pub fn eval_modulus_boundary_degree_2<AB: AirBuilder>(
+    builder: &mut AB,
+    bits: &[AB::Var],
+    ands: &[AB::Var],
+    pattern: &ModulusPattern,
+) {
+    assert_eq!(ands.len(), pattern.top_ones - 1);
+
+    // Assert that the leading bits are zero
+    for &bit in &bits[..pattern.leading_zeros] {
+        builder.assert_zero(bit);
+    }
+
+    // Every AND column multiplies the previous one with the next more significant top bit
+    let top_bits = &bits[pattern.leading_zeros..pattern.tail_start()];
+    let mut previous: AB::Expr = top_bits[top_bits.len() - 1].into();
+    for (&and, &bit) in ands.iter().zip(top_bits.iter().rev().skip(1)) {
+        builder.assert_eq(and, previous * bit);
+        previous = and.into();
+    }
+
+    let remaining_bits_sum = bits[pattern.tail_start()..pattern.width]
+        .iter()
+        .map(|&bit| bit.into())
+        .sum::<AB::Expr>();
+
+    builder.when(previous).assert_zero(remaining_bits_sum);
+}
+
+/// Values of the AND columns of `eval_modulus_boundary_degree_2` for the big endian `bits`.
+pub fn and_chain<F: Field>(bits: &[F], pattern: &ModulusPattern) -> Vec<F> {
+    let top_bits = &bits[pattern.leading_zeros..pattern.tail_start()];
+    let mut previous = top_bits[top_bits.len() - 1];
+    top_bits
+        .iter()
+        .rev()
+        .skip(1)
+        .map(|&bit| {
+            previous *= bit;
+            previous
+        })
+        .collect()
+}
+
+#[cfg(all(test, feature = "babybear", feature = "goldilocks"))]
+mod tests {
+    use super::*;
+    use p3_baby_bear::BabyBear;
+    use p3_field::{AbstractField, PrimeField32, PrimeField64};
+    use p3_goldilocks::Goldilocks;
+
+    // Expands the pattern into the big endian bits of `modulus - 1`.
+    fn pattern_bits(pattern: &ModulusPattern) -> Vec<bool> {
+        (0..pattern.width)
+            .map(|i| i >= pattern.leading_zeros && i < pattern.tail_start())
+            .collect()
+    }
+
+    fn order_bits(max: u64, width: usize) -> Vec<bool> {
+        (0..width).rev().map(|i| (max >> i) & 1 == 1).collect()
+    }
+
+    #[test]
+    fn babybear_pattern_matches_order() {
+        assert_eq!(BABYBEAR.modulus(), u64::from(BabyBear::ORDER_U32));
+        assert_eq!(pattern_bits(&BABYBEAR), order_bits(u64::from(BabyBear::ORDER_U32) - 1, 32));
+    }
+
+    #[test]
+    fn goldilocks_pattern_matches_order() {
+        assert_eq!(GOLDILOCKS.modulus(), Goldilocks::ORDER_U64);
+        assert_eq!(pattern_bits(&GOLDILOCKS), order_bits(Goldilocks::ORDER_U64 - 1, 64));
+    }
+
+    #[test]
+    fn and_chain_is_one_iff_top_bits_are_one() {
+        let bits = |max: u64| order_bits(max, 32).into_iter().map(BabyBear::from_bool).collect::<Vec<_>>();
+        let max = u64::from(BabyBear::ORDER_U32) - 1;
+        assert_eq!(and_chain(&bits(max), &BABYBEAR), vec![BabyBear::one(); 3]);
+        assert_eq!(*and_chain(&bits(max - (1 << 27)), &BABYBEAR).last().unwrap(), BabyBear::zero());
+    }
+
+    #[test]
+    #[cfg(feature = "mersenne31")]
+    fn mersenne31_pattern_matches_order() {
+        use p3_mersenne_31::Mersenne31;
+        assert_eq!(MERSENNE31.modulus(), u64::from(Mersenne31::ORDER_U32));
+    }
+
+    #[test]
+    #[cfg(feature = "koalabear")]
+    fn koalabear_pattern_matches_order() {
+        use p3_koala_bear::KoalaBear;
+        assert_eq!(KOALABEAR.modulus(), u64::from(KoalaBear::ORDER_U32));
+        assert_eq!(pattern_bits(&KOALABEAR), order_bits(u64::from(KoalaBear::ORDER_U32) - 1, 32));
+        assert_eq!((KOALABEAR.leading_zeros, KOALABEAR.top_ones), (1, 7));
+    }
+}
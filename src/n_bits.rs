@@ -0,0 +1,142 @@
+use p3_air::{Air, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, Field, PrimeField32};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::babybear_v1::{self, MyConfig, DEFAULT_FRI_PARAMS};
+use crate::bits::{reconstruct, write_bits};
+use crate::error::Error;
+use crate::field::FieldKind;
+
+pub const MAX_N_BITS: usize = 32;
+
+// Proves `value < 2^n_bits` with a single row of `n_bits` big endian bits, the high bits of a full decomposition
+// are left out, so they are zero. Public values are `[value]`.
+// Up to 30 bits the reconstruction stays below the modulus and can't wrap. From 31 bits on every canonical value
+// is in range, so the verifier only has to reject values that aren't canonical.
+pub struct BabyBearNBitsAir {
+    pub value: u32,
+    pub n_bits: usize,
+}
+
+impl<F: Field> BaseAir<F> for BabyBearNBitsAir {
+    fn width(&self) -> usize {
+        self.n_bits
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for BabyBearNBitsAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let current_row = main.row_slice(0);
+
+        let value: AB::Expr = builder.public_values()[0].into();
+        builder.assert_eq(value.clone(), AB::Expr::from_canonical_u32(self.value));
+
+        // Making sure every bit is either 0 or 1
+        for &bit in current_row.iter() {
+            builder.assert_bool(bit);
+        }
+
+        // Assert if the reconstructed value matches the original value
+        builder.assert_eq(value, reconstruct::<AB>(&current_row));
+    }
+}
+
+// Requires `value < 2^n_bits`, higher bits are dropped.
+pub fn generate_trace<F: Field>(value: u32, n_bits: usize) -> RowMajorMatrix<F> {
+    let mut row = vec![F::zero(); n_bits];
+    write_bits(&mut row, u64::from(value));
+    RowMajorMatrix::new(row, n_bits)
+}
+
+fn check_inputs(value: u32, n_bits: usize) -> Result<(), Error> {
+    if n_bits == 0 || n_bits > MAX_N_BITS {
+        return Err(Error::InvalidInput(format!("bit count {} has to be in [1, {}]", n_bits, MAX_N_BITS)));
+    }
+    if value >= BabyBear::ORDER_U32 {
+        return Err(Error::ValueOutOfField { field: FieldKind::BabyBear, value: u64::from(value) });
+    }
+    Ok(())
+}
+
+/// Proves `value < 2^n_bits` with an `n_bits` wide trace, for `n_bits` in `[1, 32]`.
+pub fn prove_n_bits(value: u32, n_bits: usize) -> Result<Proof<MyConfig>, Error> {
+    check_inputs(value, n_bits)?;
+    if u64::from(value) >> n_bits != 0 {
+        return Err(Error::InvalidInput(format!("{} doesn't fit in {} bits", value, n_bits)));
+    }
+
+    let air = BabyBearNBitsAir { value, n_bits };
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<BabyBear>(value, n_bits));
+    let config = babybear_v1::config(trace.height(), &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    let public_values = vec![BabyBear::from_canonical_u32(value)];
+    Ok(info_span!("commit_trace")
+        .in_scope(|| p3_uni_stark::prove(&config, &air, &mut challenger, trace, &public_values)))
+}
+
+pub fn verify_n_bits(value: u32, n_bits: usize, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    check_inputs(value, n_bits)?;
+    let air = BabyBearNBitsAir { value, n_bits };
+    let config = babybear_v1::config(1, &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    let public_values = vec![BabyBear::from_canonical_u32(value)];
+    p3_uni_stark::verify(&config, &air, &mut challenger, proof, &public_values)
+        .map_err(|e| Error::verification_failed(FieldKind::BabyBear, u64::from(value), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::debug::satisfies;
+
+    fn satisfied(value: u32, n_bits: usize) -> bool {
+        let air = BabyBearNBitsAir { value, n_bits };
+        let trace = generate_trace::<BabyBear>(value, n_bits);
+        satisfies(&air, &trace, &[BabyBear::from_canonical_u32(value)])
+    }
+
+    #[test]
+    fn every_bit_count_accepts_its_largest_value() {
+        for n_bits in 1..=30 {
+            assert!(satisfied((1 << n_bits) - 1, n_bits), "{} bits", n_bits);
+            assert!(satisfied(0, n_bits), "{} bits", n_bits);
+        }
+        for n_bits in 31..=MAX_N_BITS {
+            assert!(satisfied(BabyBear::ORDER_U32 - 1, n_bits), "{} bits", n_bits);
+        }
+    }
+
+    #[test]
+    fn values_wider_than_n_bits_fail() {
+        // The trace keeps only the low `n_bits` bits, which don't reconstruct the value
+        for n_bits in 1..=30 {
+            assert!(!satisfied(1 << n_bits, n_bits), "{} bits", n_bits);
+        }
+        assert!(!satisfied(256, 8));
+        assert!(!satisfied(0x1_0000, 16));
+    }
+
+    #[test]
+    fn n_bits_proofs_verify() {
+        for n_bits in [1, 8, 16, 30, 31, 32] {
+            let value = ((1u64 << n_bits) - 1).min(u64::from(BabyBear::ORDER_U32 - 1)) as u32;
+            let proof = prove_n_bits(value, n_bits).unwrap();
+            verify_n_bits(value, n_bits, &proof).unwrap();
+        }
+
+        let proof = prove_n_bits(255, 8).unwrap();
+        assert!(verify_n_bits(256, 8, &proof).is_err());
+        assert!(verify_n_bits(255, 9, &proof).is_err());
+        assert!(matches!(prove_n_bits(256, 8), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_n_bits(0, 0), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_n_bits(0, 33), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_n_bits(BabyBear::ORDER_U32, 32), Err(Error::ValueOutOfField { .. })));
+    }
+}
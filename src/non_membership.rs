@@ -0,0 +1,235 @@
+use p3_air::{Air, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, Field};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::babybear_v1::{self, MyConfig, DEFAULT_FRI_PARAMS};
+use crate::bits::{reconstruct, write_bits};
+use crate::error::Error;
+use crate::field::FieldKind;
+use crate::membership::{membership_challenger, merkle_path, merkle_root, root_from_path, Digest};
+
+// Bit width of the gaps to the neighbors. Inputs are below `2^GAP_BITS`, so an honest gap fits in the bits,
+// while a value at or past a neighbor gives a gap that wraps around the modulus to at least `p - 2^29 > 2^30`.
+pub const GAP_BITS: usize = 29;
+
+// Proves `lo < value < hi`, with a single row:
+// | lo gap bits | hi gap bits |
+// The gaps are `value - lo - 1` and `hi - value - 1`. A value below the first element of the list has no `lo`,
+// one above the last has no `hi`, the missing neighbor's public value and gap are zero.
+// Public values are `[value, lo, hi]`.
+pub struct BabyBearNonMembershipAir {
+    pub has_lo: bool,
+    pub has_hi: bool,
+}
+
+impl<F: Field> BaseAir<F> for BabyBearNonMembershipAir {
+    fn width(&self) -> usize {
+        2 * GAP_BITS
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for BabyBearNonMembershipAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+
+        let public_values = builder.public_values();
+        let value: AB::Expr = public_values[0].into();
+        let lo: AB::Expr = public_values[1].into();
+        let hi: AB::Expr = public_values[2].into();
+
+        // Making sure every bit is either 0 or 1
+        for &bit in local.iter() {
+            builder.assert_bool(bit);
+        }
+
+        let lo_gap = reconstruct::<AB>(&local[..GAP_BITS]);
+        let hi_gap = reconstruct::<AB>(&local[GAP_BITS..]);
+        if self.has_lo {
+            builder.assert_eq(lo_gap, value.clone() - lo - AB::Expr::one());
+        } else {
+            builder.assert_zero(lo_gap);
+        }
+        if self.has_hi {
+            builder.assert_eq(hi_gap, hi - value - AB::Expr::one());
+        } else {
+            builder.assert_zero(hi_gap);
+        }
+    }
+}
+
+// Requires `lo < value < hi` for the neighbors that are present.
+pub fn generate_trace<F: Field>(value: u32, lo: Option<u32>, hi: Option<u32>) -> RowMajorMatrix<F> {
+    let mut row = vec![F::zero(); 2 * GAP_BITS];
+    if let Some(lo) = lo {
+        write_bits(&mut row[..GAP_BITS], u64::from(value - lo - 1));
+    }
+    if let Some(hi) = hi {
+        write_bits(&mut row[GAP_BITS..], u64::from(hi - value - 1));
+    }
+    RowMajorMatrix::new(row, 2 * GAP_BITS)
+}
+
+/// A leaf of the committed list next to the value, with its Merkle path.
+pub struct Neighbor {
+    pub value: u32,
+    pub index: usize,
+    pub path: Vec<Digest>,
+}
+
+/// Proof that a value isn't in a sorted list committed to by a Keccak Merkle root, see `membership`.
+///
+/// The neighbors are adjacent leaves with the value strictly between them, their paths are checked natively by
+/// the verifier like `MembershipProof`'s and the gaps by the AIR. Adjacency only implies absence if the list is
+/// strictly increasing, which the root doesn't prove, so the root has to come from someone trusted to sort it.
+pub struct NonMembershipProof {
+    // `None` if the value is below the first leaf.
+    pub lo: Option<Neighbor>,
+    // `None` if the value is above the last leaf.
+    pub hi: Option<Neighbor>,
+    pub proof: Proof<MyConfig>,
+}
+
+fn public_values(value: u32, lo: Option<u32>, hi: Option<u32>) -> Vec<BabyBear> {
+    [value, lo.unwrap_or(0), hi.unwrap_or(0)].into_iter().map(BabyBear::from_canonical_u32).collect()
+}
+
+fn check_inputs(inputs: &[u32]) -> Result<(), Error> {
+    for &input in inputs {
+        if input >> GAP_BITS != 0 {
+            return Err(Error::InvalidInput(format!("{} doesn't fit in {} bits", input, GAP_BITS)));
+        }
+    }
+    Ok(())
+}
+
+fn neighbor(leaves: &[u32], index: usize) -> Result<Neighbor, Error> {
+    Ok(Neighbor { value: leaves[index], index, path: merkle_path(leaves, index)? })
+}
+
+/// Proves that `value` isn't one of `leaves`, which have to be strictly increasing and a power of two many.
+/// Returns the proof with the root of `leaves`.
+pub fn prove_non_membership(value: u32, leaves: &[u32]) -> Result<(Digest, NonMembershipProof), Error> {
+    check_inputs(leaves)?;
+    check_inputs(&[value])?;
+    if leaves.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err(Error::InvalidInput("leaves aren't strictly increasing".to_string()));
+    }
+    let root = merkle_root(leaves)?;
+    let position = leaves.partition_point(|&leaf| leaf < value);
+    if leaves.get(position) == Some(&value) {
+        return Err(Error::InvalidInput(format!("{} is leaf {}", value, position)));
+    }
+
+    let lo = if position > 0 { Some(neighbor(leaves, position - 1)?) } else { None };
+    let hi = if position < leaves.len() { Some(neighbor(leaves, position)?) } else { None };
+    let (lo_value, hi_value) = (lo.as_ref().map(|lo| lo.value), hi.as_ref().map(|hi| hi.value));
+
+    let air = BabyBearNonMembershipAir { has_lo: lo.is_some(), has_hi: hi.is_some() };
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<BabyBear>(value, lo_value, hi_value));
+    let config = babybear_v1::config(trace.height(), &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = membership_challenger(&root);
+    let public_values = public_values(value, lo_value, hi_value);
+    let proof = info_span!("commit_trace")
+        .in_scope(|| p3_uni_stark::prove(&config, &air, &mut challenger, trace, &public_values));
+    Ok((root, NonMembershipProof { lo, hi, proof }))
+}
+
+// The neighbors have to be leaves of `root` next to each other, or the first or last leaf if one is missing.
+fn check_neighbors(root: &Digest, lo: Option<&Neighbor>, hi: Option<&Neighbor>) -> Result<(), Error> {
+    let depth = match (lo, hi) {
+        (Some(lo), Some(hi)) if lo.path.len() == hi.path.len() && hi.index == lo.index + 1 => lo.path.len(),
+        (Some(lo), None) if 1usize.checked_shl(lo.path.len() as u32) == Some(lo.index + 1) => lo.path.len(),
+        (None, Some(hi)) if hi.index == 0 => hi.path.len(),
+        _ => return Err(Error::Verification("neighbors aren't adjacent leaves".to_string())),
+    };
+    for neighbor in lo.into_iter().chain(hi) {
+        let found = root_from_path(neighbor.value, neighbor.index, &neighbor.path);
+        if neighbor.path.len() != depth || !matches!(found, Ok(found) if found == *root) {
+            return Err(Error::Verification(format!("leaf {} doesn't lead to the root", neighbor.index)));
+        }
+    }
+    Ok(())
+}
+
+pub fn verify_non_membership(value: u32, root: &Digest, proof: &NonMembershipProof) -> Result<(), Error> {
+    let (lo, hi) = (proof.lo.as_ref(), proof.hi.as_ref());
+    check_neighbors(root, lo, hi)?;
+    let (lo_value, hi_value) = (lo.map(|lo| lo.value), hi.map(|hi| hi.value));
+    check_inputs(&[value, lo_value.unwrap_or(0), hi_value.unwrap_or(0)])?;
+
+    let air = BabyBearNonMembershipAir { has_lo: lo.is_some(), has_hi: hi.is_some() };
+    let config = babybear_v1::config(1, &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = membership_challenger(root);
+    p3_uni_stark::verify(&config, &air, &mut challenger, &proof.proof, &public_values(value, lo_value, hi_value))
+        .map_err(|e| Error::verification_failed(FieldKind::BabyBear, u64::from(value), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::debug::{babybear_difference, satisfies};
+
+    const LEAVES: [u32; 4] = [10, 20, 30, 40];
+
+    // The trace for any claim, a gap wraps when the value isn't strictly between the neighbors. The neighbors in
+    // these tests are far below the modulus, so adding 1 to them stays canonical.
+    fn satisfied_forged(value: u32, lo: Option<u32>, hi: Option<u32>) -> bool {
+        let mut row = vec![BabyBear::zero(); 2 * GAP_BITS];
+        if let Some(lo) = lo {
+            write_bits(&mut row[..GAP_BITS], babybear_difference(value, lo + 1));
+        }
+        if let Some(hi) = hi {
+            write_bits(&mut row[GAP_BITS..], babybear_difference(hi, value + 1));
+        }
+        let air = BabyBearNonMembershipAir { has_lo: lo.is_some(), has_hi: hi.is_some() };
+        let trace = RowMajorMatrix::new(row, 2 * GAP_BITS);
+        satisfies(&air, &trace, &public_values(value, lo, hi))
+    }
+
+    #[test]
+    fn values_strictly_between_the_neighbors_pass() {
+        assert!(satisfied_forged(15, Some(10), Some(20)));
+        assert!(satisfied_forged(5, None, Some(10)));
+        assert!(satisfied_forged(45, Some(40), None));
+        assert!(!satisfied_forged(10, Some(10), Some(20)));
+        assert!(!satisfied_forged(20, Some(10), Some(20)));
+        assert!(!satisfied_forged(10, None, Some(10)));
+        assert!(!satisfied_forged(40, Some(40), None));
+    }
+
+    #[test]
+    fn absent_values_verify_at_every_position() {
+        for value in [5, 15, 35, 45] {
+            let (root, proof) = prove_non_membership(value, &LEAVES).unwrap();
+            verify_non_membership(value, &root, &proof).unwrap();
+        }
+        for value in LEAVES {
+            assert!(matches!(prove_non_membership(value, &LEAVES), Err(Error::InvalidInput(_))));
+        }
+        assert!(matches!(prove_non_membership(15, &[20, 10, 30, 40]), Err(Error::InvalidInput(_))));
+    }
+
+    #[test]
+    fn neighbors_have_to_be_adjacent_leaves_of_the_root() {
+        let (root, mut proof) = prove_non_membership(15, &LEAVES).unwrap();
+        assert!(verify_non_membership(16, &root, &proof).is_err());
+        assert!(verify_non_membership(15, &merkle_root(&[10, 20, 30, 50]).unwrap(), &proof).is_err());
+
+        // Skipping the leaf 20 with the neighbors 10 and 30
+        proof.hi = Some(neighbor(&LEAVES, 2).unwrap());
+        assert!(verify_non_membership(15, &root, &proof).is_err());
+
+        // Dropping a neighbor that isn't at the edge of the list
+        let (root, mut proof) = prove_non_membership(25, &LEAVES).unwrap();
+        proof.lo = None;
+        assert!(verify_non_membership(25, &root, &proof).is_err());
+    }
+}
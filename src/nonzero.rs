@@ -0,0 +1,137 @@
+use p3_air::{Air, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, Field};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::babybear_v1::{self, MyConfig, DEFAULT_FRI_PARAMS};
+use crate::error::Error;
+use crate::field::FieldKind;
+use crate::pair_bounded::{check_bound, eval_bounded, write_bounded, PAIR_BITS};
+
+const INV: usize = 2 * PAIR_BITS;
+const WIDTH: usize = INV + 1;
+
+// Proves `0 < value < bound`, with a single row:
+// | value bits | slack bits | inv |
+// The bounded check is the one of `pair_bounded`. `inv` is the inverse of the value, which only exists for a
+// nonzero value, so `value * inv == 1` rules out zero.
+// Public values are `[value, bound]`, and have to match the statement the AIR is built for.
+pub struct BabyBearNonzeroBoundedAir {
+    pub value: u32,
+    pub bound: u32,
+}
+
+impl<F: Field> BaseAir<F> for BabyBearNonzeroBoundedAir {
+    fn width(&self) -> usize {
+        WIDTH
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for BabyBearNonzeroBoundedAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+
+        let public_values = builder.public_values();
+        let value: AB::Expr = public_values[0].into();
+        let bound: AB::Expr = public_values[1].into();
+
+        builder.assert_eq(value.clone(), AB::Expr::from_canonical_u32(self.value));
+        builder.assert_eq(bound.clone(), AB::Expr::from_canonical_u32(self.bound));
+
+        eval_bounded(builder, &local[..INV], value.clone(), bound);
+        builder.assert_one(value * local[INV]);
+    }
+}
+
+// Requires `0 < value < bound`.
+pub fn generate_trace<F: Field>(value: u32, bound: u32) -> RowMajorMatrix<F> {
+    let mut row = vec![F::zero(); WIDTH];
+    write_bounded(&mut row[..INV], value, bound);
+    row[INV] = F::from_canonical_u32(value).inverse();
+    RowMajorMatrix::new(row, WIDTH)
+}
+
+fn public_values(value: u32, bound: u32) -> Vec<BabyBear> {
+    vec![BabyBear::from_canonical_u32(value), BabyBear::from_canonical_u32(bound)]
+}
+
+/// Proves that `value` is nonzero and below `bound`, e.g. before dividing by it or using it as a 1-based index.
+pub fn prove_nonzero_in_range(value: u32, bound: u32) -> Result<Proof<MyConfig>, Error> {
+    check_bound(bound)?;
+    if value == 0 || value >= bound {
+        return Err(Error::InvalidInput(format!("{} isn't in [1, {})", value, bound)));
+    }
+
+    let air = BabyBearNonzeroBoundedAir { value, bound };
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<BabyBear>(value, bound));
+    let config = babybear_v1::config(trace.height(), &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    Ok(info_span!("commit_trace")
+        .in_scope(|| p3_uni_stark::prove(&config, &air, &mut challenger, trace, &public_values(value, bound))))
+}
+
+pub fn verify_nonzero_in_range(value: u32, bound: u32, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    check_bound(bound)?;
+    // Values at or above the bound can't have a valid proof, but they still have to be canonical public values
+    if value >= bound {
+        return Err(Error::InvalidInput(format!("{} isn't below {}", value, bound)));
+    }
+    let air = BabyBearNonzeroBoundedAir { value, bound };
+    let config = babybear_v1::config(1, &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    p3_uni_stark::verify(&config, &air, &mut challenger, proof, &public_values(value, bound))
+        .map_err(|e| Error::verification_failed(FieldKind::BabyBear, u64::from(value), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::debug::satisfies;
+
+    fn satisfied(trace: &RowMajorMatrix<BabyBear>, value: u32, bound: u32) -> bool {
+        let air = BabyBearNonzeroBoundedAir { value, bound };
+        satisfies(&air, trace, &public_values(value, bound))
+    }
+
+    #[test]
+    fn nonzero_values_below_the_bound_pass() {
+        for (value, bound) in [(1, 2), (5, 10), ((1 << 29) - 1, 1 << 29)] {
+            assert!(satisfied(&generate_trace(value, bound), value, bound));
+        }
+    }
+
+    #[test]
+    fn zero_has_no_inverse() {
+        // Zero is in range, so only the inverse column can reject it, and no value of it works
+        let mut trace = generate_trace::<BabyBear>(1, 10);
+        write_bounded(&mut trace.values[..INV], 0, 10);
+        for inv in [BabyBear::zero(), BabyBear::one(), BabyBear::two()] {
+            trace.values[INV] = inv;
+            assert!(!satisfied(&trace, 0, 10));
+        }
+    }
+
+    #[test]
+    fn inverse_has_to_match_the_value() {
+        let mut trace = generate_trace::<BabyBear>(5, 10);
+        trace.values[INV] = BabyBear::from_canonical_u32(4).inverse();
+        assert!(!satisfied(&trace, 5, 10));
+    }
+
+    #[test]
+    fn proof_verifies_for_the_value_and_bound() {
+        let proof = prove_nonzero_in_range(5, 10).unwrap();
+        verify_nonzero_in_range(5, 10, &proof).unwrap();
+        assert!(verify_nonzero_in_range(6, 10, &proof).is_err());
+        assert!(verify_nonzero_in_range(5, 11, &proof).is_err());
+        assert!(matches!(prove_nonzero_in_range(0, 10), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_nonzero_in_range(10, 10), Err(Error::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,172 @@
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, Field};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::babybear_v1::{self, MyConfig, DEFAULT_FRI_PARAMS};
+use crate::bits::{reconstruct, write_bits};
+use crate::error::Error;
+use crate::field::FieldKind;
+
+// Bit width of the values and the slacks. With `bound <= 2^PAIR_BITS`, an honest slack fits in the bits, while a
+// negative one wraps around the modulus to at least `p - 2^29 - 1 > 2^30` and can't be decomposed.
+pub const PAIR_BITS: usize = 29;
+
+const WIDTH: usize = 4 * PAIR_BITS;
+
+// Proves `a < bound` and `b < bound`, with a single row:
+// | a bits | a slack bits | b bits | b slack bits |
+// Every slack is `bound - 1 - value`. The two comparisons only share `bound`, each has its own columns.
+// Public values are `[a, b, bound]`, and have to match the statement the AIR is built for.
+pub struct BabyBearPairBoundedAir {
+    pub a: u32,
+    pub b: u32,
+    pub bound: u32,
+}
+
+impl<F: Field> BaseAir<F> for BabyBearPairBoundedAir {
+    fn width(&self) -> usize {
+        WIDTH
+    }
+}
+
+// Constrains `value < bound` on `2 * PAIR_BITS` columns, the value bits followed by the slack bits.
+pub(crate) fn eval_bounded<AB: AirBuilder>(builder: &mut AB, columns: &[AB::Var], value: AB::Expr, bound: AB::Expr) {
+    // Making sure every bit is either 0 or 1
+    for &bit in columns {
+        builder.assert_bool(bit);
+    }
+
+    let reconstructed_value = reconstruct::<AB>(&columns[..PAIR_BITS]);
+    let slack = reconstruct::<AB>(&columns[PAIR_BITS..]);
+    builder.assert_eq(reconstructed_value.clone(), value);
+    builder.assert_eq(slack, bound - AB::Expr::one() - reconstructed_value);
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for BabyBearPairBoundedAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+
+        let public_values = builder.public_values();
+        let a: AB::Expr = public_values[0].into();
+        let b: AB::Expr = public_values[1].into();
+        let bound: AB::Expr = public_values[2].into();
+
+        builder.assert_eq(a.clone(), AB::Expr::from_canonical_u32(self.a));
+        builder.assert_eq(b.clone(), AB::Expr::from_canonical_u32(self.b));
+        builder.assert_eq(bound.clone(), AB::Expr::from_canonical_u32(self.bound));
+
+        eval_bounded(builder, &local[..2 * PAIR_BITS], a, bound.clone());
+        eval_bounded(builder, &local[2 * PAIR_BITS..], b, bound);
+    }
+}
+
+pub(crate) fn write_bounded<F: Field>(columns: &mut [F], value: u32, bound: u32) {
+    write_bits(&mut columns[..PAIR_BITS], u64::from(value));
+    write_bits(&mut columns[PAIR_BITS..], u64::from(bound - 1 - value));
+}
+
+// Requires `a < bound` and `b < bound`.
+pub fn generate_trace<F: Field>(a: u32, b: u32, bound: u32) -> RowMajorMatrix<F> {
+    let mut row = vec![F::zero(); WIDTH];
+    write_bounded(&mut row[..2 * PAIR_BITS], a, bound);
+    write_bounded(&mut row[2 * PAIR_BITS..], b, bound);
+    RowMajorMatrix::new(row, WIDTH)
+}
+
+fn public_values(a: u32, b: u32, bound: u32) -> Vec<BabyBear> {
+    vec![BabyBear::from_canonical_u32(a), BabyBear::from_canonical_u32(b), BabyBear::from_canonical_u32(bound)]
+}
+
+pub(crate) fn check_bound(bound: u32) -> Result<(), Error> {
+    if bound > 1 << PAIR_BITS {
+        return Err(Error::InvalidInput(format!("bound {} is larger than 2^{}", bound, PAIR_BITS)));
+    }
+    Ok(())
+}
+
+/// Proves that both `a` and `b` are below `bound`, in one trace commitment and transcript.
+pub fn prove_pair_bounded(a: u32, b: u32, bound: u32) -> Result<Proof<MyConfig>, Error> {
+    check_bound(bound)?;
+    for value in [a, b] {
+        if value >= bound {
+            return Err(Error::InvalidInput(format!("{} isn't below {}", value, bound)));
+        }
+    }
+
+    let air = BabyBearPairBoundedAir { a, b, bound };
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<BabyBear>(a, b, bound));
+    let config = babybear_v1::config(trace.height(), &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    let public_values = public_values(a, b, bound);
+    Ok(info_span!("commit_trace")
+        .in_scope(|| p3_uni_stark::prove(&config, &air, &mut challenger, trace, &public_values)))
+}
+
+pub fn verify_pair_bounded(a: u32, b: u32, bound: u32, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    check_bound(bound)?;
+    // Values at or above the bound can't have a valid proof, but they still have to be canonical public values
+    if a >= bound || b >= bound {
+        return Err(Error::InvalidInput(format!("({}, {}) isn't below {}", a, b, bound)));
+    }
+    let air = BabyBearPairBoundedAir { a, b, bound };
+    let config = babybear_v1::config(1, &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    p3_uni_stark::verify(&config, &air, &mut challenger, proof, &public_values(a, b, bound))
+        .map_err(|e| Error::verification_failed(FieldKind::BabyBear, u64::from(a), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p3_field::PrimeField32;
+
+    use crate::debug::satisfies;
+
+    fn satisfied(trace: &RowMajorMatrix<BabyBear>, a: u32, b: u32, bound: u32) -> bool {
+        let air = BabyBearPairBoundedAir { a, b, bound };
+        satisfies(&air, trace, &public_values(a, b, bound))
+    }
+
+    // The trace for any claim, with the slack wrapped around the modulus when the value isn't below the bound.
+    fn forged_trace(a: u32, b: u32, bound: u32) -> RowMajorMatrix<BabyBear> {
+        let mut row = vec![BabyBear::zero(); WIDTH];
+        for (columns, value) in row.chunks_mut(2 * PAIR_BITS).zip([a, b]) {
+            let slack = BabyBear::from_canonical_u32(bound) - BabyBear::one() - BabyBear::from_canonical_u32(value);
+            write_bits(&mut columns[..PAIR_BITS], u64::from(value));
+            write_bits(&mut columns[PAIR_BITS..], u64::from(slack.as_canonical_u32()));
+        }
+        RowMajorMatrix::new(row, WIDTH)
+    }
+
+    #[test]
+    fn values_below_the_bound_pass() {
+        for (a, b, bound) in [(0, 0, 1), (3, 9, 10), (9, 3, 10), ((1 << 29) - 1, 0, 1 << 29)] {
+            assert!(satisfied(&generate_trace(a, b, bound), a, b, bound));
+        }
+    }
+
+    #[test]
+    fn either_value_at_the_bound_fails() {
+        assert!(!satisfied(&forged_trace(10, 3, 10), 10, 3, 10));
+        assert!(!satisfied(&forged_trace(3, 10, 10), 3, 10, 10));
+        // A valid comparison for `a` doesn't carry over to `b`
+        assert!(!satisfied(&forged_trace(0, (1 << 29) - 1, 5), 0, (1 << 29) - 1, 5));
+    }
+
+    #[test]
+    fn proof_verifies_for_the_pair() {
+        let proof = prove_pair_bounded(3, 9, 10).unwrap();
+        verify_pair_bounded(3, 9, 10, &proof).unwrap();
+        assert!(verify_pair_bounded(9, 3, 10, &proof).is_err());
+        assert!(verify_pair_bounded(3, 9, 11, &proof).is_err());
+        assert!(matches!(prove_pair_bounded(3, 10, 10), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_pair_bounded(3, 9, (1 << 29) + 1), Err(Error::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,114 @@
+use std::fmt;
+
+use crate::config::FriParams;
+use crate::field::FieldKind;
+use crate::modulus::{BABYBEAR, KOALABEAR};
+
+// Bytes of a Keccak digest, for the Merkle tree nodes.
+const DIGEST_BYTES: u64 = 32;
+
+// Rough single threaded costs of the proving steps, only their order of magnitude matters.
+const NS_PER_BUTTERFLY: u64 = 2;
+const NS_PER_HASHED_BYTE: u64 = 2;
+const NS_PER_HASH: u64 = 500;
+
+/// Rough resources needed to prove a batch, estimated from the trace shape and the FRI parameters without proving.
+///
+/// The trace is modelled as one row per value, like the single value AIRs stacked and padded to a power of two.
+/// Memory is dominated by the low degree extension of the trace and its Merkle tree, time by the DFT, hashing the
+/// extension and the proof of work grinding.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResourcePlan {
+    // Cells of the trace before the low degree extension, padding rows included.
+    pub trace_cells: u64,
+    pub est_memory_bytes: u64,
+    pub est_prove_ms: u64,
+}
+
+// Columns of the range check row of `field`, the bits and the AND columns of the modulus boundary if it has any.
+fn trace_width(field: FieldKind) -> u64 {
+    let width = match field {
+        FieldKind::Mersenne31 => 32,
+        FieldKind::BabyBear => 32 + BABYBEAR.top_ones - 1,
+        FieldKind::Goldilocks => 64,
+        FieldKind::KoalaBear => 32 + KOALABEAR.top_ones - 1,
+    };
+    width as u64
+}
+
+fn element_bytes(field: FieldKind) -> u64 {
+    match field {
+        FieldKind::Goldilocks => 8,
+        _ => 4,
+    }
+}
+
+/// Estimates the resources to prove `batch_size` values of `field` with `params`.
+pub fn plan(field: FieldKind, batch_size: usize, params: &FriParams) -> ResourcePlan {
+    let height = batch_size.max(1).next_power_of_two() as u64;
+    let width = trace_width(field);
+    let trace_cells = height * width;
+
+    let lde_rows = height << params.log_blowup;
+    let lde_bytes = lde_rows * width * element_bytes(field);
+    let log_lde_rows = u64::from(lde_rows.trailing_zeros());
+
+    // The trace, its extension and a Merkle tree with one leaf per extended row.
+    let est_memory_bytes = trace_cells * element_bytes(field) + lde_bytes + 2 * lde_rows * DIGEST_BYTES;
+
+    let dft_ns = lde_rows * width * log_lde_rows.max(1) * NS_PER_BUTTERFLY;
+    let commit_ns = lde_bytes * NS_PER_HASHED_BYTE + 2 * lde_rows * NS_PER_HASH;
+    let query_ns = params.num_queries as u64 * log_lde_rows * NS_PER_HASH;
+    let grinding_ns = (1u64 << params.proof_of_work_bits) * NS_PER_HASH;
+    let est_prove_ms = (dft_ns + commit_ns + query_ns + grinding_ns).div_ceil(1_000_000);
+
+    ResourcePlan { trace_cells, est_memory_bytes, est_prove_ms }
+}
+
+impl fmt::Display for ResourcePlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} trace cells, ~{} bytes of memory, ~{} ms to prove",
+            self.trace_cells, self.est_memory_bytes, self.est_prove_ms
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARAMS: FriParams = FriParams { log_blowup: 1, num_queries: 100, proof_of_work_bits: 16 };
+
+    #[test]
+    fn plan_scales_with_the_batch_size() {
+        for &field in FieldKind::all() {
+            let small = plan(field, 1 << 10, &PARAMS);
+            let large = plan(field, 1 << 11, &PARAMS);
+            assert_eq!(large.trace_cells, 2 * small.trace_cells);
+            assert_eq!(large.est_memory_bytes, 2 * small.est_memory_bytes);
+            assert!(large.est_prove_ms >= small.est_prove_ms);
+
+            // Padded to the next power of two
+            assert_eq!(plan(field, (1 << 10) + 1, &PARAMS), large);
+        }
+    }
+
+    #[test]
+    fn plan_scales_with_the_blowup() {
+        let wider = FriParams { log_blowup: 3, ..PARAMS };
+        let plan_1 = plan(FieldKind::Goldilocks, 1 << 16, &PARAMS);
+        let plan_3 = plan(FieldKind::Goldilocks, 1 << 16, &wider);
+        assert_eq!(plan_1.trace_cells, plan_3.trace_cells);
+        assert!(plan_3.est_memory_bytes > 2 * plan_1.est_memory_bytes);
+        assert!(plan_3.est_prove_ms > plan_1.est_prove_ms);
+    }
+
+    #[test]
+    fn goldilocks_needs_more_than_babybear() {
+        let babybear = plan(FieldKind::BabyBear, 1 << 12, &PARAMS);
+        let goldilocks = plan(FieldKind::Goldilocks, 1 << 12, &PARAMS);
+        assert!(goldilocks.est_memory_bytes > babybear.est_memory_bytes);
+    }
+}
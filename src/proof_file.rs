@@ -0,0 +1,89 @@
+use std::fs;
+use std::path::Path;
+
+use p3_uni_stark::{Proof, StarkGenericConfig};
+
+use crate::config::FriParams;
+use crate::error::Error;
+use crate::field::FieldKind;
+use crate::serialize;
+
+// Identifies a proof file.
+pub const MAGIC: [u8; 4] = *b"P3RC";
+// Bumped on every change to the layout after the version, readers reject versions they don't know.
+pub const VERSION: u16 = 1;
+
+const PREAMBLE_LEN: usize = MAGIC.len() + 2;
+
+// A proof file is `[magic][version: u16 LE]` followed by the tagged proof of `serialize::serialize_tagged`,
+// whose `(FieldKind, FriParams)` header comes before the proof body.
+pub fn write_proof<SC: StarkGenericConfig>(
+    path: impl AsRef<Path>,
+    field: FieldKind,
+    params: &FriParams,
+    proof: &Proof<SC>,
+) -> Result<(), Error> {
+    let mut bytes = Vec::from(MAGIC);
+    bytes.extend(VERSION.to_le_bytes());
+    bytes.extend(serialize::serialize_tagged(field, params, proof)?);
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+// Checks the magic and version and returns the tagged proof after them.
+fn body(bytes: &[u8]) -> Result<&[u8], Error> {
+    if bytes.len() < PREAMBLE_LEN || bytes[..MAGIC.len()] != MAGIC {
+        return Err(Error::Serialization("not a proof file".to_string()));
+    }
+    let version = u16::from_le_bytes([bytes[MAGIC.len()], bytes[MAGIC.len() + 1]]);
+    if version != VERSION {
+        return Err(Error::UnsupportedVersion { found: version, supported: VERSION });
+    }
+    Ok(&bytes[PREAMBLE_LEN..])
+}
+
+/// Reads the field and FRI parameters of a proof file, to pick the proof type for `read_proof`.
+pub fn read_proof_header(path: impl AsRef<Path>) -> Result<(FieldKind, FriParams), Error> {
+    serialize::read_header(body(&fs::read(path)?)?)
+}
+
+/// Reads a proof written by `write_proof`. Returns `Error::UnsupportedVersion` for files of another format version.
+pub fn read_proof<SC: StarkGenericConfig>(path: impl AsRef<Path>) -> Result<(FieldKind, FriParams, Proof<SC>), Error> {
+    serialize::deserialize_tagged(body(&fs::read(path)?)?)
+}
+
+#[cfg(all(test, feature = "babybear"))]
+mod tests {
+    use super::*;
+    use crate::babybear_v1;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("plonky3_rangecheck_{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn proofs_round_trip_through_a_file() {
+        let path = temp_path("round_trip");
+        let params = babybear_v1::DEFAULT_FRI_PARAMS;
+        write_proof(&path, FieldKind::BabyBear, &params, &babybear_v1::prove(100)).unwrap();
+
+        assert_eq!(read_proof_header(&path).unwrap(), (FieldKind::BabyBear, params));
+        let (field, read_params, proof) = read_proof::<babybear_v1::MyConfig>(&path).unwrap();
+        assert_eq!((field, read_params), (FieldKind::BabyBear, params));
+        assert!(babybear_v1::verify(100, &proof).is_ok());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn unknown_versions_are_rejected() {
+        let path = temp_path("version");
+        write_proof(&path, FieldKind::BabyBear, &babybear_v1::DEFAULT_FRI_PARAMS, &babybear_v1::prove(100)).unwrap();
+        let mut bytes = fs::read(&path).unwrap();
+        bytes[MAGIC.len()..PREAMBLE_LEN].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        fs::write(&path, bytes).unwrap();
+
+        let result = read_proof::<babybear_v1::MyConfig>(&path);
+        assert!(matches!(result, Err(Error::UnsupportedVersion { found, .. }) if found == VERSION + 1));
+        fs::remove_file(path).unwrap();
+    }
+}
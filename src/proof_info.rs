@@ -0,0 +1,205 @@
+use std::fmt;
+use std::time::Instant;
+
+use p3_uni_stark::{Proof, StarkGenericConfig};
+use serde_json::Value;
+
+use crate::config::FriParams;
+use crate::error::Error;
+
+/// Size and shape of a proof, read from the proof alone without verifying it.
+///
+/// The fields of `p3_uni_stark::Proof` aren't public, so the shape is read from its serde representation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofInfo {
+    // Size of the bincode serialization, without the header `serialize::serialize_tagged` adds.
+    pub size_bytes: u64,
+    // Number of Merkle commitments to the trace and quotient.
+    pub commitments: usize,
+    // Number of FRI query rounds in the opening proof.
+    pub fri_queries: usize,
+    // Log2 of the trace height.
+    pub degree_bits: usize,
+}
+
+impl ProofInfo {
+    pub fn from_proof<SC: StarkGenericConfig>(proof: &Proof<SC>) -> Result<Self, Error> {
+        let size_bytes = bincode::serialized_size(proof).map_err(|e| Error::Serialization(e.to_string()))?;
+        let value = serde_json::to_value(proof).map_err(|e| Error::Serialization(e.to_string()))?;
+
+        let missing = |field: &str| Error::Serialization(format!("proof has no `{}`", field));
+        let commitments = value
+            .get("commitments")
+            .and_then(Value::as_object)
+            .map(|commitments| commitments.len())
+            .ok_or_else(|| missing("commitments"))?;
+        let degree_bits = value
+            .get("degree_bits")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| missing("degree_bits"))? as usize;
+        // The FRI proof is nested differently by the two-adic and circle PCS, so it is searched for.
+        let fri_queries = find(&value, "query_proofs")
+            .and_then(Value::as_array)
+            .map(|queries| queries.len())
+            .ok_or_else(|| missing("query_proofs"))?;
+
+        Ok(Self { size_bytes, commitments, fri_queries, degree_bits })
+    }
+}
+
+/// The proof of work grinding of a proof, the FRI step that hashes until the transcript has `proof_of_work_bits`
+/// leading zero bits.
+///
+/// p3 grinds with a parallel `find_any` over all nonces, so the number of hashes tried isn't observable. Every hash
+/// succeeds with probability `2^-proof_of_work_bits`, so `expected_hashes` is the mean and proving time varies with it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProofStats {
+    // The nonce the prover found, as a canonical field element.
+    pub pow_witness: u64,
+    pub proof_of_work_bits: usize,
+    pub expected_hashes: u64,
+}
+
+impl ProofStats {
+    // `params` has to be the parameters the proof was made with, the proof doesn't record them.
+    pub fn from_proof<SC: StarkGenericConfig>(proof: &Proof<SC>, params: &FriParams) -> Result<Self, Error> {
+        let value = serde_json::to_value(proof).map_err(|e| Error::Serialization(e.to_string()))?;
+        let pow_witness = find(&value, "pow_witness")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| Error::Serialization("proof has no `pow_witness`".to_string()))?;
+
+        Ok(Self {
+            pow_witness,
+            proof_of_work_bits: params.proof_of_work_bits,
+            expected_hashes: 1 << params.proof_of_work_bits,
+        })
+    }
+}
+
+impl fmt::Display for ProofStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "PoW nonce {} for {} bits, {} hashes expected",
+            self.pow_witness, self.proof_of_work_bits, self.expected_hashes
+        )
+    }
+}
+
+/// Proof size and wall clock times of a prove and verify run, for comparing fields and parameters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProveStats {
+    // Size of the bincode serialization of the proof.
+    pub proof_bytes: usize,
+    pub prove_ms: u128,
+    pub verify_ms: u128,
+}
+
+impl ProveStats {
+    /// Times `prove`, then `verify` on its proof.
+    pub fn measure<SC: StarkGenericConfig>(
+        prove: impl FnOnce() -> Result<Proof<SC>, Error>,
+        verify: impl FnOnce(&Proof<SC>) -> Result<(), Error>,
+    ) -> Result<Self, Error> {
+        let start = Instant::now();
+        let proof = prove()?;
+        let prove_ms = start.elapsed().as_millis();
+
+        let start = Instant::now();
+        verify(&proof)?;
+        let verify_ms = start.elapsed().as_millis();
+
+        let proof_bytes = bincode::serialized_size(&proof).map_err(|e| Error::Serialization(e.to_string()))? as usize;
+        Ok(Self { proof_bytes, prove_ms, verify_ms })
+    }
+}
+
+impl fmt::Display for ProveStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} bytes, proved in {} ms, verified in {} ms", self.proof_bytes, self.prove_ms, self.verify_ms)
+    }
+}
+
+// Depth first search for the first field named `key`.
+pub(crate) fn find<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+    match value {
+        Value::Object(fields) => fields
+            .get(key)
+            .or_else(|| fields.values().find_map(|field| find(field, key))),
+        Value::Array(items) => items.iter().find_map(|item| find(item, key)),
+        _ => None,
+    }
+}
+
+impl fmt::Display for ProofInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} bytes, {} commitments, {} FRI queries, trace height 2^{}",
+            self.size_bytes, self.commitments, self.fri_queries, self.degree_bits
+        )
+    }
+}
+
+#[cfg(all(test, feature = "babybear", feature = "goldilocks", feature = "mersenne31"))]
+mod tests {
+    use super::*;
+    use crate::{babybear_v1, goldilocks_v1, m31};
+
+    #[test]
+    fn info_matches_the_prover_parameters() {
+        let info = ProofInfo::from_proof(&babybear_v1::prove(100)).unwrap();
+        assert_eq!(info.fri_queries, babybear_v1::DEFAULT_FRI_PARAMS.num_queries);
+        assert_eq!(info.commitments, 2);
+        assert_eq!(info.degree_bits, 0);
+
+        let info = ProofInfo::from_proof(&goldilocks_v1::prove(100)).unwrap();
+        assert_eq!(info.fri_queries, goldilocks_v1::DEFAULT_FRI_PARAMS.num_queries);
+
+        let info = ProofInfo::from_proof(&m31::prove(100)).unwrap();
+        assert_eq!(info.fri_queries, m31::DEFAULT_FRI_PARAMS.num_queries);
+        assert_eq!(info.degree_bits, 2);
+    }
+
+    #[test]
+    fn stats_report_the_pow_witness() {
+        let params = babybear_v1::DEFAULT_FRI_PARAMS;
+        let stats = ProofStats::from_proof(&babybear_v1::prove(100), &params).unwrap();
+        assert_eq!(stats.proof_of_work_bits, params.proof_of_work_bits);
+        assert_eq!(stats.expected_hashes, 1 << params.proof_of_work_bits);
+
+        // A different witness doesn't pass the grinding check, so the proof no longer verifies.
+        let proof = babybear_v1::prove(100);
+        let mut value = serde_json::to_value(&proof).unwrap();
+        let witness = find_mut(&mut value, "pow_witness").unwrap();
+        *witness = (witness.as_u64().unwrap() + 1).into();
+        let tampered = serde_json::from_value(value).unwrap();
+        assert!(babybear_v1::verify(100, &tampered).is_err());
+    }
+
+    #[test]
+    fn prove_stats_measure_the_serialized_proof() {
+        let stats = babybear_v1::prove_and_verify_with_stats(100).unwrap();
+        let size = bincode::serialized_size(&babybear_v1::prove(100)).unwrap() as usize;
+        assert_eq!(stats.proof_bytes, size);
+        assert_eq!(stats.proof_bytes as u64, ProofInfo::from_proof(&babybear_v1::prove(100)).unwrap().size_bytes);
+
+        let failed = ProveStats::measure(|| Ok(babybear_v1::prove(100)), |_| Err(Error::Cancelled));
+        assert!(matches!(failed, Err(Error::Cancelled)));
+        assert!(m31::prove_and_verify_with_stats(100).unwrap().proof_bytes > 0);
+        assert!(goldilocks_v1::prove_and_verify_with_stats(100).unwrap().proof_bytes > 0);
+    }
+
+    fn find_mut<'a>(value: &'a mut Value, key: &str) -> Option<&'a mut Value> {
+        match value {
+            Value::Object(fields) => {
+                if fields.contains_key(key) {
+                    return fields.get_mut(key);
+                }
+                fields.values_mut().find_map(|field| find_mut(field, key))
+            }
+            Value::Array(items) => items.iter_mut().find_map(|item| find_mut(item, key)),
+            _ => None,
+        }
+    }
+}
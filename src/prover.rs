@@ -0,0 +1,199 @@
+use p3_field::Field;
+use p3_uni_stark::{Proof, StarkGenericConfig};
+use tracing::info_span;
+
+use crate::config::FriParams;
+use crate::error::Error;
+use crate::field::{check_value_in_field, FieldKind};
+use crate::proof_info::{ProofStats, ProveStats};
+#[cfg(any(feature = "mersenne31", feature = "babybear", feature = "koalabear"))]
+use crate::serialize::to_u32;
+use crate::timings;
+
+/// A field with a range check prover, `prove_and_verify::<F>` runs the same logic on every implementation.
+///
+/// `Config` bundles what differs between the fields besides the AIR: the challenge extension and its degree, the
+/// DFT, and the hashes of the Merkle trees and the challenger. A new field implements `prove` and `verify` with its
+/// AIR and config, and gets `prove_and_verify` for free. The provers behind the implementations are
+/// `m31`, `babybear_v1`, `goldilocks_v1` and `koalabear`.
+pub trait RangeCheckField: Field {
+    const KIND: FieldKind;
+    const DEFAULT_FRI_PARAMS: FriParams;
+
+    type Config: StarkGenericConfig;
+
+    /// Returns `Error::ValueOutOfField` for values that don't fit the prover's input type.
+    fn prove(value: u64, params: &FriParams) -> Result<Proof<Self::Config>, Error>;
+
+    fn verify(value: u64, proof: &Proof<Self::Config>, params: &FriParams) -> Result<(), Error>;
+}
+
+#[cfg(feature = "mersenne31")]
+impl RangeCheckField for p3_mersenne_31::Mersenne31 {
+    const KIND: FieldKind = FieldKind::Mersenne31;
+    const DEFAULT_FRI_PARAMS: FriParams = crate::m31::DEFAULT_FRI_PARAMS;
+
+    type Config = crate::m31::MyConfig;
+
+    fn prove(value: u64, params: &FriParams) -> Result<Proof<Self::Config>, Error> {
+        Ok(crate::m31::prove_with_params(to_u32(Self::KIND, value)?, params))
+    }
+
+    fn verify(value: u64, proof: &Proof<Self::Config>, params: &FriParams) -> Result<(), Error> {
+        check_value_in_field(Self::KIND, value)?;
+        crate::m31::verify_with_params(to_u32(Self::KIND, value)?, proof, params)
+    }
+}
+
+#[cfg(feature = "babybear")]
+impl RangeCheckField for p3_baby_bear::BabyBear {
+    const KIND: FieldKind = FieldKind::BabyBear;
+    const DEFAULT_FRI_PARAMS: FriParams = crate::babybear_v1::DEFAULT_FRI_PARAMS;
+
+    type Config = crate::babybear_v1::MyConfig;
+
+    fn prove(value: u64, params: &FriParams) -> Result<Proof<Self::Config>, Error> {
+        Ok(crate::babybear_v1::prove_with_params(to_u32(Self::KIND, value)?, params))
+    }
+
+    fn verify(value: u64, proof: &Proof<Self::Config>, params: &FriParams) -> Result<(), Error> {
+        check_value_in_field(Self::KIND, value)?;
+        crate::babybear_v1::verify_with_params(to_u32(Self::KIND, value)?, proof, params)
+    }
+}
+
+#[cfg(feature = "goldilocks")]
+impl RangeCheckField for p3_goldilocks::Goldilocks {
+    const KIND: FieldKind = FieldKind::Goldilocks;
+    const DEFAULT_FRI_PARAMS: FriParams = crate::goldilocks_v1::DEFAULT_FRI_PARAMS;
+
+    type Config = crate::goldilocks_v1::MyConfig;
+
+    fn prove(value: u64, params: &FriParams) -> Result<Proof<Self::Config>, Error> {
+        Ok(crate::goldilocks_v1::prove_with_params(value, params))
+    }
+
+    fn verify(value: u64, proof: &Proof<Self::Config>, params: &FriParams) -> Result<(), Error> {
+        check_value_in_field(Self::KIND, value)?;
+        crate::goldilocks_v1::verify_with_params(value, proof, params)
+    }
+}
+
+#[cfg(feature = "koalabear")]
+impl RangeCheckField for p3_koala_bear::KoalaBear {
+    const KIND: FieldKind = FieldKind::KoalaBear;
+    const DEFAULT_FRI_PARAMS: FriParams = crate::koalabear::DEFAULT_FRI_PARAMS;
+
+    type Config = crate::koalabear::MyConfig;
+
+    fn prove(value: u64, params: &FriParams) -> Result<Proof<Self::Config>, Error> {
+        Ok(crate::koalabear::prove_with_params(to_u32(Self::KIND, value)?, params))
+    }
+
+    fn verify(value: u64, proof: &Proof<Self::Config>, params: &FriParams) -> Result<(), Error> {
+        check_value_in_field(Self::KIND, value)?;
+        crate::koalabear::verify_with_params(to_u32(Self::KIND, value)?, proof, params)
+    }
+}
+
+/// Proves and verifies `value` over `F` with its default FRI parameters, and returns the proof of work stats of
+/// the proof, which are also logged.
+pub fn prove_and_verify<F: RangeCheckField>(value: u64) -> Result<ProofStats, Error> {
+    prove_and_verify_with_params::<F>(value, &F::DEFAULT_FRI_PARAMS)
+}
+
+pub fn prove_and_verify_with_params<F: RangeCheckField>(value: u64, params: &FriParams) -> Result<ProofStats, Error> {
+    timings::init_tracing();
+
+    let proof = info_span!("prove").in_scope(|| F::prove(value, params))?;
+    info_span!("verify").in_scope(|| F::verify(value, &proof, params))?;
+
+    let stats = ProofStats::from_proof(&proof, params)?;
+    tracing::info!("{}", stats);
+    Ok(stats)
+}
+
+/// Proves and verifies `value` over `F` with its default FRI parameters, and returns the proof size and the
+/// proving and verifying times.
+pub fn prove_and_verify_with_stats<F: RangeCheckField>(value: u64) -> Result<ProveStats, Error> {
+    let params = F::DEFAULT_FRI_PARAMS;
+    ProveStats::measure(|| F::prove(value, &params), |proof| F::verify(value, proof, &params))
+}
+
+/// Whether `value` is in range of `F`, by proving and verifying it with the default FRI parameters.
+///
+/// Never panics, values outside the field are `false` without proving, and doesn't install the tracing subscriber.
+pub fn check<F: RangeCheckField>(value: u64) -> bool {
+    let params = F::DEFAULT_FRI_PARAMS;
+    check_value_in_field(F::KIND, value).is_ok()
+        && F::prove(value, &params).and_then(|proof| F::verify(value, &proof, &params)).is_ok()
+}
+
+#[cfg(all(test, feature = "babybear", feature = "goldilocks"))]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use p3_field::{PrimeField32, PrimeField64};
+    use p3_goldilocks::Goldilocks;
+
+    use super::*;
+
+    fn kind<F: RangeCheckField>() -> FieldKind {
+        F::KIND
+    }
+
+    #[test]
+    fn generic_prover_accepts_several_fields() {
+        assert_eq!(kind::<BabyBear>(), FieldKind::BabyBear);
+        assert_eq!(kind::<Goldilocks>(), FieldKind::Goldilocks);
+
+        prove_and_verify::<BabyBear>(100).unwrap();
+        prove_and_verify::<Goldilocks>(1 << 40).unwrap();
+        assert!(matches!(prove_and_verify::<BabyBear>(1 << 40), Err(Error::ValueOutOfField { .. })));
+    }
+
+    #[test]
+    fn check_returns_false_instead_of_panicking() {
+        assert!(check::<BabyBear>(100));
+        assert!(!check::<BabyBear>(u64::from(BabyBear::ORDER_U32)));
+        assert!(!check::<BabyBear>(1 << 40));
+        assert!(check::<Goldilocks>(Goldilocks::ORDER_U64 - 1));
+        assert!(!check::<Goldilocks>(u64::MAX));
+
+        assert!(crate::babybear_v2::check(100));
+        assert!(!crate::babybear_v2::check(1 << 40));
+        assert!(!crate::goldilocks_v2::check(Goldilocks::ORDER_U64));
+    }
+
+    // A proof of `v` mustn't verify for `order + v`, which wraps to the same field element.
+    fn assert_wrapped_value_is_rejected<F: RangeCheckField>(order: u64) {
+        let proof = F::prove(5, &F::DEFAULT_FRI_PARAMS).unwrap();
+        assert!(F::verify(5, &proof, &F::DEFAULT_FRI_PARAMS).is_ok());
+        let wrapped = F::verify(order + 5, &proof, &F::DEFAULT_FRI_PARAMS);
+        assert!(matches!(wrapped, Err(Error::ValueOutOfField { .. })));
+    }
+
+    #[test]
+    fn values_past_the_field_order_are_rejected() {
+        assert_wrapped_value_is_rejected::<BabyBear>(u64::from(BabyBear::ORDER_U32));
+        assert_wrapped_value_is_rejected::<Goldilocks>(Goldilocks::ORDER_U64);
+        #[cfg(feature = "mersenne31")]
+        {
+            use p3_mersenne_31::Mersenne31;
+            assert_wrapped_value_is_rejected::<Mersenne31>(u64::from(Mersenne31::ORDER_U32));
+        }
+        #[cfg(feature = "koalabear")]
+        {
+            use p3_koala_bear::KoalaBear;
+            assert_wrapped_value_is_rejected::<KoalaBear>(u64::from(KoalaBear::ORDER_U32));
+        }
+
+        let proof = crate::babybear_v1::prove(5);
+        assert!(crate::babybear_v1::verify(BabyBear::ORDER_U32 + 5, &proof).is_err());
+        let proof = crate::goldilocks_v1::prove(5);
+        assert!(crate::goldilocks_v1::verify(Goldilocks::ORDER_U64 + 5, &proof).is_err());
+        let proof = crate::babybear_v2::prove(5);
+        assert!(crate::babybear_v2::verify(BabyBear::ORDER_U32 + 5, &proof).is_err());
+        let proof = crate::goldilocks_v2::prove(5);
+        assert!(crate::goldilocks_v2::verify(Goldilocks::ORDER_U64 + 5, &proof).is_err());
+    }
+}
@@ -0,0 +1,72 @@
+use std::fmt::Write;
+use std::time::Instant;
+
+use crate::debug::verify_trace;
+use crate::error::Error;
+use crate::field::{max_provable_value, value_bits, FieldKind};
+use crate::serialize;
+
+/// Proves and verifies `value` over `field` with the default FRI parameters and describes every step:
+/// the modulus, the bit decomposition of the trace, the first constraint that fails if any, the proof size
+/// and the proving and verification times. Values the constraints reject aren't proven, the report says why.
+pub fn prove_and_verify_with_report(field: FieldKind, value: u64) -> Result<String, Error> {
+    let params = serialize::default_params(field)?;
+    let bits: String = value_bits(field, value).iter().map(|&bit| if bit { '1' } else { '0' }).collect();
+
+    let mut report = String::new();
+    writeln!(report, "field:       {:?}", field).unwrap();
+    writeln!(report, "modulus:     {}", max_provable_value(field) + 1).unwrap();
+    writeln!(report, "value:       {}", value).unwrap();
+    writeln!(report, "bits:        {}", bits).unwrap();
+    writeln!(report, "parameters:  {:?}", params).unwrap();
+
+    match verify_trace(field, value) {
+        Ok(()) => writeln!(report, "constraints: all hold").unwrap(),
+        Err(Error::ConstraintViolated(violation)) => {
+            let (constraint, row) = (violation.constraint, violation.row);
+            writeln!(report, "constraints: constraint {} fails on row {}", constraint, row).unwrap();
+            writeln!(report, "result:      not proven, {} is out of range", value).unwrap();
+            return Ok(report);
+        }
+        Err(e) => return Err(e),
+    }
+
+    let start = Instant::now();
+    let bytes = serialize::prove_with_params(field, value, &params)?;
+    let prove_time = start.elapsed();
+
+    let start = Instant::now();
+    let verified = serialize::verify_with_params(field, &bytes, value, &params);
+    let verify_time = start.elapsed();
+
+    writeln!(report, "proof size:  {} bytes", bytes.len()).unwrap();
+    writeln!(report, "prove time:  {:?}", prove_time).unwrap();
+    writeln!(report, "verify time: {:?}", verify_time).unwrap();
+    match verified {
+        Ok(()) => writeln!(report, "result:      verified").unwrap(),
+        Err(e) => writeln!(report, "result:      {}", e).unwrap(),
+    }
+    Ok(report)
+}
+
+#[cfg(all(test, feature = "babybear"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_describes_a_verified_proof() {
+        let report = prove_and_verify_with_report(FieldKind::BabyBear, 5).unwrap();
+        assert!(report.contains("modulus:     2013265921"));
+        assert!(report.contains("bits:        00000000000000000000000000000101"));
+        assert!(report.contains("constraints: all hold"));
+        assert!(report.contains("result:      verified"));
+    }
+
+    #[test]
+    fn report_stops_at_the_failing_constraint() {
+        let report = prove_and_verify_with_report(FieldKind::BabyBear, 2013265921).unwrap();
+        assert!(report.contains("fails on row 0"));
+        assert!(report.contains("not proven"));
+        assert!(!report.contains("proof size"));
+    }
+}
@@ -0,0 +1,243 @@
+use p3_keccak::Keccak256Hash;
+use p3_symmetric::CryptographicHasher;
+use p3_uni_stark::{Proof, StarkGenericConfig};
+
+use crate::config::FriParams;
+use crate::error::Error;
+use crate::field::{check_value_in_field, FieldKind};
+#[cfg(feature = "babybear")]
+use crate::babybear_v1;
+#[cfg(feature = "goldilocks")]
+use crate::goldilocks_v1;
+#[cfg(feature = "koalabear")]
+use crate::koalabear;
+#[cfg(feature = "mersenne31")]
+use crate::m31;
+
+// Proofs are encoded as the bincode serialization of `(FieldKind, FriParams, Proof)`, so the header
+// always comes first and can be read without knowing the proof type.
+pub fn serialize_tagged<SC: StarkGenericConfig>(
+    field: FieldKind,
+    params: &FriParams,
+    proof: &Proof<SC>,
+) -> Result<Vec<u8>, Error> {
+    bincode::serialize(&(field, params, proof)).map_err(|e| Error::Serialization(e.to_string()))
+}
+
+pub fn deserialize_tagged<SC: StarkGenericConfig>(bytes: &[u8]) -> Result<(FieldKind, FriParams, Proof<SC>), Error> {
+    bincode::deserialize(bytes).map_err(|e| Error::Serialization(e.to_string()))
+}
+
+// Reads only the header of a serialized proof.
+pub fn read_header(bytes: &[u8]) -> Result<(FieldKind, FriParams), Error> {
+    bincode::deserialize(bytes).map_err(|e| Error::Serialization(e.to_string()))
+}
+
+// Returns `Error::UnsupportedField` if the field's feature is disabled.
+pub fn default_params(field: FieldKind) -> Result<FriParams, Error> {
+    match field {
+        #[cfg(feature = "mersenne31")]
+        FieldKind::Mersenne31 => Ok(m31::DEFAULT_FRI_PARAMS),
+        #[cfg(feature = "babybear")]
+        FieldKind::BabyBear => Ok(babybear_v1::DEFAULT_FRI_PARAMS),
+        #[cfg(feature = "goldilocks")]
+        FieldKind::Goldilocks => Ok(goldilocks_v1::DEFAULT_FRI_PARAMS),
+        #[cfg(feature = "koalabear")]
+        FieldKind::KoalaBear => Ok(koalabear::DEFAULT_FRI_PARAMS),
+        #[allow(unreachable_patterns)]
+        _ => Err(Error::UnsupportedField(field)),
+    }
+}
+
+#[cfg(any(feature = "babybear", feature = "koalabear", feature = "mersenne31"))]
+pub(crate) fn to_u32(field: FieldKind, value: u64) -> Result<u32, Error> {
+    u32::try_from(value).map_err(|_| Error::ValueOutOfField { field, value })
+}
+
+/// Proves `value` is in range of `field` with the default FRI parameters and returns the tagged proof bytes.
+///
+/// BabyBear proofs are produced with `babybear_v1`.
+pub fn prove(field: FieldKind, value: u64) -> Result<Vec<u8>, Error> {
+    prove_with_params(field, value, &default_params(field)?)
+}
+
+/// Returns `Error::ValueOutOfField` for a value at or above the field's order, instead of proving a trace the AIR
+/// rejects.
+pub fn prove_with_params(field: FieldKind, value: u64, params: &FriParams) -> Result<Vec<u8>, Error> {
+    check_value_in_field(field, value)?;
+    match field {
+        #[cfg(feature = "mersenne31")]
+        FieldKind::Mersenne31 => {
+            serialize_tagged(field, params, &m31::prove_with_params(to_u32(field, value)?, params))
+        }
+        #[cfg(feature = "babybear")]
+        FieldKind::BabyBear => {
+            serialize_tagged(field, params, &babybear_v1::prove_with_params(to_u32(field, value)?, params))
+        }
+        #[cfg(feature = "goldilocks")]
+        FieldKind::Goldilocks => serialize_tagged(field, params, &goldilocks_v1::prove_with_params(value, params)),
+        #[cfg(feature = "koalabear")]
+        FieldKind::KoalaBear => {
+            serialize_tagged(field, params, &koalabear::prove_with_params(to_u32(field, value)?, params))
+        }
+        #[allow(unreachable_patterns)]
+        _ => Err(Error::UnsupportedField(field)),
+    }
+}
+
+/// Verifies tagged proof bytes for `value`, routing to the verifier of the field in the header and using
+/// the FRI parameters the prover recorded.
+///
+/// Only the bytes and the claimed value are needed, none of the verifiers build a trace or see the witness.
+///
+/// Returns `Error::FieldMismatch` if the proof was made for a different field than `expected`, and
+/// `Error::InsufficientSecurity` if the header parameters are below `MIN_SECURITY_BITS`, since a prover could
+/// otherwise pick a single query. Use `verify_with_params` to pin the exact parameters.
+pub fn verify(expected: FieldKind, bytes: &[u8], value: u64) -> Result<(), Error> {
+    let (_, params) = read_header(bytes)?;
+    params.check_security()?;
+    verify_with_params(expected, bytes, value, &params)
+}
+
+/// Like `verify`, but returns `Error::ParameterMismatch` if the proof wasn't made with `params`.
+pub fn verify_with_params(expected: FieldKind, bytes: &[u8], value: u64, params: &FriParams) -> Result<(), Error> {
+    let (found, found_params) = read_header(bytes)?;
+    if found != expected {
+        return Err(Error::FieldMismatch { expected, found });
+    }
+    if found_params != *params {
+        return Err(Error::ParameterMismatch {
+            expected: *params,
+            found: found_params,
+        });
+    }
+    check_value_in_field(found, value)?;
+
+    match found {
+        #[cfg(feature = "mersenne31")]
+        FieldKind::Mersenne31 => {
+            let (_, _, proof) = deserialize_tagged(bytes)?;
+            m31::verify_with_params(to_u32(found, value)?, &proof, params)
+        }
+        #[cfg(feature = "babybear")]
+        FieldKind::BabyBear => {
+            let (_, _, proof) = deserialize_tagged(bytes)?;
+            babybear_v1::verify_with_params(to_u32(found, value)?, &proof, params)
+        }
+        #[cfg(feature = "goldilocks")]
+        FieldKind::Goldilocks => {
+            let (_, _, proof) = deserialize_tagged(bytes)?;
+            goldilocks_v1::verify_with_params(value, &proof, params)
+        }
+        #[cfg(feature = "koalabear")]
+        FieldKind::KoalaBear => {
+            let (_, _, proof) = deserialize_tagged(bytes)?;
+            koalabear::verify_with_params(to_u32(found, value)?, &proof, params)
+        }
+        #[allow(unreachable_patterns)]
+        _ => Err(Error::UnsupportedField(found)),
+    }
+}
+
+/// Returns the first of `candidates` the tagged proof verifies for, for proofs sent without their value.
+///
+/// Every candidate costs a full verification, so this is meant for small candidate sets.
+pub fn which_value(expected: FieldKind, candidates: &[u64], bytes: &[u8]) -> Option<u64> {
+    candidates.iter().copied().find(|&value| verify(expected, bytes, value).is_ok())
+}
+
+/// Keccak-256 of the bincode serialization of `(field, params, value)`, everything a range proof attests to
+/// apart from the proof itself. Protocols can bind it into their own transcript, it doesn't depend on the
+/// proof bytes and is the same for every proof of the same statement.
+pub fn public_input_digest(field: FieldKind, value: u64, params: &FriParams) -> [u8; 32] {
+    let bytes = bincode::serialize(&(field, params, value)).expect("public inputs always serialize");
+    Keccak256Hash {}.hash_iter(bytes)
+}
+
+#[cfg(all(test, feature = "babybear"))]
+mod tests {
+    use super::*;
+    use crate::field::max_provable_value;
+
+    #[test]
+    fn mismatched_params_are_rejected() {
+        let params = babybear_v1::MINIMAL_FRI_PARAMS;
+        let bytes = prove_with_params(FieldKind::BabyBear, 100, &params).unwrap();
+
+        let result = verify_with_params(FieldKind::BabyBear, &bytes, 100, &babybear_v1::DEFAULT_FRI_PARAMS);
+        assert!(matches!(result, Err(Error::ParameterMismatch { .. })));
+
+        // The header carries the prover's parameters, so the plain verifier picks them up.
+        verify(FieldKind::BabyBear, &bytes, 100).unwrap();
+    }
+
+    #[test]
+    fn weak_header_params_are_rejected() {
+        let weak = FriParams {
+            log_blowup: 1,
+            num_queries: 1,
+            proof_of_work_bits: 0,
+        };
+        let bytes = prove_with_params(FieldKind::BabyBear, 100, &weak).unwrap();
+        assert!(matches!(verify(FieldKind::BabyBear, &bytes, 100), Err(Error::InsufficientSecurity { .. })));
+        assert_eq!(which_value(FieldKind::BabyBear, &[100], &bytes), None);
+        // Callers who chose the parameters themselves can still verify such a proof.
+        verify_with_params(FieldKind::BabyBear, &bytes, 100, &weak).unwrap();
+    }
+
+    #[test]
+    fn public_input_digest_covers_every_input() {
+        let params = babybear_v1::DEFAULT_FRI_PARAMS;
+        let digest = public_input_digest(FieldKind::BabyBear, 100, &params);
+        assert_eq!(digest, public_input_digest(FieldKind::BabyBear, 100, &params));
+        assert_ne!(digest, public_input_digest(FieldKind::BabyBear, 101, &params));
+        assert_ne!(digest, public_input_digest(FieldKind::Goldilocks, 100, &params));
+        let params = FriParams { num_queries: 50, ..params };
+        assert_ne!(digest, public_input_digest(FieldKind::BabyBear, 100, &params));
+    }
+
+    #[test]
+    fn which_value_finds_the_proven_value() {
+        let bytes = prove(FieldKind::BabyBear, 100).unwrap();
+        assert_eq!(which_value(FieldKind::BabyBear, &[1, 100, 1000], &bytes), Some(100));
+        assert_eq!(which_value(FieldKind::BabyBear, &[1, 1000], &bytes), None);
+    }
+
+    #[test]
+    fn values_outside_the_field_are_rejected_before_verifying() {
+        let bytes = prove(FieldKind::BabyBear, 100).unwrap();
+        let order = max_provable_value(FieldKind::BabyBear) as u64 + 1;
+        let result = verify(FieldKind::BabyBear, &bytes, order);
+        assert!(matches!(result, Err(Error::ValueOutOfField { value, .. }) if value == order));
+        assert!(matches!(verify(FieldKind::BabyBear, &bytes, u64::from(u32::MAX)), Err(Error::ValueOutOfField { .. })));
+    }
+
+    #[test]
+    fn values_outside_the_field_are_rejected_before_proving() {
+        for &field in FieldKind::all().iter().filter(|field| field.is_enabled()) {
+            let first_outside = u64::try_from(max_provable_value(field) + 1).unwrap_or(u64::MAX);
+            for value in [first_outside, u64::MAX] {
+                assert!(matches!(prove(field, value), Err(Error::ValueOutOfField { .. })), "{:?} {}", field, value);
+            }
+        }
+    }
+
+    #[test]
+    fn verify_needs_only_the_proof_bytes() {
+        // Proving happens on other threads, so nothing but the bytes reaches the verifier.
+        for &field in FieldKind::all().iter().filter(|field| field.is_enabled()) {
+            let value = 100;
+            let bytes = std::thread::spawn(move || prove(field, value).unwrap()).join().unwrap();
+            verify(field, &bytes, value).unwrap();
+            let result = verify(field, &bytes, value + 1);
+            assert!(matches!(result, Err(Error::VerificationFailed { field: f, value: 101, .. }) if f == field));
+        }
+    }
+
+    #[test]
+    fn disabled_fields_are_unsupported() {
+        for &field in FieldKind::all().iter().filter(|field| !field.is_enabled()) {
+            assert!(matches!(prove(field, 100), Err(Error::UnsupportedField(found)) if found == field));
+        }
+    }
+}
@@ -0,0 +1,129 @@
+use p3_air::{Air, AirBuilder, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, Field, PrimeField32};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::babybear_v1::{self, MyConfig, DEFAULT_FRI_PARAMS};
+use crate::bits::{reconstruct, write_bits};
+use crate::error::Error;
+use crate::field::FieldKind;
+
+// Values below `2^SHORT_BITS` are decomposed into `SHORT_BITS` bits, whose sum stays below the modulus
+// `2^31 - 2^27 + 1`, so the reconstruction can't wrap and no modulus boundary constraints are needed.
+// `2^31` is not enough, the values in `[p, 2^31)` need the boundary check of `babybear_v1`.
+pub const SHORT_BITS: usize = 30;
+
+// Range check for BabyBear values below `2^SHORT_BITS`, a single row of `SHORT_BITS` big endian bits with
+// only booleanity and reconstruction constraints.
+pub struct BabyBearShortRangeCheckAir {
+    pub value: u32,
+}
+
+impl<F: Field> BaseAir<F> for BabyBearShortRangeCheckAir {
+    fn width(&self) -> usize {
+        SHORT_BITS
+    }
+}
+
+impl<AB: AirBuilder> Air<AB> for BabyBearShortRangeCheckAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let current_row = main.row_slice(0);
+
+        // Making sure every bit is either 0 or 1
+        for &bit in current_row.iter() {
+            builder.assert_bool(bit);
+        }
+
+        // Assert if the reconstructed value matches the original value
+        builder.assert_eq(AB::Expr::from_wrapped_u32(self.value), reconstruct::<AB>(&current_row));
+    }
+}
+
+pub fn generate_trace<F: Field>(value: u32) -> RowMajorMatrix<F> {
+    let mut row = vec![F::zero(); SHORT_BITS];
+    write_bits(&mut row, u64::from(value));
+    RowMajorMatrix::new(row, SHORT_BITS)
+}
+
+/// A BabyBear range check proof from `prove_checked`, `Short` for values below `2^SHORT_BITS`.
+pub enum CheckedProof {
+    Short(Proof<MyConfig>),
+    Full(Proof<MyConfig>),
+}
+
+fn is_short(value: u32) -> bool {
+    value >> SHORT_BITS == 0
+}
+
+/// Proves that `value` is in range, with the short range AIR whenever the modulus boundary can't matter and
+/// `babybear_v1` otherwise.
+pub fn prove_checked(value: u32) -> Result<CheckedProof, Error> {
+    if value >= BabyBear::ORDER_U32 {
+        return Err(Error::ValueOutOfField { field: FieldKind::BabyBear, value: u64::from(value) });
+    }
+    if !is_short(value) {
+        return Ok(CheckedProof::Full(babybear_v1::prove(value)));
+    }
+
+    let air = BabyBearShortRangeCheckAir { value };
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<BabyBear>(value));
+    let config = babybear_v1::config(trace.height(), &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    let proof =
+        info_span!("commit_trace").in_scope(|| p3_uni_stark::prove(&config, &air, &mut challenger, trace, &vec![]));
+    Ok(CheckedProof::Short(proof))
+}
+
+// The verifier picks the AIR from the value too, so a short proof can't be passed off for a larger value.
+pub fn verify_checked(value: u32, proof: &CheckedProof) -> Result<(), Error> {
+    match proof {
+        CheckedProof::Short(proof) if is_short(value) => {
+            let config = babybear_v1::config(1, &DEFAULT_FRI_PARAMS);
+            let air = BabyBearShortRangeCheckAir { value };
+            p3_uni_stark::verify(&config, &air, &mut babybear_v1::challenger(), proof, &vec![])
+                .map_err(|e| Error::verification_failed(FieldKind::BabyBear, u64::from(value), e))
+        }
+        CheckedProof::Full(proof) if !is_short(value) => {
+            babybear_v1::verify(value, proof)
+        }
+        _ => Err(Error::InvalidInput(format!("proof doesn't match the AIR for {}", value))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::debug::satisfies;
+
+    #[test]
+    fn short_values_use_the_short_air() {
+        let proof = prove_checked(100).unwrap();
+        assert!(matches!(proof, CheckedProof::Short(_)));
+        verify_checked(100, &proof).unwrap();
+        assert!(verify_checked(101, &proof).is_err());
+        assert!(verify_checked(1 << SHORT_BITS, &proof).is_err());
+
+        let value = BabyBear::ORDER_U32 - 1;
+        let proof = prove_checked(value).unwrap();
+        assert!(matches!(proof, CheckedProof::Full(_)));
+        verify_checked(value, &proof).unwrap();
+    }
+
+    #[test]
+    fn short_air_constraints() {
+        let value = (1 << SHORT_BITS) - 1;
+        let air = BabyBearShortRangeCheckAir { value };
+        assert!(satisfies(&air, &generate_trace::<BabyBear>(value), &[]));
+        assert!(!satisfies(&air, &generate_trace::<BabyBear>(value - 1), &[]));
+    }
+
+    #[test]
+    fn values_above_the_modulus_are_rejected() {
+        assert!(matches!(prove_checked(BabyBear::ORDER_U32), Err(Error::ValueOutOfField { .. })));
+    }
+}
@@ -0,0 +1,232 @@
+use p3_air::{Air, AirBuilder, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, Field};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::babybear_v1::{self, MyConfig, DEFAULT_FRI_PARAMS};
+use crate::bits::{reconstruct, write_bits};
+use crate::error::Error;
+use crate::field::FieldKind;
+
+// Bit width of the values and the gaps. The verifier checks that the public values are below `2^SORTED_BITS`, so
+// an honest gap fits in the bits, while a decrease wraps around the modulus to at least `p - 2^29 > 2^30`.
+pub const SORTED_BITS: usize = 29;
+
+const REAL: usize = SORTED_BITS;
+const REMAINING: usize = SORTED_BITS + 1;
+const QUEUE: usize = SORTED_BITS + 2;
+
+// Proves `values[i] <= values[i + 1]`, or `<` if `strict`, one value per trace row:
+// | gap bits | real | remaining | queue |
+// The queue holds every public value on the first row and shifts by one per row, its head is the row's value.
+// On a real transition the gap is `next - value`, minus one if `strict`. `real` marks the first `count - 1` rows,
+// the ones with a successor, and `remaining` counts the real rows left, so that padding rows, up to the next power
+// of two, are skipped.
+// Public values are the values in order.
+pub struct BabyBearSortedAir {
+    pub count: usize,
+    pub strict: bool,
+}
+
+impl<F: Field> BaseAir<F> for BabyBearSortedAir {
+    fn width(&self) -> usize {
+        QUEUE + self.count
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for BabyBearSortedAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let next = main.row_slice(1);
+        let public_values = builder.public_values().to_vec();
+
+        let queue = &local[QUEUE..];
+        let next_queue = &next[QUEUE..];
+        let real = local[REAL];
+        let remaining = local[REMAINING];
+
+        // Making sure the gap bits and `real` are either 0 or 1
+        for &bit in local[..=REAL].iter() {
+            builder.assert_bool(bit);
+        }
+
+        // The queue starts out as the public values
+        for (&entry, &value) in queue.iter().zip(public_values.iter()) {
+            builder.when_first_row().assert_eq(entry, value);
+        }
+
+        // Exactly the first `count - 1` rows are real: `remaining` drops by one per real row to zero on the last
+        // row, and a real row is only ever preceded by real rows
+        builder.when_first_row().assert_eq(remaining, AB::Expr::from_canonical_usize(self.count - 1));
+        builder.when_transition().assert_eq(next[REMAINING], remaining - real);
+        builder.when_transition().assert_zero((AB::Expr::one() - real) * next[REAL]);
+        builder.when_last_row().assert_zero(remaining);
+        builder.when_last_row().assert_zero(real);
+
+        // The gap to the next value on real rows
+        let gap = reconstruct::<AB>(&local[..SORTED_BITS]);
+        let strict = AB::Expr::from_bool(self.strict);
+        builder.when_transition().when(real).assert_eq(gap, next_queue[0] - queue[0] - strict);
+
+        // Shift the queue by one, filling in zeros for the padding rows
+        let mut transition = builder.when_transition();
+        for i in 0..self.count - 1 {
+            transition.assert_eq(next_queue[i], queue[i + 1]);
+        }
+        transition.assert_zero(next_queue[self.count - 1]);
+    }
+}
+
+// Requires `values` to be sorted, strictly if `strict`.
+pub fn generate_trace<F: Field>(values: &[u32], strict: bool) -> RowMajorMatrix<F> {
+    let count = values.len();
+    let width = QUEUE + count;
+    let height = count.next_power_of_two();
+    let mut trace = RowMajorMatrix::new(vec![F::zero(); height * width], width);
+
+    for (i, row) in trace.values.chunks_mut(width).enumerate() {
+        if i + 1 < count {
+            write_bits(&mut row[..SORTED_BITS], u64::from(values[i + 1] - values[i] - u32::from(strict)));
+            row[REAL] = F::one();
+        }
+        row[REMAINING] = F::from_canonical_usize((count - 1).saturating_sub(i));
+        for (entry, j) in row[QUEUE..].iter_mut().zip(i..) {
+            *entry = F::from_canonical_u32(values.get(j).copied().unwrap_or(0));
+        }
+    }
+    trace
+}
+
+fn public_values(values: &[u32]) -> Result<Vec<BabyBear>, Error> {
+    if values.is_empty() {
+        return Err(Error::InvalidInput("no values to check".to_string()));
+    }
+    if let Some(&value) = values.iter().find(|&&value| value >> SORTED_BITS != 0) {
+        return Err(Error::InvalidInput(format!("{} doesn't fit in {} bits", value, SORTED_BITS)));
+    }
+    Ok(values.iter().map(|&value| BabyBear::from_canonical_u32(value)).collect())
+}
+
+fn prove(values: &[u32], strict: bool) -> Result<Proof<MyConfig>, Error> {
+    let public_values = public_values(values)?;
+    let in_order = |pair: &[u32]| if strict { pair[0] < pair[1] } else { pair[0] <= pair[1] };
+    if let Some(i) = values.windows(2).position(|pair| !in_order(pair)) {
+        return Err(Error::InvalidInput(format!("{} and {} at {} are out of order", values[i], values[i + 1], i)));
+    }
+
+    let air = BabyBearSortedAir { count: values.len(), strict };
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<BabyBear>(values, strict));
+    let config = babybear_v1::config(trace.height(), &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    Ok(info_span!("commit_trace")
+        .in_scope(|| p3_uni_stark::prove(&config, &air, &mut challenger, trace, &public_values)))
+}
+
+fn verify(values: &[u32], strict: bool, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    let public_values = public_values(values)?;
+    let air = BabyBearSortedAir { count: values.len(), strict };
+    let config = babybear_v1::config(1, &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    p3_uni_stark::verify(&config, &air, &mut challenger, proof, &public_values)
+        .map_err(|e| Error::verification_failed(FieldKind::BabyBear, u64::from(values[0]), e))
+}
+
+/// Proves that `values` never decrease, equal neighbours are allowed.
+pub fn prove_sorted(values: &[u32]) -> Result<Proof<MyConfig>, Error> {
+    prove(values, false)
+}
+
+/// Proves that `values` strictly increase.
+pub fn prove_strictly_sorted(values: &[u32]) -> Result<Proof<MyConfig>, Error> {
+    prove(values, true)
+}
+
+pub fn verify_sorted(values: &[u32], proof: &Proof<MyConfig>) -> Result<(), Error> {
+    verify(values, false, proof)
+}
+
+pub fn verify_strictly_sorted(values: &[u32], proof: &Proof<MyConfig>) -> Result<(), Error> {
+    verify(values, true, proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use p3_field::PrimeField32;
+
+    use crate::debug::satisfies;
+
+    fn satisfied(trace: &RowMajorMatrix<BabyBear>, values: &[u32], strict: bool) -> bool {
+        let air = BabyBearSortedAir { count: values.len(), strict };
+        satisfies(&air, trace, &public_values(values).unwrap())
+    }
+
+    #[test]
+    fn sorted_trace_satisfies_constraints() {
+        let values = [1, 1, 5, 9, 9, 20];
+        let trace = generate_trace::<BabyBear>(&values, false);
+        assert_eq!(trace.height(), 8);
+        assert!(satisfied(&trace, &values, false));
+        assert!(!satisfied(&trace, &values, true));
+
+        let values = [0, 1, 5];
+        assert!(satisfied(&generate_trace::<BabyBear>(&values, true), &values, true));
+        assert!(satisfied(&generate_trace::<BabyBear>(&[7], false), &[7], false));
+    }
+
+    #[test]
+    fn decreasing_neighbours_fail() {
+        // The honest trace with the gap of the decrease wrapped around the modulus, which doesn't fit in the bits
+        let values = [3, 9, 5];
+        let mut trace = generate_trace::<BabyBear>(&[3, 9, 9], false);
+        let width = trace.width();
+        let wrapped = BabyBear::from_canonical_u32(5) - BabyBear::from_canonical_u32(9);
+        write_bits(&mut trace.values[width..width + SORTED_BITS], u64::from(wrapped.as_canonical_u32()));
+        // The 5 moves through the queue from its last entry on the first row to the head on the third
+        for i in 0..3 {
+            trace.values[i * width + QUEUE + 2 - i] = BabyBear::from_canonical_u32(5);
+        }
+        assert!(!satisfied(&trace, &values, false));
+    }
+
+    #[test]
+    fn real_rows_cant_be_skipped() {
+        // Marking the decreasing transition and the ones after it as padding leaves `remaining` nonzero on the
+        // last row
+        let values = [3, 9, 5, 6];
+        let width = QUEUE + values.len();
+        let mut trace = RowMajorMatrix::new(vec![BabyBear::zero(); 4 * width], width);
+        for (i, row) in trace.values.chunks_mut(width).enumerate() {
+            if i == 0 {
+                write_bits(&mut row[..SORTED_BITS], 6);
+                row[REAL] = BabyBear::one();
+            }
+            row[REMAINING] = BabyBear::from_canonical_usize(if i == 0 { 3 } else { 2 });
+            for (entry, j) in row[QUEUE..].iter_mut().zip(i..) {
+                *entry = BabyBear::from_canonical_u32(values.get(j).copied().unwrap_or(0));
+            }
+        }
+        assert!(!satisfied(&trace, &values, false));
+    }
+
+    #[test]
+    fn sorted_values_prove_and_verify() {
+        let values = [2, 2, 4, 100];
+        let proof = prove_sorted(&values).unwrap();
+        verify_sorted(&values, &proof).unwrap();
+        assert!(verify_strictly_sorted(&values, &proof).is_err());
+        assert!(verify_sorted(&[2, 3, 4, 100], &proof).is_err());
+
+        let proof = prove_strictly_sorted(&[2, 3, 4]).unwrap();
+        verify_strictly_sorted(&[2, 3, 4], &proof).unwrap();
+        assert!(matches!(prove_strictly_sorted(&values), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_sorted(&[2, 1]), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_sorted(&[]), Err(Error::InvalidInput(_))));
+    }
+}
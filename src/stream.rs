@@ -0,0 +1,114 @@
+use std::io::{self, Read, Write};
+
+use crate::error::Error;
+use crate::field::FieldKind;
+use crate::serialize;
+
+// Every entry of a proof stream is `[length: u64 LE][value: u64 LE][proof]`, where `proof` is `length` bytes
+// of a tagged proof as produced by `serialize::prove`.
+pub fn write_entry<W: Write>(writer: &mut W, value: u64, proof: &[u8]) -> Result<(), Error> {
+    writer.write_all(&(proof.len() as u64).to_le_bytes())?;
+    writer.write_all(&value.to_le_bytes())?;
+    writer.write_all(proof)?;
+    Ok(())
+}
+
+// Reads a length prefix, or returns `None` if the stream ended cleanly before it.
+fn read_prefix<R: Read>(reader: &mut R) -> Result<Option<u64>, Error> {
+    let mut buf = [0u8; 8];
+    let mut read = 0;
+    while read < buf.len() {
+        match reader.read(&mut buf[read..]) {
+            Ok(0) if read == 0 => return Ok(None),
+            Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+            Ok(n) => read += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(Some(u64::from_le_bytes(buf)))
+}
+
+/// Iterator over the entries of a proof stream, yielding each claimed value once its proof verified.
+pub struct VerifyStream<R> {
+    reader: R,
+    expected: FieldKind,
+    // Set after an IO or framing error, since the rest of the stream can't be located anymore.
+    done: bool,
+}
+
+impl<R: Read> VerifyStream<R> {
+    fn next_entry(&mut self) -> Result<Option<(u64, Vec<u8>)>, Error> {
+        let Some(length) = read_prefix(&mut self.reader)? else {
+            return Ok(None);
+        };
+        let mut value = [0u8; 8];
+        self.reader.read_exact(&mut value)?;
+        let mut proof = Vec::new();
+        self.reader.by_ref().take(length).read_to_end(&mut proof)?;
+        if proof.len() as u64 != length {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        Ok(Some((u64::from_le_bytes(value), proof)))
+    }
+}
+
+impl<R: Read> Iterator for VerifyStream<R> {
+    type Item = Result<u64, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.next_entry() {
+            Ok(Some((value, proof))) => {
+                // A proof that fails to verify doesn't affect the framing, so the stream continues after it.
+                Some(serialize::verify(self.expected, &proof, value).map(|()| value))
+            }
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// Verifies the proofs of a stream written with `write_entry` one at a time, as they arrive.
+pub fn verify_stream<R: Read>(reader: R, expected: FieldKind) -> VerifyStream<R> {
+    VerifyStream { reader, expected, done: false }
+}
+
+#[cfg(all(test, feature = "babybear"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stream_verifies_every_entry() {
+        let mut stream = Vec::new();
+        for value in [1, 100] {
+            write_entry(&mut stream, value, &serialize::prove(FieldKind::BabyBear, value).unwrap()).unwrap();
+        }
+        // A proof for 5 claimed as 6
+        write_entry(&mut stream, 6, &serialize::prove(FieldKind::BabyBear, 5).unwrap()).unwrap();
+
+        let results = verify_stream(stream.as_slice(), FieldKind::BabyBear).collect::<Vec<_>>();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap(), &1);
+        assert_eq!(results[1].as_ref().unwrap(), &100);
+        assert!(matches!(results[2], Err(Error::VerificationFailed { value: 6, .. })));
+    }
+
+    #[test]
+    fn truncated_stream_ends_with_an_error() {
+        let mut stream = Vec::new();
+        write_entry(&mut stream, 1, &serialize::prove(FieldKind::BabyBear, 1).unwrap()).unwrap();
+        stream.truncate(stream.len() - 1);
+
+        let results = verify_stream(stream.as_slice(), FieldKind::BabyBear).collect::<Vec<_>>();
+        assert!(matches!(results.as_slice(), [Err(Error::Io(_))]));
+    }
+}
@@ -0,0 +1,186 @@
+use p3_air::{Air, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, Field};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::babybear_v1::{self, MyConfig, DEFAULT_FRI_PARAMS};
+use crate::bits::{reconstruct, write_bits};
+use crate::error::Error;
+use crate::field::FieldKind;
+
+// Largest supported bit width of the sum bound. Every row keeps `acc < 2^SUM_BITS` and `value < 2^SUM_BITS`,
+// so `acc + value < 2^30` can never wrap around the BabyBear modulus.
+pub const MAX_SUM_BITS: usize = 29;
+
+// Proves a batch of values sums to at most `S`, one value per row.
+// Every row has three big endian bit decompositions of `sum_bits` bits each:
+// | value | acc | slack |
+// `acc` is the running sum including the row's value, `slack` is `S - acc` and only set on the last row.
+// Public values are `[sum, S]`.
+pub struct BabyBearSumRangeCheckAir {
+    pub sum_bits: usize,
+}
+
+impl<F: Field> BaseAir<F> for BabyBearSumRangeCheckAir {
+    fn width(&self) -> usize {
+        3 * self.sum_bits
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for BabyBearSumRangeCheckAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+        let next = main.row_slice(1);
+        let n = self.sum_bits;
+
+        let public_values = builder.public_values();
+        let sum: AB::Expr = public_values[0].into();
+        let max_sum: AB::Expr = public_values[1].into();
+
+        // Making sure every bit is either 0 or 1
+        for &bit in local.iter() {
+            builder.assert_bool(bit);
+        }
+
+        let value = reconstruct::<AB>(&local[..n]);
+        let acc = reconstruct::<AB>(&local[n..2 * n]);
+        let slack = reconstruct::<AB>(&local[2 * n..]);
+        let next_value = reconstruct::<AB>(&next[..n]);
+        let next_acc = reconstruct::<AB>(&next[n..2 * n]);
+
+        // The accumulator starts at the first value and adds the next value on every transition
+        builder.when_first_row().assert_eq(acc.clone(), value);
+        builder.when_transition().assert_eq(next_acc, acc.clone() + next_value);
+
+        // The final accumulator is the public sum, and `S - sum` has to fit in `sum_bits` bits
+        builder.when_last_row().assert_eq(acc.clone(), sum);
+        builder.when_last_row().assert_eq(max_sum - acc, slack);
+    }
+}
+
+// The bit width the AIR uses for a given bound, both prover and verifier derive it from `S`.
+pub fn sum_bits(max_sum: u32) -> usize {
+    ((32 - max_sum.leading_zeros()) as usize).max(1)
+}
+
+pub fn generate_trace<F: Field>(values: &[u32], max_sum: u32) -> RowMajorMatrix<F> {
+    let n = sum_bits(max_sum);
+    let width = 3 * n;
+    // Padding rows hold a zero value, so the accumulator carries over unchanged to the last row
+    let height = values.len().next_power_of_two();
+    let mut trace = RowMajorMatrix::new(vec![F::zero(); height * width], width);
+
+    let mut acc = 0u64;
+    for (i, row) in trace.values.chunks_mut(width).enumerate() {
+        let value = values.get(i).copied().unwrap_or(0);
+        acc += u64::from(value);
+        write_bits(&mut row[..n], u64::from(value));
+        write_bits(&mut row[n..2 * n], acc);
+        if i == height - 1 {
+            write_bits(&mut row[2 * n..], u64::from(max_sum) - acc);
+        }
+    }
+    trace
+}
+
+fn check_bound(max_sum: u32) -> Result<(), Error> {
+    if sum_bits(max_sum) > MAX_SUM_BITS {
+        return Err(Error::InvalidInput(format!(
+            "sum bound {} doesn't fit in {} bits",
+            max_sum, MAX_SUM_BITS
+        )));
+    }
+    Ok(())
+}
+
+/// Proves that the sum of `values` is at most `max_sum`, returning the sum as a public output with the proof.
+pub fn prove(values: &[u32], max_sum: u32) -> Result<(u32, Proof<MyConfig>), Error> {
+    check_bound(max_sum)?;
+    if values.is_empty() {
+        return Err(Error::InvalidInput("no values to sum".to_string()));
+    }
+    let sum: u64 = values.iter().map(|&value| u64::from(value)).sum();
+    if sum > u64::from(max_sum) {
+        return Err(Error::InvalidInput(format!("sum {} is larger than {}", sum, max_sum)));
+    }
+    let sum = sum as u32;
+
+    let air = BabyBearSumRangeCheckAir { sum_bits: sum_bits(max_sum) };
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<BabyBear>(values, max_sum));
+    let config = babybear_v1::config(trace.height(), &DEFAULT_FRI_PARAMS);
+    let public_values = vec![BabyBear::from_canonical_u32(sum), BabyBear::from_canonical_u32(max_sum)];
+
+    let mut challenger = babybear_v1::challenger();
+    let proof = info_span!("commit_trace")
+        .in_scope(|| p3_uni_stark::prove(&config, &air, &mut challenger, trace, &public_values));
+    Ok((sum, proof))
+}
+
+pub fn verify(sum: u32, max_sum: u32, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    check_bound(max_sum)?;
+    let air = BabyBearSumRangeCheckAir { sum_bits: sum_bits(max_sum) };
+    let config = babybear_v1::config(1, &DEFAULT_FRI_PARAMS);
+    let public_values = vec![BabyBear::from_canonical_u32(sum), BabyBear::from_canonical_u32(max_sum)];
+
+    let mut challenger = babybear_v1::challenger();
+    p3_uni_stark::verify(&config, &air, &mut challenger, proof, &public_values)
+        .map_err(|e| Error::verification_failed(FieldKind::BabyBear, u64::from(sum), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::debug::satisfies;
+
+    #[test]
+    fn sums_within_the_bound_verify() {
+        let (sum, proof) = prove(&[10, 20, 30, 40], 100).unwrap();
+        assert_eq!(sum, 100);
+        verify(sum, 100, &proof).unwrap();
+    }
+
+    #[test]
+    fn sums_above_the_bound_are_rejected() {
+        assert!(matches!(prove(&[60, 41], 100), Err(Error::InvalidInput(_))));
+
+        // A proof for the same values under a larger bound doesn't verify for a smaller one
+        let (sum, proof) = prove(&[60, 41], 127).unwrap();
+        assert!(verify(sum, 100, &proof).is_err());
+    }
+
+    #[test]
+    fn wrong_public_sum_is_rejected() {
+        let (sum, proof) = prove(&[1, 2, 3, 4], 100).unwrap();
+        assert!(verify(sum + 1, 100, &proof).is_err());
+        assert!(verify(sum - 1, 100, &proof).is_err());
+    }
+
+    #[test]
+    fn value_counts_are_padded_to_a_power_of_two() {
+        let values = [7, 8, 9];
+        let trace = generate_trace::<BabyBear>(&values, 50);
+        assert_eq!(trace.height(), 4);
+        let air = BabyBearSumRangeCheckAir { sum_bits: sum_bits(50) };
+        let public_values = [BabyBear::from_canonical_u32(24), BabyBear::from_canonical_u32(50)];
+        assert!(satisfies(&air, &trace, &public_values));
+
+        let (sum, proof) = prove(&values, 50).unwrap();
+        assert_eq!(sum, 24);
+        verify(sum, 50, &proof).unwrap();
+    }
+
+    #[test]
+    fn bound_at_max_sum_bits_is_supported() {
+        let max_sum = (1 << MAX_SUM_BITS) - 1;
+        assert_eq!(sum_bits(max_sum), MAX_SUM_BITS);
+        let (sum, proof) = prove(&[max_sum - 1, 1], max_sum).unwrap();
+        verify(sum, max_sum, &proof).unwrap();
+
+        assert!(matches!(prove(&[1], max_sum + 1), Err(Error::InvalidInput(_))));
+        assert!(matches!(verify(sum, max_sum + 1, &proof), Err(Error::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,112 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Once;
+use std::time::Instant;
+
+use serde::Serialize;
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_forest::util::LevelFilter;
+use tracing_forest::ForestLayer;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Registry};
+
+// If set, span timings are appended to the file it names.
+pub const TIMINGS_ENV_VAR: &str = "RANGE_CHECK_TIMINGS";
+
+/// Wall time of a span and of the spans nested in it.
+#[derive(Clone, Debug, Serialize)]
+pub struct SpanTiming {
+    pub name: String,
+    pub duration_us: u64,
+    pub children: Vec<SpanTiming>,
+}
+
+// Stored in the extensions of an open span.
+struct OpenSpan {
+    start: Instant,
+    children: Vec<SpanTiming>,
+}
+
+/// Writes the timing tree of every top level span as one JSON line to `path` once the span closes.
+pub struct JsonTimingLayer {
+    path: PathBuf,
+}
+
+impl JsonTimingLayer {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    fn write(&self, timing: &SpanTiming) {
+        let result = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| writeln!(file, "{}", serde_json::to_string(timing).unwrap()));
+        // Timings are best effort, failing to write them must not fail the proof.
+        if let Err(e) = result {
+            eprintln!("failed to write span timings to {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+impl<S> Layer<S> for JsonTimingLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, _attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(OpenSpan { start: Instant::now(), children: vec![] });
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(&id) else {
+            return;
+        };
+        let Some(open) = span.extensions_mut().remove::<OpenSpan>() else {
+            return;
+        };
+        let timing = SpanTiming {
+            name: span.name().to_string(),
+            duration_us: open.start.elapsed().as_micros() as u64,
+            children: open.children,
+        };
+        match span.parent() {
+            Some(parent) => {
+                if let Some(parent) = parent.extensions_mut().get_mut::<OpenSpan>() {
+                    parent.children.push(timing);
+                }
+            }
+            None => self.write(&timing),
+        }
+    }
+}
+
+/// The JSON timing layer if `RANGE_CHECK_TIMINGS` is set, to be added next to the forest layer.
+pub fn layer_from_env() -> Option<JsonTimingLayer> {
+    std::env::var_os(TIMINGS_ENV_VAR).map(JsonTimingLayer::new)
+}
+
+static INIT_TRACING: Once = Once::new();
+
+/// Installs the global subscriber every `prove_and_verify` logs to, the forest layer filtered by `RUST_LOG` and the
+/// timing layer. Only the first call in a process does anything, and a subscriber the caller installed before
+/// is kept.
+pub fn init_tracing() {
+    INIT_TRACING.call_once(|| {
+        let env_filter = EnvFilter::builder()
+            .with_default_directive(LevelFilter::INFO.into())
+            .from_env_lossy();
+
+        let _ = Registry::default()
+            .with(env_filter)
+            .with(ForestLayer::default())
+            .with(layer_from_env())
+            .try_init();
+    });
+}
@@ -0,0 +1,132 @@
+use p3_air::{Air, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, Field, PrimeField32};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::babybear_v1::{self, eval_value_columns, value_columns, MyConfig, DEFAULT_FRI_PARAMS, WIDTH};
+use crate::bits::reconstruct;
+use crate::error::Error;
+use crate::field::FieldKind;
+
+// Largest top byte of a canonical value.
+pub const MAX_TAG: u8 = (BabyBear::ORDER_U32 >> 24) as u8;
+
+// Proves `value >> 24 == tag` for a value below the modulus, with the single row of `babybear_v1`:
+// | 32 bits of value in big endian format | AND columns |
+// The top byte is the first 8 bits. Since `p = 0x78000001`, no canonical value has a top byte above `0x78`, and
+// `0x78` only `0x78000000`.
+// Public values are `[value, tag]`, and have to match the statement the AIR is built for.
+pub struct BabyBearTopByteAir {
+    pub value: u32,
+    pub tag: u8,
+}
+
+impl<F: Field> BaseAir<F> for BabyBearTopByteAir {
+    fn width(&self) -> usize {
+        WIDTH
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for BabyBearTopByteAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let current_row = main.row_slice(0);
+
+        let public_values = builder.public_values();
+        let value: AB::Expr = public_values[0].into();
+        let tag: AB::Expr = public_values[1].into();
+
+        builder.assert_eq(value.clone(), AB::Expr::from_canonical_u32(self.value));
+        builder.assert_eq(tag.clone(), AB::Expr::from_canonical_u8(self.tag));
+
+        // Booleanity, the modulus boundary and the reconstruction
+        let reconstructed_value = eval_value_columns(builder, &current_row);
+        builder.assert_eq(value, reconstructed_value);
+
+        builder.assert_eq(tag, reconstruct::<AB>(&current_row[..8]));
+    }
+}
+
+pub fn generate_trace<F: Field>(value: u32) -> RowMajorMatrix<F> {
+    RowMajorMatrix::new(value_columns(value), WIDTH)
+}
+
+fn public_values(value: u32, tag: u8) -> Vec<BabyBear> {
+    vec![BabyBear::from_canonical_u32(value), BabyBear::from_canonical_u8(tag)]
+}
+
+fn check_tag(tag: u8) -> Result<(), Error> {
+    if tag > MAX_TAG {
+        return Err(Error::InvalidInput(format!("no BabyBear element has the top byte {:#x}", tag)));
+    }
+    Ok(())
+}
+
+/// Proves that `value` is a canonical BabyBear element whose top byte is `tag`.
+pub fn prove_top_byte(value: u32, tag: u8) -> Result<Proof<MyConfig>, Error> {
+    check_tag(tag)?;
+    babybear_v1::try_generate_trace::<BabyBear>(value)?;
+    if value >> 24 != u32::from(tag) {
+        return Err(Error::InvalidInput(format!("the top byte of {:#x} isn't {:#x}", value, tag)));
+    }
+
+    let air = BabyBearTopByteAir { value, tag };
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<BabyBear>(value));
+    let config = babybear_v1::config(trace.height(), &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    Ok(info_span!("commit_trace")
+        .in_scope(|| p3_uni_stark::prove(&config, &air, &mut challenger, trace, &public_values(value, tag))))
+}
+
+pub fn verify_top_byte(value: u32, tag: u8, proof: &Proof<MyConfig>) -> Result<(), Error> {
+    check_tag(tag)?;
+    babybear_v1::try_generate_trace::<BabyBear>(value)?;
+    let air = BabyBearTopByteAir { value, tag };
+    let config = babybear_v1::config(1, &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    p3_uni_stark::verify(&config, &air, &mut challenger, proof, &public_values(value, tag))
+        .map_err(|e| Error::verification_failed(FieldKind::BabyBear, u64::from(value), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::debug::satisfies;
+
+    fn satisfied(value: u32, tag: u8) -> bool {
+        let air = BabyBearTopByteAir { value, tag };
+        satisfies(&air, &generate_trace::<BabyBear>(value), &public_values(value, tag))
+    }
+
+    #[test]
+    fn top_byte_has_to_match_the_tag() {
+        assert!(satisfied(0x12345678, 0x12));
+        assert!(satisfied(0xff, 0));
+        assert!(satisfied(0x78000000, MAX_TAG));
+        assert!(!satisfied(0x12345678, 0x13));
+        assert!(!satisfied(0x12345678, 0x34));
+    }
+
+    #[test]
+    fn tags_above_the_modulus_are_rejected() {
+        assert_eq!(MAX_TAG, 0x78);
+        assert!(matches!(prove_top_byte(0x79000000, 0x79), Err(Error::InvalidInput(_))));
+        // Every value with the top byte `0x78` but `0x78000000` is at or above the modulus
+        assert!(matches!(prove_top_byte(0x78000001, 0x78), Err(Error::ValueOutOfField { .. })));
+    }
+
+    #[test]
+    fn proof_is_bound_to_the_value_and_tag() {
+        let proof = prove_top_byte(0x12345678, 0x12).unwrap();
+        verify_top_byte(0x12345678, 0x12, &proof).unwrap();
+        assert!(verify_top_byte(0x12345678, 0x13, &proof).is_err());
+        assert!(verify_top_byte(0x12345679, 0x12, &proof).is_err());
+        assert!(matches!(prove_top_byte(0x12345678, 0x11), Err(Error::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,91 @@
+use std::fs;
+use std::path::Path;
+
+use p3_field::PrimeField64;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+
+use crate::error::Error;
+use crate::field::FieldKind;
+#[cfg(any(feature = "mersenne31", feature = "babybear", feature = "koalabear"))]
+use crate::serialize::to_u32;
+
+/// The trace as CSV, one line per row with every cell as its canonical integer.
+///
+/// The header names the first `bits` columns `bit_0` to `bit_{bits - 1}`, most significant first like the trace, and
+/// the columns after them, the AND columns of the modulus boundary, `and_0` and up.
+pub fn trace_csv<F: PrimeField64>(trace: &RowMajorMatrix<F>, bits: usize) -> String {
+    let header = (0..trace.width())
+        .map(|i| if i < bits { format!("bit_{}", i) } else { format!("and_{}", i - bits) })
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let mut csv = header + "\n";
+    for row in trace.rows() {
+        let cells = row.map(|cell| cell.as_canonical_u64().to_string()).collect::<Vec<_>>();
+        csv += &cells.join(",");
+        csv += "\n";
+    }
+    csv
+}
+
+/// Writes the range check trace of `value` over `field` to `path` as CSV, see `trace_csv`.
+///
+/// The trace is written as generated, also for values the AIR rejects, so that a failing witness can be inspected.
+pub fn dump_trace_csv(field: FieldKind, value: u64, path: impl AsRef<Path>) -> Result<(), Error> {
+    let bits = field.bit_width();
+    let csv = match field {
+        #[cfg(feature = "mersenne31")]
+        FieldKind::Mersenne31 => {
+            let trace = crate::m31::generate_mersenne31_trace::<p3_mersenne_31::Mersenne31>(to_u32(field, value)?);
+            trace_csv(&trace, bits)
+        }
+        #[cfg(feature = "babybear")]
+        FieldKind::BabyBear => {
+            let trace = crate::babybear_v1::generate_trace::<p3_baby_bear::BabyBear>(to_u32(field, value)?);
+            trace_csv(&trace, bits)
+        }
+        #[cfg(feature = "goldilocks")]
+        FieldKind::Goldilocks => {
+            trace_csv(&crate::goldilocks_v1::generate_trace::<p3_goldilocks::Goldilocks>(value), bits)
+        }
+        #[cfg(feature = "koalabear")]
+        FieldKind::KoalaBear => {
+            let trace = crate::koalabear::generate_trace::<p3_koala_bear::KoalaBear>(to_u32(field, value)?);
+            trace_csv(&trace, bits)
+        }
+        #[allow(unreachable_patterns)]
+        _ => return Err(Error::UnsupportedField(field)),
+    };
+    fs::write(path, csv)?;
+    Ok(())
+}
+
+#[cfg(all(test, feature = "babybear", feature = "mersenne31"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_has_a_line_per_row_and_a_cell_per_column() {
+        let path = std::env::temp_dir().join(format!("plonky3_rangecheck_{}_trace.csv", std::process::id()));
+        dump_trace_csv(FieldKind::BabyBear, 5, &path).unwrap();
+        let csv = fs::read_to_string(&path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        let lines = csv.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("bit_0,bit_1,") && lines[0].contains("bit_31,and_0"));
+        let cells = lines[1].split(',').collect::<Vec<_>>();
+        assert_eq!(cells.len(), crate::babybear_v1::WIDTH);
+        // 5 is 101 in the last bits
+        assert_eq!(cells[29..32], ["1", "0", "1"]);
+    }
+
+    #[test]
+    fn padding_rows_are_included() {
+        let trace = crate::m31::generate_mersenne31_trace::<p3_mersenne_31::Mersenne31>(1);
+        let csv = trace_csv(&trace, 32);
+        assert_eq!(csv.lines().count(), 1 + trace.height());
+        assert!(csv.contains("and_28"));
+    }
+}
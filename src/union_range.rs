@@ -0,0 +1,177 @@
+use p3_air::{Air, AirBuilderWithPublicValues, BaseAir};
+use p3_baby_bear::BabyBear;
+use p3_field::{AbstractField, Field};
+use p3_matrix::Matrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_uni_stark::Proof;
+use tracing::info_span;
+
+use crate::babybear_v1::{self, MyConfig, DEFAULT_FRI_PARAMS};
+use crate::bits::{reconstruct, write_bits};
+use crate::error::Error;
+use crate::field::FieldKind;
+
+// Bit width of the slacks to the bounds of the chosen range. Inputs are below `2^UNION_BITS`, so an honest slack
+// fits in the bits, while a value outside the chosen range gives a slack that wraps around the modulus to at least
+// `p - 2^29 > 2^30` and can't be decomposed.
+pub const UNION_BITS: usize = 29;
+
+const WIDTH: usize = 1 + 2 * UNION_BITS;
+
+// Proves `value` in `[a, b]` or in `[c, d]`, with a single row:
+// | second | lower slack bits | upper slack bits |
+// `second` selects the range, 0 for `[a, b]` and 1 for `[c, d]`. The slacks are `value - lo` and `hi - value` for
+// the bounds `lo` and `hi` of the selected range.
+// Public values are `[value, a, b, c, d]`.
+pub struct BabyBearUnionRangeAir;
+
+impl<F: Field> BaseAir<F> for BabyBearUnionRangeAir {
+    fn width(&self) -> usize {
+        WIDTH
+    }
+}
+
+impl<AB: AirBuilderWithPublicValues> Air<AB> for BabyBearUnionRangeAir {
+    fn eval(&self, builder: &mut AB) {
+        let main = builder.main();
+        let local = main.row_slice(0);
+
+        let public_values = builder.public_values();
+        let [value, a, b, c, d] = core::array::from_fn(|i| -> AB::Expr { public_values[i].into() });
+
+        // Making sure the selector and every bit are either 0 or 1
+        for &bit in local.iter() {
+            builder.assert_bool(bit);
+        }
+
+        let second: AB::Expr = local[0].into();
+        let first = AB::Expr::one() - second.clone();
+        let lo = first.clone() * a + second.clone() * c;
+        let hi = first * b + second * d;
+
+        let lower_slack = reconstruct::<AB>(&local[1..1 + UNION_BITS]);
+        let upper_slack = reconstruct::<AB>(&local[1 + UNION_BITS..]);
+        builder.assert_eq(lower_slack, value.clone() - lo);
+        builder.assert_eq(upper_slack, hi - value);
+    }
+}
+
+// Requires `value` to be in one of the ranges.
+pub fn generate_trace<F: Field>(value: u32, ranges: [(u32, u32); 2]) -> RowMajorMatrix<F> {
+    let second = !(ranges[0].0..=ranges[0].1).contains(&value);
+    let (lo, hi) = ranges[usize::from(second)];
+
+    let mut row = vec![F::zero(); WIDTH];
+    row[0] = F::from_bool(second);
+    write_bits(&mut row[1..1 + UNION_BITS], u64::from(value - lo));
+    write_bits(&mut row[1 + UNION_BITS..], u64::from(hi - value));
+    RowMajorMatrix::new(row, WIDTH)
+}
+
+fn public_values(value: u32, ranges: [(u32, u32); 2]) -> Vec<BabyBear> {
+    let [(a, b), (c, d)] = ranges;
+    [value, a, b, c, d].into_iter().map(BabyBear::from_canonical_u32).collect()
+}
+
+// The ranges have to be ordered and disjoint, `a <= b < c <= d`. They may touch, `b + 1 == c`.
+fn check_inputs(value: u32, ranges: [(u32, u32); 2]) -> Result<(), Error> {
+    let [(a, b), (c, d)] = ranges;
+    for input in [value, a, b, c, d] {
+        if input >> UNION_BITS != 0 {
+            return Err(Error::InvalidInput(format!("{} doesn't fit in {} bits", input, UNION_BITS)));
+        }
+    }
+    if !(a <= b && b < c && c <= d) {
+        return Err(Error::InvalidInput(format!("[{}, {}] and [{}, {}] aren't disjoint and ordered", a, b, c, d)));
+    }
+    Ok(())
+}
+
+/// Proves that `value` lies in `[a, b]` or `[c, d]` for `ranges` `[(a, b), (c, d)]`, without revealing which.
+pub fn prove_union_range(value: u32, ranges: [(u32, u32); 2]) -> Result<Proof<MyConfig>, Error> {
+    check_inputs(value, ranges)?;
+    if !ranges.iter().any(|&(lo, hi)| (lo..=hi).contains(&value)) {
+        return Err(Error::InvalidInput(format!("{} is in neither range", value)));
+    }
+
+    let trace = info_span!("generate_trace").in_scope(|| generate_trace::<BabyBear>(value, ranges));
+    let config = babybear_v1::config(trace.height(), &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    Ok(info_span!("commit_trace").in_scope(|| {
+        p3_uni_stark::prove(&config, &BabyBearUnionRangeAir, &mut challenger, trace, &public_values(value, ranges))
+    }))
+}
+
+pub fn verify_union_range(value: u32, ranges: [(u32, u32); 2], proof: &Proof<MyConfig>) -> Result<(), Error> {
+    check_inputs(value, ranges)?;
+    let config = babybear_v1::config(1, &DEFAULT_FRI_PARAMS);
+
+    let mut challenger = babybear_v1::challenger();
+    p3_uni_stark::verify(&config, &BabyBearUnionRangeAir, &mut challenger, proof, &public_values(value, ranges))
+        .map_err(|e| Error::verification_failed(FieldKind::BabyBear, u64::from(value), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::debug::{babybear_difference, satisfies};
+
+    const RANGES: [(u32, u32); 2] = [(10, 20), (30, 40)];
+
+    // The trace for any claim that the value is in the selected range, the slacks of a value outside it wrap.
+    fn satisfied_forged(value: u32, second: bool, ranges: [(u32, u32); 2]) -> bool {
+        let (lo, hi) = ranges[usize::from(second)];
+        let mut row = vec![BabyBear::zero(); WIDTH];
+        row[0] = BabyBear::from_bool(second);
+        write_bits(&mut row[1..1 + UNION_BITS], babybear_difference(value, lo));
+        write_bits(&mut row[1 + UNION_BITS..], babybear_difference(hi, value));
+        let trace = RowMajorMatrix::new(row, WIDTH);
+        satisfies(&BabyBearUnionRangeAir, &trace, &public_values(value, ranges))
+    }
+
+    #[test]
+    fn values_in_either_range_pass() {
+        for value in [10, 15, 20] {
+            assert!(satisfied_forged(value, false, RANGES));
+        }
+        for value in [30, 35, 40] {
+            assert!(satisfied_forged(value, true, RANGES));
+        }
+    }
+
+    #[test]
+    fn values_outside_the_chosen_range_fail() {
+        for value in [9, 21, 25, 29, 41] {
+            assert!(!satisfied_forged(value, false, RANGES), "{} in the first range", value);
+            assert!(!satisfied_forged(value, true, RANGES), "{} in the second range", value);
+        }
+        // In the other range than the selected one
+        assert!(!satisfied_forged(15, true, RANGES));
+        assert!(!satisfied_forged(35, false, RANGES));
+    }
+
+    #[test]
+    fn non_boolean_selector_fails() {
+        // A selector of 2 turns the bounds into `2c - a` and `2d - b`, 50 and 60 here
+        let mut row = vec![BabyBear::zero(); WIDTH];
+        row[0] = BabyBear::two();
+        write_bits(&mut row[1 + UNION_BITS..], 10);
+        let trace = RowMajorMatrix::new(row, WIDTH);
+        assert!(!satisfies(&BabyBearUnionRangeAir, &trace, &public_values(50, RANGES)));
+    }
+
+    #[test]
+    fn touching_ranges_prove_and_verify() {
+        let ranges = [(10, 20), (21, 30)];
+        for value in [10, 20, 21, 30] {
+            let proof = prove_union_range(value, ranges).unwrap();
+            verify_union_range(value, ranges, &proof).unwrap();
+        }
+        let proof = prove_union_range(20, ranges).unwrap();
+        assert!(verify_union_range(22, ranges, &proof).is_err());
+        assert!(matches!(prove_union_range(25, RANGES), Err(Error::InvalidInput(_))));
+        assert!(matches!(prove_union_range(15, [(10, 20), (20, 30)]), Err(Error::InvalidInput(_))));
+    }
+}
@@ -0,0 +1,92 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+use crate::field::FieldKind;
+use crate::serialize;
+
+// A conformance test vector, `proof` is the tagged proof bytes produced by `serialize::prove`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestVector {
+    pub field: FieldKind,
+    pub value: u64,
+    pub proof: Vec<u8>,
+}
+
+// Proofs don't use any randomness, so the same inputs always give the same vectors.
+pub fn generate_test_vectors(inputs: &[(FieldKind, u64)]) -> Result<Vec<TestVector>, Error> {
+    inputs
+        .iter()
+        .map(|&(field, value)| {
+            Ok(TestVector {
+                field,
+                value,
+                proof: serialize::prove(field, value)?,
+            })
+        })
+        .collect()
+}
+
+pub fn write_test_vectors(path: impl AsRef<Path>, vectors: &[TestVector]) -> Result<(), Error> {
+    let bytes = bincode::serialize(vectors).map_err(|e| Error::Serialization(e.to_string()))?;
+    fs::write(path, bytes)?;
+    Ok(())
+}
+
+/// Loads the test vectors at `path` and verifies every one of them, returning how many were verified.
+pub fn verify_test_vectors(path: impl AsRef<Path>) -> Result<usize, Error> {
+    let bytes = fs::read(path)?;
+    let vectors: Vec<TestVector> = bincode::deserialize(&bytes).map_err(|e| Error::Serialization(e.to_string()))?;
+    for vector in &vectors {
+        serialize::verify(vector.field, &vector.proof, vector.value)?;
+    }
+    Ok(vectors.len())
+}
+
+#[cfg(all(test, feature = "babybear", feature = "goldilocks", feature = "mersenne31"))]
+mod tests {
+    use super::*;
+
+    const VECTORS_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/vectors.bin");
+
+    fn inputs() -> Vec<(FieldKind, u64)> {
+        vec![
+            (FieldKind::Mersenne31, 0),
+            (FieldKind::Mersenne31, 100),
+            (FieldKind::BabyBear, 100),
+            (FieldKind::BabyBear, (1 << 31) - (1 << 27)),
+            (FieldKind::Goldilocks, 100),
+            (FieldKind::Goldilocks, u64::MAX - (1 << 32) + 1),
+        ]
+    }
+
+    // Proofs written before a change to the proof format stop verifying, unlike freshly generated ones.
+    #[test]
+    fn committed_test_vectors_verify() {
+        assert_eq!(verify_test_vectors(VECTORS_PATH).unwrap(), inputs().len());
+    }
+
+    #[test]
+    fn written_test_vectors_verify() {
+        let path = std::env::temp_dir().join(format!("plonky3_rangecheck_{}_vectors.bin", std::process::id()));
+        write_test_vectors(&path, &generate_test_vectors(&inputs()).unwrap()).unwrap();
+        let verified = verify_test_vectors(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(verified.unwrap(), inputs().len());
+    }
+
+    #[test]
+    fn test_vectors_are_deterministic() {
+        let vectors = generate_test_vectors(&inputs()[..1]).unwrap();
+        assert_eq!(vectors, generate_test_vectors(&inputs()[..1]).unwrap());
+    }
+
+    // Run with `cargo test regenerate_test_vectors -- --ignored` after an intentional proof format change.
+    #[test]
+    #[ignore]
+    fn regenerate_test_vectors() {
+        write_test_vectors(VECTORS_PATH, &generate_test_vectors(&inputs()).unwrap()).unwrap();
+    }
+}